@@ -0,0 +1,46 @@
+-- Full-text search documents, weighted so that matches in titles rank above
+-- matches in list descriptions, which rank above matches in archived pages.
+-- The search queries need to use these exact functions for the indexes below
+-- to be picked up.
+
+create function bookmarks_search_vector(title text)
+    returns tsvector
+    language sql
+    immutable
+    parallel safe
+as $$
+    select setweight(to_tsvector('english', title), 'A')
+$$;
+
+create function lists_search_vector(title text, content text)
+    returns tsvector
+    language sql
+    immutable
+    parallel safe
+as $$
+    select setweight(to_tsvector('english', title), 'A')
+        || setweight(to_tsvector('english', coalesce(content, '')), 'B')
+$$;
+
+-- tsvectors are limited to 1MB, so we only index the beginning of large pages.
+-- The default parser skips html tags, so we don't need to strip them here.
+create function archives_search_vector(extracted_html text)
+    returns tsvector
+    language sql
+    immutable
+    parallel safe
+as $$
+    select setweight(to_tsvector('english', left(coalesce(extracted_html, ''), 250000)), 'C')
+$$;
+
+create index bookmarks_search_idx
+    on bookmarks
+    using gin (bookmarks_search_vector(title));
+
+create index lists_search_idx
+    on lists
+    using gin (lists_search_vector(title, content));
+
+create index archives_search_idx
+    on archives
+    using gin (archives_search_vector(extracted_html));
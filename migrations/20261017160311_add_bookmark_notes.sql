@@ -0,0 +1,21 @@
+alter table bookmarks add column notes text default null;
+
+-- Include notes in the full-text search for bookmarks, with the same weight as
+-- list descriptions.
+drop index bookmarks_search_idx;
+
+create function bookmarks_search_vector(title text, notes text)
+    returns tsvector
+    language sql
+    immutable
+    parallel safe
+as $$
+    select setweight(to_tsvector('english', title), 'A')
+        || setweight(to_tsvector('english', coalesce(notes, '')), 'B')
+$$;
+
+create index bookmarks_search_idx
+    on bookmarks
+    using gin (bookmarks_search_vector(title, notes));
+
+drop function bookmarks_search_vector(text);
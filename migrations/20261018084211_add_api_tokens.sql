@@ -0,0 +1,27 @@
+create type api_token_scope as enum ('Read', 'Write');
+
+create table api_tokens (
+    id uuid primary key
+        default gen_random_uuid()
+        not null,
+
+    user_id uuid
+        references users(id)
+        on delete cascade
+        not null,
+
+    name varchar(100)
+        not null,
+    scope api_token_scope
+        not null,
+    -- SHA-256 hash of the token, which is only shown once after creating it
+    token_hash text
+        unique
+        not null,
+
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    last_used_at timestamp with time zone
+        default null
+);
@@ -0,0 +1,47 @@
+create table feed_subscriptions (
+    id uuid primary key
+        default gen_random_uuid()
+        not null,
+
+    list_id uuid
+        references lists(id)
+        on delete cascade
+        not null,
+
+    url text
+        not null,
+    -- Whether to archive the bookmarks created for new feed entries
+    archive boolean
+        default true
+        not null,
+
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    last_polled_at timestamp with time zone
+        default null,
+    -- Why the last poll failed, shown to the user
+    last_error text
+        default null,
+
+    unique (list_id, url)
+);
+
+-- Entries we've already seen for a subscription, so that we add each of them
+-- to the list only once
+create table feed_subscription_entries (
+    subscription_id uuid
+        references feed_subscriptions(id)
+        on delete cascade
+        not null,
+
+    -- The entry's GUID, or its URL if it doesn't have one
+    entry_key text
+        not null,
+
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+
+    primary key (subscription_id, entry_key)
+);
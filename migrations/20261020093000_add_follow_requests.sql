@@ -0,0 +1,5 @@
+-- Follows sent to other servers only count once they've been accepted
+alter table follows
+    add column accepted boolean
+        default true
+        not null;
@@ -0,0 +1,21 @@
+-- Users can hide the bookmarks of people they follow from their timeline
+-- without unfollowing them
+create table mutes (
+    id uuid primary key
+        default gen_random_uuid()
+        not null,
+    muter_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    muted_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+
+    unique (muter_id, muted_id)
+);
@@ -0,0 +1,39 @@
+-- Public lists are federated as ActivityPub group actors, which need their
+-- own keypair to sign activities. Keys are generated when a list is first
+-- federated.
+create table list_actors (
+    list_id uuid primary key
+        references lists(id)
+        on delete cascade
+        not null,
+
+    public_key text
+        not null,
+    private_key text
+        not null,
+
+    created_at timestamp with time zone
+        default current_timestamp
+        not null
+);
+
+-- Users on other servers following a public list
+create table list_followers (
+    id uuid primary key
+        default gen_random_uuid()
+        not null,
+    list_id uuid
+        references lists(id)
+        on delete cascade
+        not null,
+    follower_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+
+    unique (list_id, follower_id)
+);
@@ -0,0 +1,27 @@
+-- Pending archives waiting to be processed by the archive queue.
+-- Workers claim jobs by moving `next_attempt_at` into the future, so that jobs
+-- of crashed workers are picked up again later.
+create table archive_jobs (
+    archive_id uuid primary key
+        references archives(id) on delete cascade
+        not null,
+
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+
+    attempts integer
+        default 0
+        not null,
+    next_attempt_at timestamp with time zone
+        default current_timestamp
+        not null,
+    last_error json
+        default null
+);
+
+create index archive_jobs_next_attempt_at_idx on archive_jobs (next_attempt_at);
+
+insert into archive_jobs (archive_id)
+select id from archives
+where status = 'Pending';
@@ -0,0 +1,11 @@
+-- Keep every archived snapshot of a bookmark, instead of only the last one.
+alter table archives drop constraint archives_bookmark_id_key;
+
+create index archives_bookmark_id_created_at_idx on archives (bookmark_id, created_at desc);
+
+-- The newest snapshot of each bookmark, which is the one shown and searched by
+-- default.
+create view latest_archives as
+select distinct on (bookmark_id) *
+from archives
+order by bookmark_id, created_at desc, id desc;
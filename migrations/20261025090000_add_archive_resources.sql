@@ -0,0 +1,32 @@
+-- Images referenced by archived pages. Each file is stored once, identified by
+-- the SHA-256 hash of its contents.
+create table archive_resources (
+    hash text primary key
+        not null,
+
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+
+    content_type text
+        not null,
+    size bigint
+        not null,
+    -- null if the file is stored in the configured resources directory
+    data bytea
+        default null
+);
+
+-- Which archives use which resources, so unused ones can be deleted.
+create table archive_resource_uses (
+    archive_id uuid
+        references archives(id) on delete cascade
+        not null,
+    resource_hash text
+        references archive_resources(hash) on delete cascade
+        not null,
+
+    primary key (archive_id, resource_hash)
+);
+
+create index archive_resource_uses_resource_hash_idx on archive_resource_uses (resource_hash);
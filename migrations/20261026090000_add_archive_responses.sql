@@ -0,0 +1,24 @@
+-- The response of the website as it was fetched, stored for exporting archives
+-- as WARC files if the server is configured to keep it.
+create table archive_responses (
+    archive_id uuid primary key
+        references archives(id) on delete cascade
+        not null,
+
+    -- after following redirects
+    url text
+        not null,
+    status integer
+        not null,
+    -- list of [name, value] pairs, in the order the website sent them
+    headers jsonb
+        not null,
+    body bytea
+        not null
+);
+
+-- Where each resource was downloaded from. null for resources archived before
+-- this was recorded.
+alter table archive_resource_uses
+    add column url text
+        default null;
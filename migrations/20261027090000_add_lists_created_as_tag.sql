@@ -0,0 +1,6 @@
+-- Lists that were created for a new tag through the Pinboard API. Only these
+-- lose their links to a bookmark when a client changes the bookmark's tags.
+alter table lists
+    add column created_as_tag boolean
+        default false
+        not null;
@@ -0,0 +1,9 @@
+-- When a bookmark was last edited, so that sync clients know whether they
+-- need to download bookmarks again.
+alter table bookmarks
+    add column updated_at timestamp with time zone
+        default current_timestamp
+        not null;
+
+update bookmarks
+set updated_at = created_at;
@@ -0,0 +1,154 @@
+-- Escape "%", "_" and "\" so that `like` matches the pattern literally.
+create function escape_like(pattern text)
+    returns text
+    language sql
+    immutable
+    parallel safe
+as $$
+    select replace(replace(replace(pattern, '\', '\\'), '%', '\%'), '_', '\_')
+$$;
+
+-- Whether the title contains all words. Without any words, the search only
+-- consists of filters, so every title matches.
+create function title_contains(title text, words text[])
+    returns boolean
+    language sql
+    immutable
+    parallel safe
+as $$
+    select not exists (
+        select null
+        from unnest(words) as word
+        where title not ilike '%' || escape_like(word) || '%'
+    )
+$$;
+
+-- All conditions for matching bookmarks and lists in a search, shared by the
+-- queries for paginating search results in `db::search`.
+-- Parameters are prefixed to avoid clashing with column names.
+create function search_items(
+    search_term text,
+    -- words of the search term, matched literally in titles
+    search_words text[],
+    search_ap_user_id uuid,
+    search_include_bookmarks boolean,
+    search_include_lists boolean,
+    -- words and phrases that must not appear in the item
+    search_excluded_terms text[],
+    -- lowercase titles of lists the item needs to be linked from
+    search_lists text[],
+    -- match bookmarks on these domains or their subdomains
+    search_domains text[],
+    search_created_before timestamp with time zone,
+    search_archive_status archive_status,
+    search_unsorted boolean,
+    search_private boolean
+)
+    returns table (id uuid, rank real)
+    language sql
+    stable
+    parallel safe
+as $$
+    select bookmarks.id,
+        coalesce(ts_rank(
+            bookmarks_search_vector(bookmarks.title, bookmarks.notes)
+                || archives_search_vector(archives.extracted_html),
+            query,
+            1
+        ), 0) as rank
+    from bookmarks
+    -- Only search the newest snapshot of each bookmark
+    left join latest_archives as archives on archives.bookmark_id = bookmarks.id
+    -- Searching only for filters leaves an empty search term, which we turn
+    -- into a null query to avoid postgres complaining about it
+    cross join websearch_to_tsquery('english', nullif(search_term, '')) as query
+    where search_include_bookmarks
+        and bookmarks.ap_user_id = search_ap_user_id
+        and (
+            bookmarks_search_vector(bookmarks.title, bookmarks.notes) @@ query
+            or archives_search_vector(archives.extracted_html) @@ query
+            or title_contains(bookmarks.title, search_words)
+        )
+        and not exists (
+            select null
+            from unnest(search_excluded_terms) as excluded
+            where bookmarks_search_vector(bookmarks.title, bookmarks.notes)
+                    @@ phraseto_tsquery('english', excluded)
+                or archives_search_vector(archives.extracted_html)
+                    @@ phraseto_tsquery('english', excluded)
+                or bookmarks.title ilike '%' || escape_like(excluded) || '%'
+        )
+        and cardinality(search_lists) = (
+            select count(distinct lower(lists.title))
+            from links
+            inner join lists on lists.id = links.src_list_id
+            where links.dest_bookmark_id = bookmarks.id
+                and lists.ap_user_id = search_ap_user_id
+                and lower(lists.title) = any(search_lists)
+        )
+        and (
+            cardinality(search_domains) = 0
+            or exists (
+                select null
+                from unnest(search_domains) as domain,
+                    lower(substring(bookmarks.url from '^[^:]+://([^/:?#]+)')) as host
+                where host = domain
+                    or host like '%.' || escape_like(domain) escape '\'
+            )
+        )
+        and (search_created_before is null or bookmarks.created_at < search_created_before)
+        and (search_archive_status is null or archives.status = search_archive_status)
+        and (
+            not search_unsorted
+            or not exists (
+                select null from links
+                where links.dest_bookmark_id = bookmarks.id
+            )
+        )
+        and (
+            not search_private
+            or not exists (
+                select null
+                from links
+                inner join lists on lists.id = links.src_list_id
+                where links.dest_bookmark_id = bookmarks.id
+                    and not lists.private
+            )
+        )
+    union all
+    select lists.id,
+        coalesce(ts_rank(
+            lists_search_vector(lists.title, lists.content),
+            query,
+            1
+        ), 0) as rank
+    from lists
+    cross join websearch_to_tsquery('english', nullif(search_term, '')) as query
+    where search_include_lists
+        and lists.ap_user_id = search_ap_user_id
+        and (
+            lists_search_vector(lists.title, lists.content) @@ query
+            or title_contains(lists.title, search_words)
+        )
+        and not exists (
+            select null
+            from unnest(search_excluded_terms) as excluded
+            where lists_search_vector(lists.title, lists.content)
+                    @@ phraseto_tsquery('english', excluded)
+                or lists.title ilike '%' || escape_like(excluded) || '%'
+        )
+        and cardinality(search_lists) = (
+            select count(distinct lower(src.title))
+            from links
+            inner join lists as src on src.id = links.src_list_id
+            where links.dest_list_id = lists.id
+                and src.ap_user_id = search_ap_user_id
+                and lower(src.title) = any(search_lists)
+        )
+        -- these filters only apply to bookmarks
+        and cardinality(search_domains) = 0
+        and search_archive_status is null
+        and not search_unsorted
+        and (search_created_before is null or lists.created_at < search_created_before)
+        and (not search_private or lists.private)
+$$;
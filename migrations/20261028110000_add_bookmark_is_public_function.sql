@@ -0,0 +1,16 @@
+-- Whether at least one public list links to the bookmark. Only public
+-- bookmarks are shown to other users and sent to remote instances.
+create function bookmark_is_public(bookmark_id uuid)
+    returns boolean
+    language sql
+    stable
+    parallel safe
+as $$
+    select exists (
+        select null
+        from links
+        inner join lists on lists.id = links.src_list_id
+        where links.dest_bookmark_id = bookmark_id
+            and not lists.private
+    )
+$$;
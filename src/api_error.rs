@@ -0,0 +1,72 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::{form_errors::FormErrors, response_error::ResponseError};
+
+pub type ApiResult<T> = std::result::Result<T, ApiError>;
+
+/// Errors returned by the JSON API. Unlike [`ResponseError`], these are always
+/// rendered as JSON and never redirect to the login page.
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("Not found")]
+    NotFound,
+    #[error("Missing or invalid API token")]
+    NotAuthenticated,
+    #[error("This API token can only be used for reading")]
+    ReadOnlyToken,
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Internal error")]
+    Internal(ResponseError),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::NotAuthenticated => StatusCode::UNAUTHORIZED,
+            ApiError::ReadOnlyToken => StatusCode::FORBIDDEN,
+            ApiError::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Internal(error) => {
+                tracing::error!("{error:?}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+
+        (status, Json(json!({ "error": self.to_string() }))).into_response()
+    }
+}
+
+impl From<ResponseError> for ApiError {
+    fn from(value: ResponseError) -> Self {
+        match value {
+            ResponseError::NotFound => Self::NotFound,
+            ResponseError::NotAuthenticated => Self::NotAuthenticated,
+            other => Self::Internal(other),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(value: sqlx::Error) -> Self {
+        ResponseError::from(value).into()
+    }
+}
+
+impl From<garde::Report> for ApiError {
+    fn from(report: garde::Report) -> Self {
+        Self::Invalid(report.to_string().trim().to_string())
+    }
+}
+
+impl From<FormErrors> for ApiError {
+    fn from(errors: FormErrors) -> Self {
+        errors.0.into()
+    }
+}
@@ -1,10 +1,18 @@
+mod diff;
 mod error;
+mod export;
 mod fetch_url;
 mod queue;
+mod rate_limit;
 mod readability;
+mod resources;
+mod robots_txt;
 mod safe_ips;
 
+pub use diff::{DiffLine, diff};
 pub use error::Error;
-pub use fetch_url::fetch_url_as_text;
+pub use export::{Export, warc};
+pub use fetch_url::{Client, Config, RawResponse};
 pub use queue::QueueHandle;
 pub use readability::make_readable;
+pub use resources::{Resource, ResourceStore, download_images};
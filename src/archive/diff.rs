@@ -0,0 +1,278 @@
+//! Compare the text of two archived snapshots of a page, to see how it changed
+//! over time.
+
+/// A line of the difference between two snapshots.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    Unchanged(String),
+    Added(String),
+    Removed(String),
+    /// A number of unchanged lines that are hidden because they're not close to
+    /// any change.
+    Skipped(usize),
+}
+
+/// Number of unchanged lines to show around each change.
+const CONTEXT_LINES: usize = 2;
+
+/// Comparing every line of two long, completely different pages takes a lot
+/// of memory. Above this many comparisons, the changed part is shown as
+/// replaced entirely.
+const MAX_COMPARISONS: usize = 4_000_000;
+
+/// Elements that start a new line in the text of a page.
+const BLOCK_ELEMENTS: [&str; 32] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+/// Compare the text of two snapshots, given as their readable HTML.
+pub fn diff(old_html: &str, new_html: &str) -> Vec<DiffLine> {
+    let old = text_lines(old_html);
+    let new = text_lines(new_html);
+
+    collapse_unchanged(diff_lines(&old, &new))
+}
+
+/// Turn HTML into lines of text, one for each paragraph, list item, heading
+/// and so on.
+fn text_lines(html: &str) -> Vec<String> {
+    let mut text = String::new();
+    let mut in_pre = false;
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        push_text(&mut text, &rest[..start], in_pre);
+        let Some(length) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[start + 1..start + length];
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if name == "pre" {
+            in_pre = !tag.starts_with('/');
+        }
+        if BLOCK_ELEMENTS.contains(&name.as_str()) {
+            text.push('\n');
+        }
+        rest = &rest[start + length + 1..];
+    }
+    push_text(&mut text, rest, in_pre);
+
+    text.lines()
+        .map(|line| decode_entities(&line.split_whitespace().collect::<Vec<_>>().join(" ")))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Line breaks in HTML source only matter in preformatted text.
+fn push_text(text: &mut String, html: &str, in_pre: bool) {
+    if in_pre {
+        text.push_str(html);
+    } else {
+        text.push_str(&html.replace('\n', " "));
+    }
+}
+
+/// Archived HTML is sanitized, so it only contains these entities.
+fn decode_entities(line: &str) -> String {
+    line.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn diff_lines(old: &[String], new: &[String]) -> Vec<DiffLine> {
+    // Most changes only touch a small part of a page, so skip the common start
+    // and end before comparing the rest line by line
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let mut lines = old[..prefix]
+        .iter()
+        .cloned()
+        .map(DiffLine::Unchanged)
+        .collect::<Vec<_>>();
+    lines.extend(diff_changed_lines(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    ));
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .cloned()
+            .map(DiffLine::Unchanged),
+    );
+
+    lines
+}
+
+/// Find the longest common subsequence of lines, and mark all other lines as
+/// added or removed.
+fn diff_changed_lines(old: &[String], new: &[String]) -> Vec<DiffLine> {
+    let removed = || old.iter().cloned().map(DiffLine::Removed);
+    let added = || new.iter().cloned().map(DiffLine::Added);
+    if old.len().saturating_mul(new.len()) > MAX_COMPARISONS {
+        return removed().chain(added()).collect();
+    }
+
+    // Length of the longest common subsequence of `old[i..]` and `new[j..]`,
+    // stored at `i * width + j`
+    let width = new.len() + 1;
+    let mut lengths = vec![0_u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Unchanged(old[i].clone()));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            lines.push(DiffLine::Removed(old[i].clone()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].clone()));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().cloned().map(DiffLine::Removed));
+    lines.extend(new[j..].iter().cloned().map(DiffLine::Added));
+
+    lines
+}
+
+/// Hide unchanged lines that aren't close to a change.
+fn collapse_unchanged(lines: Vec<DiffLine>) -> Vec<DiffLine> {
+    let mut is_visible = vec![false; lines.len()];
+    for (index, line) in lines.iter().enumerate() {
+        if !matches!(line, DiffLine::Unchanged(_)) {
+            let start = index.saturating_sub(CONTEXT_LINES);
+            let end = (index + CONTEXT_LINES + 1).min(lines.len());
+            is_visible[start..end].fill(true);
+        }
+    }
+
+    let mut collapsed = Vec::new();
+    let mut skipped = 0;
+    for (line, is_visible) in lines.into_iter().zip(is_visible) {
+        if is_visible {
+            if skipped > 0 {
+                collapsed.push(DiffLine::Skipped(skipped));
+                skipped = 0;
+            }
+            collapsed.push(line);
+        } else {
+            skipped += 1;
+        }
+    }
+    if skipped > 0 {
+        collapsed.push(DiffLine::Skipped(skipped));
+    }
+
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_lines_of_text() {
+        let html = "<h1>Title</h1>\n<p>Some <em>emphasized</em>\ntext &amp; \
+                    more</p><ul><li>One</li><li>Two</li></ul><pre>let a = 1;\nlet b = 2;</pre>";
+
+        assert_eq!(
+            text_lines(html),
+            vec![
+                "Title",
+                "Some emphasized text & more",
+                "One",
+                "Two",
+                "let a = 1;",
+                "let b = 2;"
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_changed_lines() {
+        let old = "<p>One</p><p>Two</p><p>Three</p><p>Four</p><p>Five</p><p>Six</p>";
+        let new = "<p>One</p><p>Two</p><p>Three</p><p>4</p><p>Five</p><p>Six</p><p>Seven</p>";
+
+        assert_eq!(
+            diff(old, new),
+            vec![
+                DiffLine::Skipped(1),
+                DiffLine::Unchanged("Two".to_string()),
+                DiffLine::Unchanged("Three".to_string()),
+                DiffLine::Removed("Four".to_string()),
+                DiffLine::Added("4".to_string()),
+                DiffLine::Unchanged("Five".to_string()),
+                DiffLine::Unchanged("Six".to_string()),
+                DiffLine::Added("Seven".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_pages_have_no_changes() {
+        let html = "<p>One</p><p>Two</p><p>Three</p>";
+
+        assert_eq!(diff(html, html), vec![DiffLine::Skipped(3)]);
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 // TODO: add tests for these errors
-#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[derive(thiserror::Error, Debug, Clone, Serialize, Deserialize)]
 pub enum Error {
     #[error("The URL of this bookmark is in a format ties doesn't recognize.")]
     InvalidUrl,
@@ -20,7 +20,12 @@ pub enum Error {
     #[error("The website's host did not answer.")]
     Timeout,
     #[error("The website's host indicated an error on their end (status code: {status:?}).")]
-    Status { status: Option<u16> },
+    Status {
+        status: Option<u16>,
+        /// How long the website asked us to wait before trying again
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_after_secs: Option<u64>,
+    },
     #[error("There was an unexpected error in ties. Please report this to your server operator.")]
     UnexpectedInternal,
 
@@ -31,6 +36,34 @@ pub enum Error {
     ResponseTooLarge { actual_size_mb: f64 },
     #[error("Ties could not convert the website into a readable version.")]
     NotReadable,
+    #[error("The website's robots.txt file does not allow ties to archive this page.")]
+    DisallowedByRobotsTxt,
+    #[error("The website redirected ties too many times.")]
+    TooManyRedirects,
+}
+
+impl Error {
+    /// Whether archiving might work when trying again later. Other errors are
+    /// not retried.
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Connect | Self::Timeout => true,
+            // Too many requests, or an error on the website's end
+            Self::Status { status, .. } => {
+                status.is_none_or(|status| status == 429 || status >= 500)
+            }
+            Self::InvalidUrl
+            | Self::IpUrl
+            | Self::UnsupportedScheme { .. }
+            | Self::UnsupportedContentType { .. }
+            | Self::UnexpectedInternal
+            | Self::ResponseTooLarge { .. }
+            | Self::NotReadable
+            | Self::DisallowedByRobotsTxt
+            | Self::TooManyRedirects => false,
+        }
+    }
 }
 
 // Custom From impls are necessary because these libraries don't implement
@@ -49,6 +82,7 @@ impl From<reqwest::Error> for Error {
         } else if value.is_status() {
             Self::Status {
                 status: value.status().map(|s| s.as_u16()),
+                retry_after_secs: None,
             }
         } else if value.is_timeout() {
             Self::Timeout
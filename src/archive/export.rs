@@ -0,0 +1,272 @@
+//! Export archives to read them without ties, either as single HTML files with
+//! the images inlined, or as [WARC](https://iipc.github.io/warc-specifications/specifications/warc-format/warc-1.1/)
+//! files for web archiving tools.
+//!
+//! A WARC file contains, for each archive:
+//!
+//! - the response of the website, if the server is configured to store it
+//! - the readable HTML, as a `conversion` of the response
+//! - the archived images, pointed to by their original URLs
+
+use std::{collections::HashSet, fmt::Write};
+
+use anyhow::Context;
+use base64::Engine;
+use reqwest::StatusCode;
+use time::{OffsetDateTime, UtcOffset, format_description::well_known::Rfc3339};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    archive::{self, resources},
+    db::{self, AppTx},
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// A successful archive along with everything needed to export it.
+pub struct Export {
+    pub bookmark: db::Bookmark,
+    pub archive: db::Archive,
+    /// Only available if raw responses were stored when archiving
+    pub response: Option<archive::RawResponse>,
+    pub resources: Vec<archive::Resource>,
+}
+
+impl Export {
+    /// Load the response and resources of a successful archive.
+    pub async fn load(
+        tx: &mut AppTx,
+        resource_store: &archive::ResourceStore,
+        base_url: &Url,
+        bookmark: db::Bookmark,
+        archive: db::Archive,
+    ) -> ResponseResult<Self> {
+        if archive.extracted_html.is_none() {
+            return Err(ResponseError::NotFound);
+        }
+
+        let response = db::archive_responses::by_archive_id(tx, archive.id).await?;
+
+        let mut resources = Vec::new();
+        for resource_use in db::archive_resources::list_by_archive_id(tx, archive.id).await? {
+            let (content_type, data) = resource_store.load(tx, &resource_use.hash).await?;
+            // Older archives didn't record where images came from, so point to
+            // the copy on this server instead
+            let url = match resource_use.url {
+                Some(url) => url,
+                None => base_url
+                    .join(&resources::path(&resource_use.hash))?
+                    .to_string(),
+            };
+            resources.push(archive::Resource {
+                url,
+                hash: resource_use.hash,
+                content_type,
+                data,
+            });
+        }
+
+        Ok(Self {
+            bookmark,
+            archive,
+            response,
+            resources,
+        })
+    }
+
+    /// The readable HTML, with images inlined as `data:` URLs.
+    pub fn self_contained_html(&self) -> String {
+        let paths = self
+            .resources
+            .iter()
+            .map(|resource| {
+                let data = base64::engine::general_purpose::STANDARD.encode(&resource.data);
+                (
+                    resources::path(&resource.hash),
+                    format!("data:{};base64,{data}", resource.content_type),
+                )
+            })
+            .collect();
+
+        resources::rewrite_image_urls(self.html(), paths)
+    }
+
+    /// The readable HTML, with images pointing to the website again.
+    fn html_with_original_urls(&self) -> String {
+        let paths = self
+            .resources
+            .iter()
+            .map(|resource| (resources::path(&resource.hash), resource.url.clone()))
+            .collect();
+
+        resources::rewrite_image_urls(self.html(), paths)
+    }
+
+    fn html(&self) -> &str {
+        self.archive.extracted_html.as_deref().unwrap_or_default()
+    }
+}
+
+/// Write archives into a single WARC file.
+pub fn warc(exports: &[Export]) -> anyhow::Result<Vec<u8>> {
+    let mut warc = Vec::new();
+
+    let info = format!(
+        "software: ties/{}\r\nformat: WARC File Format 1.1\r\nconformsTo: \
+         http://iipc.github.io/warc-specifications/specifications/warc-format/warc-1.1/\r\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    let info_id = write_record(
+        &mut warc,
+        &[
+            ("WARC-Type", "warcinfo".to_string()),
+            ("WARC-Date", warc_date(OffsetDateTime::now_utc())?),
+            ("Content-Type", "application/warc-fields".to_string()),
+        ],
+        info.as_bytes(),
+    );
+
+    // Images used by several archives are only written once
+    let mut written_resources = HashSet::new();
+    for export in exports {
+        let date = warc_date(export.archive.created_at)?;
+        let with_common_fields = |mut fields: Vec<(&'static str, String)>| {
+            fields.push(("WARC-Date", date.clone()));
+            fields.push(("WARC-Warcinfo-ID", info_id.clone()));
+            fields
+        };
+
+        let response_id = export.response.as_ref().map(|response| {
+            write_record(
+                &mut warc,
+                &with_common_fields(vec![
+                    ("WARC-Type", "response".to_string()),
+                    ("WARC-Target-URI", response.url.clone()),
+                    (
+                        "Content-Type",
+                        "application/http;msgtype=response".to_string(),
+                    ),
+                ]),
+                &http_response(response),
+            )
+        });
+
+        let mut conversion_fields = vec![
+            ("WARC-Type", "conversion".to_string()),
+            ("WARC-Target-URI", export.bookmark.url.clone()),
+            ("Content-Type", "text/html; charset=utf-8".to_string()),
+        ];
+        if let Some(response_id) = response_id {
+            conversion_fields.push(("WARC-Refers-To", response_id));
+        }
+        write_record(
+            &mut warc,
+            &with_common_fields(conversion_fields),
+            export.html_with_original_urls().as_bytes(),
+        );
+
+        for resource in &export.resources {
+            if !written_resources.insert((&resource.url, &resource.hash)) {
+                continue;
+            }
+            write_record(
+                &mut warc,
+                &with_common_fields(vec![
+                    ("WARC-Type", "resource".to_string()),
+                    ("WARC-Target-URI", resource.url.clone()),
+                    ("Content-Type", resource.content_type.clone()),
+                ]),
+                &resource.data,
+            );
+        }
+    }
+
+    Ok(warc)
+}
+
+/// Append a record to a WARC file, and return its id.
+fn write_record(warc: &mut Vec<u8>, fields: &[(&str, String)], block: &[u8]) -> String {
+    let id = format!("<urn:uuid:{}>", Uuid::new_v4());
+
+    let mut header = format!("WARC/1.1\r\nWARC-Record-ID: {id}\r\n");
+    for (name, value) in fields {
+        // Values from websites must not be able to start a new field
+        let value = value.replace(['\r', '\n'], " ");
+        let _ = write!(header, "{name}: {value}\r\n");
+    }
+    let _ = write!(header, "Content-Length: {}\r\n\r\n", block.len());
+
+    warc.extend_from_slice(header.as_bytes());
+    warc.extend_from_slice(block);
+    warc.extend_from_slice(b"\r\n\r\n");
+
+    id
+}
+
+/// Turn a stored response back into an HTTP message.
+fn http_response(response: &archive::RawResponse) -> Vec<u8> {
+    let reason = StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or_default();
+
+    let mut head = format!("HTTP/1.1 {} {reason}\r\n", response.status);
+    for (name, value) in &response.headers {
+        // The stored body is already decoded
+        if name.eq_ignore_ascii_case("transfer-encoding") {
+            continue;
+        }
+        let _ = write!(head, "{name}: {value}\r\n");
+    }
+    head.push_str("\r\n");
+
+    let mut message = head.into_bytes();
+    message.extend_from_slice(&response.body);
+
+    message
+}
+
+/// WARC dates are in UTC and have no fractional seconds.
+fn warc_date(date: OffsetDateTime) -> anyhow::Result<String> {
+    let date = date.to_offset(UtcOffset::UTC);
+    date.replace_nanosecond(0)
+        .unwrap_or(date)
+        .format(&Rfc3339)
+        .context("Failed to format date")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_warc_records() {
+        let mut warc = Vec::new();
+        let id = write_record(
+            &mut warc,
+            &[
+                ("WARC-Type", "resource".to_string()),
+                (
+                    "WARC-Target-URI",
+                    "https://example.com/\r\nEvil: yes".to_string(),
+                ),
+            ],
+            b"hello",
+        );
+
+        assert_eq!(
+            String::from_utf8(warc).unwrap(),
+            format!(
+                "WARC/1.1\r\nWARC-Record-ID: {id}\r\nWARC-Type: resource\r\nWARC-Target-URI: \
+                 https://example.com/  Evil: yes\r\nContent-Length: 5\r\n\r\nhello\r\n\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn formats_warc_dates() {
+        let date = OffsetDateTime::parse("2026-10-17T14:03:53.352+02:00", &Rfc3339).unwrap();
+
+        assert_eq!(warc_date(date).unwrap(), "2026-10-17T12:03:53Z");
+    }
+}
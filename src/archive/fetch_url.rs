@@ -1,13 +1,23 @@
 use core::f64;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
 use easy_cast::Conv;
 use encoding_rs::{Encoding, UTF_8};
 use http_body_util::BodyExt;
 use mime_guess::Mime;
+use reqwest::{
+    StatusCode,
+    header::{self, HeaderMap},
+};
+use time::{OffsetDateTime, format_description::well_known::Rfc2822};
 use url::Url;
 
-use crate::archive::{self, safe_ips};
+use crate::archive::{self, rate_limit::HostLimiter, robots_txt, safe_ips};
 
 const MAX_RESPONSE_SIZE_BYTES: u64 = 5 * 1000 * 1000; // ~ 5 megabytes
 const MAX_RESPONSE_SIZE_BYTES_USIZE: usize = 5 * 1000 * 1000; // ~ 5 megabytes
@@ -23,7 +33,344 @@ fn is_domain_url(url: &Url) -> bool {
     }
 }
 
-pub async fn fetch_url_as_text(unvalidated_url: &str) -> Result<String, archive::Error> {
+/// Settings for requests to other websites.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Sent as the `User-Agent` header. The part before the first `/` is used
+    /// to find matching rules in `robots.txt` files.
+    pub user_agent: String,
+    /// Don't archive pages that `robots.txt` disallows for our user agent.
+    pub respect_robots_txt: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            user_agent: format!(
+                "ties/{} (+{})",
+                env!("CARGO_PKG_VERSION"),
+                env!("CARGO_PKG_REPOSITORY")
+            ),
+            respect_robots_txt: false,
+        }
+    }
+}
+
+/// Hosts asking us to wait longer than this using `Retry-After` are only left
+/// alone for this long.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Redirects followed before giving up on a URL.
+const MAX_REDIRECTS: usize = 5;
+
+/// How long to remember a website's `robots.txt` rules.
+const ROBOTS_TXT_CACHE_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A response exactly as the website sent it, see [`Client::fetch_page`].
+pub struct RawResponse {
+    /// The URL after following redirects
+    pub url: String,
+    pub status: u16,
+    /// Header names and values, in the order the website sent them
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RawResponse {
+    /// Decode the body using the charset given in the `Content-Type` header.
+    pub fn text(&self) -> String {
+        let content_type = self
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(header::CONTENT_TYPE.as_str()))
+            .map(|(_, value)| value.as_str());
+
+        decode(content_type, &self.body)
+    }
+}
+
+impl std::fmt::Debug for RawResponse {
+    // Don't log the whole page
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawResponse")
+            .field("url", &self.url)
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .field("size", &self.body.len())
+            .finish()
+    }
+}
+
+/// Fetches websites and feeds, and makes sure ties is a polite visitor: there
+/// is a limit on how many requests are sent to each host, hosts asking us to
+/// slow down using `Retry-After` are left alone for a while, and optionally,
+/// `robots.txt` files are respected.
+///
+/// Cloning the client is cheap and shares the limits between the clones.
+#[derive(Clone)]
+pub struct Client {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    http: reqwest::Client,
+    config: Config,
+    limiter: HostLimiter,
+    /// Rules by origin, along with the time they were fetched
+    robots_txt: Mutex<HashMap<String, (Instant, Arc<robots_txt::Rules>)>>,
+}
+
+impl Client {
+    pub fn new(config: Config) -> Result<Self, archive::Error> {
+        let http = reqwest::Client::builder()
+            .user_agent(&config.user_agent)
+            .dns_resolver(safe_ips::SafeDnsResolver)
+            // Redirects are followed in `send`, to stick to the rate limit of
+            // every host along the way
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(Duration::from_secs(60))
+            .build()?;
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                http,
+                config,
+                limiter: HostLimiter::default(),
+                robots_txt: Mutex::default(),
+            }),
+        })
+    }
+
+    pub async fn fetch_url_as_text(&self, unvalidated_url: &str) -> Result<String, archive::Error> {
+        Ok(self.fetch_page(unvalidated_url).await?.text())
+    }
+
+    /// Fetch an HTML page, keeping the response as the website sent it.
+    pub async fn fetch_page(&self, unvalidated_url: &str) -> Result<RawResponse, archive::Error> {
+        let url = validate(unvalidated_url)?;
+
+        if self.inner.config.respect_robots_txt && !self.is_allowed_by_robots_txt(&url).await? {
+            return Err(archive::Error::DisallowedByRobotsTxt);
+        }
+
+        let response = self
+            .fetch(url, |content_type| content_type.starts_with("text/html"))
+            .await?;
+
+        let url = response.url().to_string();
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        let body = limited_body(response).await?;
+
+        Ok(RawResponse {
+            url,
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// Fetch an RSS, Atom or JSON feed. Feeds declare their encoding
+    /// themselves, so this returns the raw bytes.
+    pub async fn fetch_feed(&self, unvalidated_url: &str) -> Result<Vec<u8>, archive::Error> {
+        let url = validate(unvalidated_url)?;
+
+        let response = self
+            .fetch(url, |content_type| {
+                [
+                    "application/rss+xml",
+                    "application/atom+xml",
+                    "application/feed+json",
+                    "application/xml",
+                    "application/json",
+                    "text/xml",
+                ]
+                .iter()
+                .any(|supported| content_type.starts_with(supported))
+            })
+            .await?;
+
+        limited_body(response).await
+    }
+
+    /// Fetch an image referenced by an archived page, returning its content
+    /// type along with its contents.
+    pub async fn fetch_image(
+        &self,
+        unvalidated_url: &str,
+    ) -> Result<(String, Vec<u8>), archive::Error> {
+        let url = validate(unvalidated_url)?;
+
+        let response = self
+            .fetch(url, |content_type| content_type.starts_with("image/"))
+            .await?;
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("application/octet-stream")
+            .to_string();
+
+        Ok((content_type, limited_body(response).await?))
+    }
+
+    /// Send a GET request, and check that the response is small enough and has
+    /// a supported content type.
+    async fn fetch(
+        &self,
+        url: Url,
+        is_supported_content_type: impl Fn(&str) -> bool,
+    ) -> Result<reqwest::Response, archive::Error> {
+        let response = self.send(url).await?.error_for_status()?;
+
+        if let Some(length_according_to_header) = response.content_length()
+            && length_according_to_header > MAX_RESPONSE_SIZE_BYTES
+        {
+            return Err(archive::Error::ResponseTooLarge {
+                actual_size_mb: f64::try_conv(length_according_to_header).unwrap_or(f64::MAX)
+                    / 1_000_000.0,
+            });
+        }
+
+        tracing::debug!(headers = ?response.headers());
+
+        // Check that the content type is supported
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .ok_or(archive::Error::UnexpectedInternal)?
+            .to_str()
+            .map_err(|_| archive::Error::UnexpectedInternal)?
+            .to_string();
+        if !is_supported_content_type(&content_type) {
+            return Err(archive::Error::UnsupportedContentType { content_type });
+        }
+
+        Ok(response)
+    }
+
+    /// Send a GET request and follow its redirects.
+    async fn send(&self, mut url: Url) -> Result<reqwest::Response, archive::Error> {
+        for _ in 0..=MAX_REDIRECTS {
+            let response = self.send_once(url.clone()).await?;
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .filter(|_| response.status().is_redirection());
+            let Some(location) = location else {
+                return Ok(response);
+            };
+
+            let location = location.to_str().map_err(|_| archive::Error::InvalidUrl)?;
+            url = validate(url.join(location)?.as_str())?;
+            tracing::debug!(%url, "Following redirect");
+        }
+
+        Err(archive::Error::TooManyRedirects)
+    }
+
+    /// Send a GET request while sticking to the rate limit of the URL's host.
+    async fn send_once(&self, url: Url) -> Result<reqwest::Response, archive::Error> {
+        let host = url.host_str().unwrap_or_default().to_string();
+        let _permit = self
+            .inner
+            .limiter
+            .acquire(&host)
+            .await
+            .map_err(|blocked_for| archive::Error::Status {
+                // The host asked us to slow down earlier
+                status: Some(StatusCode::TOO_MANY_REQUESTS.as_u16()),
+                retry_after_secs: Some(blocked_for.as_secs()),
+            })?;
+
+        let response = self.inner.http.get(url).send().await?;
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE {
+            let retry_after =
+                retry_after(response.headers()).map(|duration| duration.min(MAX_RETRY_AFTER));
+            if let Some(retry_after) = retry_after {
+                tracing::info!(host, ?retry_after, "Host asked us to retry later");
+                self.inner.limiter.block(&host, retry_after);
+            }
+            return Err(archive::Error::Status {
+                status: Some(status.as_u16()),
+                retry_after_secs: retry_after.map(|duration| duration.as_secs()),
+            });
+        }
+
+        Ok(response)
+    }
+
+    async fn is_allowed_by_robots_txt(&self, url: &Url) -> Result<bool, archive::Error> {
+        let path = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+
+        Ok(self.robots_txt_rules(url).await?.is_allowed(&path))
+    }
+
+    async fn robots_txt_rules(&self, url: &Url) -> Result<Arc<robots_txt::Rules>, archive::Error> {
+        let origin = url.origin().ascii_serialization();
+        if let Some((fetched_at, rules)) = self
+            .inner
+            .robots_txt
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&origin)
+            && fetched_at.elapsed() < ROBOTS_TXT_CACHE_DURATION
+        {
+            return Ok(rules.clone());
+        }
+
+        let response = self.send(url.join("/robots.txt")?).await?;
+        let rules = if response.status().is_client_error() {
+            // No robots.txt file means that everything is allowed
+            robots_txt::Rules::default()
+        } else {
+            let text = limited_body_to_text(response.error_for_status()?).await?;
+            robots_txt::Rules::parse(&text, &self.product_token())
+        };
+        tracing::debug!(origin, ?rules, "Fetched robots.txt");
+
+        let rules = Arc::new(rules);
+        let mut cache = self
+            .inner
+            .robots_txt
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < ROBOTS_TXT_CACHE_DURATION);
+        cache.insert(origin, (Instant::now(), rules.clone()));
+
+        Ok(rules)
+    }
+
+    /// The name of our crawler in `robots.txt` files, e.g. `ties` for the
+    /// user agent `ties/1.0`.
+    fn product_token(&self) -> String {
+        self.inner
+            .config
+            .user_agent
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+    }
+}
+
+/// Check that a URL is safe to request from the server.
+fn validate(unvalidated_url: &str) -> Result<Url, archive::Error> {
     let url = Url::parse(unvalidated_url)?;
 
     // Do not allow protocols other than http/s - this can pose a security risk, and
@@ -43,51 +390,21 @@ pub async fn fetch_url_as_text(unvalidated_url: &str) -> Result<String, archive:
         return Err(archive::Error::IpUrl);
     }
 
-    let redirect_policy = reqwest::redirect::Policy::custom(|attempt| {
-        if attempt.previous().len() > 5 {
-            attempt.error("Too many redirects")
-        } else if !is_domain_url(attempt.url()) {
-            attempt.error(archive::Error::IpUrl)
-        } else {
-            attempt.follow()
-        }
-    });
-
-    // TODO include version in user agent, or use a different user agent that won't
-    // get us blocked on so many sites
-    let client = reqwest::Client::builder()
-        .user_agent("ties")
-        .dns_resolver(safe_ips::SafeDnsResolver)
-        .redirect(redirect_policy)
-        .timeout(std::time::Duration::from_secs(60))
-        .build()?;
-
-    let response = client.get(url).send().await?.error_for_status()?;
-
-    if let Some(length_according_to_header) = response.content_length()
-        && length_according_to_header > MAX_RESPONSE_SIZE_BYTES
-    {
-        return Err(archive::Error::ResponseTooLarge {
-            actual_size_mb: f64::try_conv(length_according_to_header).unwrap_or(f64::MAX)
-                / 1_000_000.0,
-        });
-    }
+    Ok(url)
+}
 
-    tracing::debug!(headers = ?response.headers());
+/// Parse a `Retry-After` header, which is either a number of seconds or a
+/// date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
 
-    // Check that the content type is supported
-    let content_type = response
-        .headers()
-        .get("content-type")
-        .ok_or(archive::Error::UnexpectedInternal)?
-        .to_str()
-        .map_err(|_| archive::Error::UnexpectedInternal)?
-        .to_string();
-    if !content_type.starts_with("text/html") {
-        return Err(archive::Error::UnsupportedContentType { content_type });
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
     }
 
-    limited_body_to_text(response).await
+    let date = OffsetDateTime::parse(value, &Rfc2822).ok()?;
+    // Dates in the past mean that we can retry right away
+    Some(Duration::try_from(date - OffsetDateTime::now_utc()).unwrap_or(Duration::ZERO))
 }
 
 async fn limited_body_to_text(response: reqwest::Response) -> Result<String, archive::Error> {
@@ -95,21 +412,70 @@ async fn limited_body_to_text(response: reqwest::Response) -> Result<String, arc
         .headers()
         .get(reqwest::header::CONTENT_TYPE)
         .and_then(|value| value.to_str().ok())
-        .and_then(|value| value.parse::<Mime>().ok());
-    let limited_body =
-        http_body_util::Limited::new(reqwest::Body::from(response), MAX_RESPONSE_SIZE_BYTES_USIZE);
-    let encoding_name = content_type
+        .map(ToString::to_string);
+
+    let full = limited_body(response).await?;
+
+    Ok(decode(content_type.as_deref(), &full))
+}
+
+/// Decode a body using the charset of its content type, falling back to UTF-8.
+fn decode(content_type: Option<&str>, body: &[u8]) -> String {
+    let mime = content_type.and_then(|value| value.parse::<Mime>().ok());
+    let encoding_name = mime
         .as_ref()
         .and_then(|mime| mime.get_param("charset").map(|charset| charset.as_str()))
         .unwrap_or("utf-8");
     let encoding = Encoding::for_label(encoding_name.as_bytes()).unwrap_or(UTF_8);
 
-    let full = BodyExt::collect(limited_body)
+    let (text, _, _) = encoding.decode(body);
+
+    text.to_string()
+}
+
+async fn limited_body(response: reqwest::Response) -> Result<Vec<u8>, archive::Error> {
+    let limited_body =
+        http_body_util::Limited::new(reqwest::Body::from(response), MAX_RESPONSE_SIZE_BYTES_USIZE);
+
+    BodyExt::collect(limited_body)
         .await
-        .map(|buf| buf.to_bytes())
-        .map_err(|_| archive::Error::UnexpectedInternal)?;
+        .map(|buf| buf.to_bytes().to_vec())
+        .map_err(|_| archive::Error::UnexpectedInternal)
+}
 
-    let (text, _, _) = encoding.decode(&full);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(text.to_string())
+    #[test]
+    fn parses_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(header::RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            header::RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn decodes_raw_responses_using_their_charset() {
+        let response = RawResponse {
+            url: "https://example.com".to_string(),
+            status: 200,
+            headers: vec![(
+                "Content-Type".to_string(),
+                "text/html; charset=iso-8859-1".to_string(),
+            )],
+            body: b"caf\xe9".to_vec(),
+        };
+        assert_eq!(response.text(), "café");
+    }
 }
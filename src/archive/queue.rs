@@ -1,187 +1,308 @@
-//! In-memory queue for processing archival requests in the background with rate
-//! limiting.
+//! Queue for processing archival requests in the background.
 //!
-//! - Jobs are automatically queued when a new local bookmark is inserted.
-//! - Failed extraction will not be retried automatically.
+//! - Jobs are stored in the database, see [`db::archive_jobs`], and are
+//!   automatically created when a new pending archive is inserted.
+//! - A configurable number of workers process jobs concurrently. Workers claim
+//!   jobs using `for update skip locked`, so multiple server processes can
+//!   share the same database.
+//! - Imported bookmarks are spread out over time, so that archiving them
+//!   doesn't delay newly added bookmarks.
+//! - Transient failures, like timeouts or server errors, are retried with
+//!   exponential backoff. Other failures are stored right away.
 //! - The database stores archival status for each bookmark (ok, not archived
 //!   yet, failed).
-//! - Jobs are processed in serial, without parallelism.
-//! - Requests are not rate limited at the moment.
+//! - Requests are rate limited per host, see [`archive::Client`].
+//! - Images are archived along with the page, see [`archive::ResourceStore`].
+//! - Optionally, the response of the website is stored as well, so archives can
+//!   be exported as WARC files that contain the original page.
+
+use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
-use tokio::sync::{broadcast, mpsc, oneshot};
+use time::OffsetDateTime;
+use tokio::{
+    sync::{Notify, broadcast},
+    time::Instant,
+};
 use uuid::Uuid;
 
 use crate::{
     archive,
-    db::{self, AppTx},
+    db::{self, archive_jobs::Job},
 };
 
-struct Queue {
-    receiver: tokio::sync::mpsc::Receiver<Message>,
-    db_pool: sqlx::PgPool,
-    processed_archive_id_sender: broadcast::Sender<Uuid>,
-}
+/// Time between processing two archives of a batch, see
+/// [`QueueHandle::archive_all_in_background`].
+const BATCH_INTERVAL: Duration = Duration::from_secs(5);
 
-struct ArchiveTask {
-    archive_id: Uuid,
-    respond_to: oneshot::Sender<Result<db::Archive>>,
-}
+/// Maximum time a worker waits before checking for new jobs, e.g. from other
+/// server processes.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
 
-enum Message {
-    ArchiveBookmark(ArchiveTask),
-}
+/// How long other workers wait before retrying a claimed job. Fetching is
+/// stopped after [`FETCH_DEADLINE`], so this only kicks in if a worker crashes.
+const LEASE: Duration = Duration::from_secs(10 * 60);
 
-impl Queue {
-    fn new(
-        receiver: mpsc::Receiver<Message>,
-        db_pool: sqlx::PgPool,
-        processed_archive_id_sender: broadcast::Sender<Uuid>,
-    ) -> Self {
-        Self {
-            receiver,
-            db_pool,
-            processed_archive_id_sender,
-        }
-    }
+/// Maximum time for fetching a website along with its images. Images that
+/// aren't downloaded by then keep pointing to the website.
+const FETCH_DEADLINE: Duration = Duration::from_secs(5 * 60);
 
-    async fn process(mut self) {
-        tracing::debug!("Starting");
+/// Delay before the first retry, doubled for each following attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(60);
 
-        while !self.receiver.is_closed() {
-            // Process all incoming messages with high priority.
-            while let Ok(message) = self.receiver.try_recv() {
-                self.receive_message(message).await;
-            }
+/// Give up after this many attempts, storing the last error.
+const MAX_ATTEMPTS: i32 = 5;
 
-            tracing::debug!("Processed all high-priority tasks");
+struct Worker {
+    db_pool: sqlx::PgPool,
+    client: archive::Client,
+    resource_store: archive::ResourceStore,
+    store_raw_responses: bool,
+    new_job: Arc<Notify>,
+    processed_archive_id_sender: broadcast::Sender<Uuid>,
+}
 
-            // No incoming messages at the moment, process an arbitrary pending bookmark if
-            // it exists.
-            let pending_archive_id = self.get_pending_archive_id().await;
+impl Worker {
+    async fn process(self) {
+        tracing::debug!("Starting");
 
-            if let Some(pending_archive_id) = pending_archive_id {
-                tracing::debug!("Found dangling pending archive");
-                let _ = self.archive(pending_archive_id).await;
+        loop {
+            match self.process_next_job().await {
+                // Look for more work right away
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(error) => tracing::error!(?error, "Could not process archive job"),
             }
 
-            // No work at the moment: wait for new messages to come in
-            if self.receiver.is_empty() && pending_archive_id.is_none() {
-                tracing::debug!("No archiving to do, waiting...");
-
-                if let Some(msg) = self.receiver.recv().await {
-                    self.receive_message(msg).await;
-                }
+            let wait = self.time_until_next_job().await;
+            tracing::debug!(?wait, "No archiving to do, waiting...");
+            tokio::select! {
+                () = self.new_job.notified() => {}
+                () = tokio::time::sleep(wait) => {}
             }
         }
-
-        tracing::debug!("Exiting");
     }
 
-    async fn receive_message(&mut self, message: Message) {
-        tracing::debug!("Received message");
-        match message {
-            Message::ArchiveBookmark(task) => {
-                let archive = self.archive(task.archive_id).await;
-
-                let _ = task.respond_to.send(archive);
-            }
-        }
-    }
+    /// Returns `false` if there was no job to process.
+    async fn process_next_job(&self) -> Result<bool> {
+        let mut tx = self.db_pool.begin().await?;
+        let Some(job) = db::archive_jobs::claim_due(&mut tx, LEASE).await? else {
+            return Ok(false);
+        };
+        // Don't keep a connection busy while fetching the website
+        tx.commit().await?;
 
-    async fn get_pending_archive_id(&mut self) -> Option<Uuid> {
-        let mut tx = self.db_pool.begin().await.ok()?;
-        let pending_id = db::archives::find_one_pending(&mut tx).await.ok()?;
-        let _ = tx.commit().await;
+        self.archive(&job).await?;
 
-        pending_id
+        Ok(true)
     }
 
-    async fn archive(&mut self, archive_id: Uuid) -> Result<db::Archive> {
+    async fn archive(&self, job: &Job) -> Result<()> {
         let mut tx = self.db_pool.begin().await?;
-        let pending = db::archives::by_id(&mut tx, archive_id).await?;
+        let pending = db::archives::by_id(&mut tx, job.archive_id).await?;
+        if pending.status != db::archives::Status::Pending {
+            tracing::debug!(?pending, "Archive was already processed");
+            db::archive_jobs::delete(&mut tx, job.archive_id).await?;
+            tx.commit().await?;
+            return Ok(());
+        }
+        if job.attempts > MAX_ATTEMPTS {
+            // Earlier attempts stopped without storing a result, e.g. because
+            // saving it failed, so give up instead of fetching forever
+            let error = job
+                .last_error
+                .as_ref()
+                .map_or(archive::Error::UnexpectedInternal, |error| error.0.clone());
+            tracing::info!(?error, "Archiving failed too often, giving up");
+            let archive = db::archives::update(&mut tx, job.archive_id, &Err(error)).await?;
+            db::archive_jobs::delete(&mut tx, job.archive_id).await?;
+            tx.commit().await?;
+            let _ = self.processed_archive_id_sender.send(archive.id);
+            return Ok(());
+        }
         let bookmark = db::bookmarks::by_id(&mut tx, pending.bookmark_id).await?;
+        tx.commit().await?;
 
-        tracing::info!(?bookmark, "Archiving bookmark");
-        let article = self.get_article(&bookmark.url).await;
-        if article.is_err() {
-            tracing::info!(?article, "Fetching complete");
+        tracing::info!(?bookmark, attempt = job.attempts, "Archiving bookmark");
+        let fetched = fetch(&self.client, &bookmark.url).await;
+
+        let mut tx = self.db_pool.begin().await?;
+        if let Err(error) = &fetched
+            && error.is_transient()
+            && job.attempts < MAX_ATTEMPTS
+        {
+            let delay = retry_delay(job.attempts).max(retry_after(error));
+            tracing::info!(?error, ?delay, "Archiving failed, retrying later");
+            db::archive_jobs::retry_later(&mut tx, job.archive_id, error, delay).await?;
+            tx.commit().await?;
+            return Ok(());
         }
-        let archive = self.save_archive(&mut tx, &pending, &article).await;
-        match &archive {
-            Ok(archive) => {
-                let _ = self.processed_archive_id_sender.send(archive.id);
-            }
-            Err(_) => {
-                tracing::error!(?archive, "Could not save archive");
-            }
+        if fetched.is_err() {
+            tracing::info!(?fetched, "Fetching complete");
         }
 
+        let (article, resources, response) = match fetched {
+            Ok(Fetched {
+                article,
+                resources,
+                response,
+            }) => (Ok(article), resources, Some(response)),
+            Err(error) => (Err(error), Vec::new(), None),
+        };
+        let archive = db::archives::update(&mut tx, job.archive_id, &article).await?;
+        for resource in &resources {
+            self.resource_store
+                .save(&mut tx, archive.id, resource)
+                .await?;
+        }
+        if self.store_raw_responses
+            && let Some(response) = &response
+        {
+            db::archive_responses::insert(&mut tx, archive.id, response).await?;
+        }
+        db::archive_jobs::delete(&mut tx, job.archive_id).await?;
         tx.commit().await?;
 
         tracing::info!(?archive, "Archived bookmark");
+        let _ = self.processed_archive_id_sender.send(archive.id);
 
-        archive
+        Ok(())
     }
 
-    async fn get_article(&mut self, url: &str) -> Result<legible::Article, archive::Error> {
-        let html = archive::fetch_url_as_text(url).await?;
-        tracing::debug!(html_length = html.len(), "Fetched website HTML");
-        let article = archive::make_readable(url.parse()?, &html)?;
-        tracing::debug!(
-            readable_html_length = article.content.len(),
-            "Extracted readable HTML"
-        );
-
-        Ok(article)
+    async fn time_until_next_job(&self) -> Duration {
+        let next_attempt_at = match self.db_pool.begin().await {
+            Ok(mut tx) => db::archive_jobs::next_attempt_at(&mut tx)
+                .await
+                .ok()
+                .flatten(),
+            Err(_) => None,
+        };
+
+        next_attempt_at.map_or(POLL_INTERVAL, |next| {
+            // Jobs that are already due don't need any waiting
+            Duration::try_from(next - OffsetDateTime::now_utc())
+                .unwrap_or(Duration::ZERO)
+                .min(POLL_INTERVAL)
+        })
     }
+}
+
+/// Everything that's fetched when archiving a page.
+#[derive(Debug)]
+struct Fetched {
+    article: legible::Article,
+    resources: Vec<archive::Resource>,
+    response: archive::RawResponse,
+}
 
-    async fn save_archive(
-        &mut self,
-        tx: &mut AppTx,
-        archive: &db::Archive,
-        article: &std::result::Result<legible::Article, archive::Error>,
-    ) -> Result<db::Archive> {
-        let archive = db::archives::update(tx, archive.id, article).await?;
+async fn fetch(client: &archive::Client, url: &str) -> Result<Fetched, archive::Error> {
+    let deadline = Instant::now() + FETCH_DEADLINE;
+    let response = tokio::time::timeout_at(deadline, client.fetch_page(url))
+        .await
+        .map_err(|_| archive::Error::Timeout)??;
+    let html = response.text();
+    tracing::debug!(html_length = html.len(), "Fetched website HTML");
+    let mut article = archive::make_readable(url.parse()?, &html)?;
+    tracing::debug!(
+        readable_html_length = article.content.len(),
+        "Extracted readable HTML"
+    );
+
+    let (content, resources) = archive::download_images(client, &article.content, deadline).await;
+    article.content = content;
+    tracing::debug!(?resources, "Downloaded images");
+
+    Ok(Fetched {
+        article,
+        resources,
+        response,
+    })
+}
+
+/// Exponential backoff after the given number of failed attempts.
+fn retry_delay(attempts: i32) -> Duration {
+    let exponent = u32::try_from(attempts - 1).unwrap_or(0).min(10);
+    RETRY_BASE_DELAY * 2_u32.pow(exponent)
+}
 
-        Ok(archive)
+/// How long the website asked us to wait before trying again.
+fn retry_after(error: &archive::Error) -> Duration {
+    match error {
+        archive::Error::Status {
+            retry_after_secs: Some(secs),
+            ..
+        } => Duration::from_secs(*secs),
+        _ => Duration::ZERO,
     }
 }
 
 #[derive(Clone)]
 pub struct QueueHandle {
-    sender: mpsc::Sender<Message>,
-    // Intentionally do not store a receiver here: there's no associated process that receives
-    // messages, so having a receiver here would clog up the channel
+    db_pool: sqlx::PgPool,
+    new_job: Arc<Notify>,
     processed_archive_id_sender: broadcast::Sender<Uuid>,
 }
 
 impl QueueHandle {
-    pub fn new(db_pool: sqlx::PgPool) -> Self {
-        let (sender, receiver) = mpsc::channel(50);
+    /// Start `workers` tasks that archive websites concurrently.
+    /// `store_raw_responses` keeps the responses of websites along with the
+    /// readable archive.
+    pub fn new(
+        db_pool: sqlx::PgPool,
+        client: archive::Client,
+        resource_store: archive::ResourceStore,
+        store_raw_responses: bool,
+        workers: usize,
+    ) -> Self {
+        let new_job = Arc::new(Notify::new());
 
         // Since the messages here are only UUIDs, we can afford a large buffer size to
         // support slow receivers.
         let (processed_archive_id_sender, _) = broadcast::channel(200);
 
-        let queue = Queue::new(receiver, db_pool, processed_archive_id_sender.clone());
-        tokio::spawn(queue.process());
+        for _ in 0..workers {
+            let worker = Worker {
+                db_pool: db_pool.clone(),
+                client: client.clone(),
+                resource_store: resource_store.clone(),
+                store_raw_responses,
+                new_job: new_job.clone(),
+                processed_archive_id_sender: processed_archive_id_sender.clone(),
+            };
+            tokio::spawn(worker.process());
+        }
 
         Self {
-            sender,
+            db_pool,
+            new_job,
             processed_archive_id_sender,
         }
     }
 
-    /// Dispatch a bookmark for archiving, but ignore any failures.
-    pub fn archive_in_background(&self, archive_id: Uuid) {
-        let (send, _recv) = oneshot::channel();
-        let msg = Message::ArchiveBookmark(ArchiveTask {
-            archive_id,
-            respond_to: send,
-        });
+    /// Wake up a worker to process a newly inserted pending archive. The
+    /// archive is processed later in any case, this just avoids waiting.
+    pub fn wake_worker(&self) {
+        self.new_job.notify_one();
+    }
 
-        let _ = self.sender.try_send(msg);
+    /// Spread out the processing of many archives, so that other archives can
+    /// still be processed in between.
+    pub fn archive_all_in_background(&self, archive_ids: Vec<Uuid>) {
+        let db_pool = self.db_pool.clone();
+        let new_job = self.new_job.clone();
+        tokio::spawn(async move {
+            let result = async {
+                let mut tx = db_pool.begin().await?;
+                db::archive_jobs::stagger(&mut tx, &archive_ids, BATCH_INTERVAL).await?;
+                tx.commit().await?;
+                anyhow::Ok(())
+            }
+            .await;
+            if let Err(error) = result {
+                tracing::error!(?error, "Could not schedule archives");
+            }
+            new_job.notify_one();
+        });
     }
 
     /// Wait for the given archive id to be processed.
@@ -198,3 +319,15 @@ impl QueueHandle {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles() {
+        assert_eq!(retry_delay(1), Duration::from_secs(60));
+        assert_eq!(retry_delay(2), Duration::from_secs(120));
+        assert_eq!(retry_delay(4), Duration::from_secs(480));
+    }
+}
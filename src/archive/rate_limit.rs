@@ -0,0 +1,129 @@
+//! Keep ties from sending too many requests to the same host, e.g. when
+//! archiving a bulk import of bookmarks from one website.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+/// Minimum time between the start of two requests to the same host.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Maximum number of requests to the same host that run at the same time.
+const MAX_CONCURRENT_REQUESTS: usize = 2;
+
+/// Don't wait for hosts that are blocked for longer than this, see
+/// [`HostLimiter::block`].
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+pub struct HostLimiter {
+    hosts: Mutex<HashMap<String, Host>>,
+}
+
+struct Host {
+    in_flight: usize,
+    next_request_at: Instant,
+}
+
+/// Allows sending a request to a host. The request counts as running until
+/// the permit is dropped.
+pub struct Permit<'a> {
+    limiter: &'a HostLimiter,
+    host: String,
+}
+
+impl HostLimiter {
+    /// Wait until a request to `host` is allowed. If the host is blocked for
+    /// a long time, returns how long it's blocked for instead of waiting.
+    pub async fn acquire(&self, host: &str) -> Result<Permit<'_>, Duration> {
+        loop {
+            let wait = {
+                let mut hosts = self.hosts.lock().unwrap_or_else(PoisonError::into_inner);
+                let now = Instant::now();
+                // Forget hosts we're done with
+                hosts.retain(|_, host| host.in_flight > 0 || host.next_request_at > now);
+
+                let entry = hosts.entry(host.to_string()).or_insert(Host {
+                    in_flight: 0,
+                    next_request_at: now,
+                });
+                if entry.in_flight < MAX_CONCURRENT_REQUESTS && entry.next_request_at <= now {
+                    entry.in_flight += 1;
+                    entry.next_request_at = now + MIN_REQUEST_INTERVAL;
+                    return Ok(Permit {
+                        limiter: self,
+                        host: host.to_string(),
+                    });
+                }
+
+                let blocked_for = entry.next_request_at - now;
+                if blocked_for > MAX_WAIT {
+                    return Err(blocked_for);
+                }
+                // Check again once a running request might be done
+                blocked_for.max(MIN_REQUEST_INTERVAL / 2)
+            };
+
+            tracing::debug!(host, ?wait, "Waiting for rate limit");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Don't send any new requests to `host` for the given duration, e.g.
+    /// because it answered with a `Retry-After` header.
+    pub fn block(&self, host: &str, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut hosts = self.hosts.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = hosts.entry(host.to_string()).or_insert(Host {
+            in_flight: 0,
+            next_request_at: until,
+        });
+        entry.next_request_at = entry.next_request_at.max(until);
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let mut hosts = self
+            .limiter
+            .hosts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(host) = hosts.get_mut(&self.host) {
+            host.in_flight = host.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn limits_requests_per_host() {
+        let limiter = HostLimiter::default();
+        let start = Instant::now();
+
+        let first = limiter.acquire("example.com").await.unwrap();
+        // Other hosts don't have to wait
+        let _other = limiter.acquire("example.org").await.unwrap();
+        assert!(start.elapsed() < MIN_REQUEST_INTERVAL);
+
+        drop(first);
+        let _second = limiter.acquire("example.com").await.unwrap();
+        assert!(start.elapsed() >= MIN_REQUEST_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn blocked_hosts_are_not_waited_for() {
+        let limiter = HostLimiter::default();
+        limiter.block("example.com", Duration::from_secs(60 * 60));
+
+        let blocked_for = limiter.acquire("example.com").await.err();
+        assert!(blocked_for.is_some_and(|duration| duration > MAX_WAIT));
+    }
+}
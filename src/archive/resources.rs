@@ -0,0 +1,273 @@
+//! Images of archived pages are downloaded along with the page, so that the
+//! archive still shows them when the website changes or goes offline.
+//!
+//! - Each file is stored once, identified by the SHA-256 hash of its contents,
+//!   either in the database or in a directory configured by the server
+//!   operator.
+//! - `src` attributes in the archived HTML are rewritten to
+//!   `/archive_resources/{hash}`. Images that can't be downloaded keep pointing
+//!   to the website.
+//! - The number and total size of images per archive is limited.
+//! - Files that no archive uses anymore are deleted periodically.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use anyhow::Context;
+use itertools::Itertools;
+use sha2::{Digest, Sha256};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::{
+    archive,
+    db::{self, AppTx},
+    response_error::ResponseResult,
+};
+
+/// Maximum number of images downloaded for one archive.
+const MAX_IMAGES: usize = 30;
+
+/// Maximum total size of the images of one archive. Images that don't fit
+/// anymore keep pointing to the website.
+const MAX_TOTAL_SIZE_BYTES: usize = 20 * 1000 * 1000; // ~ 20 megabytes
+
+/// Time between deleting resources that aren't used anymore.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A downloaded file, ready to be stored.
+pub struct Resource {
+    /// Where the file was downloaded from
+    pub url: String,
+    pub hash: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl std::fmt::Debug for Resource {
+    // Don't log the whole file
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Resource")
+            .field("url", &self.url)
+            .field("hash", &self.hash)
+            .field("content_type", &self.content_type)
+            .field("size", &self.data.len())
+            .finish()
+    }
+}
+
+/// Download the images referenced by readable HTML, and point the HTML to the
+/// downloaded copies. Stops downloading once `deadline` has passed.
+pub async fn download_images(
+    client: &archive::Client,
+    html: &str,
+    deadline: Instant,
+) -> (String, Vec<Resource>) {
+    let mut resources = Vec::new();
+    let mut paths = HashMap::new();
+    let mut total_size = 0;
+
+    for url in image_urls(html).into_iter().take(MAX_IMAGES) {
+        let Ok(image) = tokio::time::timeout_at(deadline, client.fetch_image(&url)).await else {
+            tracing::debug!(%url, "Ran out of time for archiving images");
+            break;
+        };
+        let (content_type, data) = match image {
+            Ok(image) => image,
+            Err(error) => {
+                tracing::debug!(?error, %url, "Could not archive image");
+                continue;
+            }
+        };
+        if total_size + data.len() > MAX_TOTAL_SIZE_BYTES {
+            tracing::debug!(%url, "Image doesn't fit into the archive size limit");
+            continue;
+        }
+        total_size += data.len();
+
+        let hash = format!("{:x}", Sha256::digest(&data));
+        paths.insert(url.clone(), path(&hash));
+        resources.push(Resource {
+            url,
+            hash,
+            content_type,
+            data,
+        });
+    }
+
+    (rewrite_image_urls(html, paths), resources)
+}
+
+/// Collect the unique `src` attributes of all images.
+fn image_urls(html: &str) -> Vec<String> {
+    let urls = Arc::new(Mutex::new(Vec::new()));
+    let collected = urls.clone();
+
+    let mut builder = ammonia::Builder::default();
+    builder.attribute_filter(move |element, attribute, value| {
+        if element == "img" && attribute == "src" {
+            collected
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(value.to_string());
+        }
+        Some(value.into())
+    });
+    builder.clean(html);
+
+    let urls = std::mem::take(&mut *urls.lock().unwrap_or_else(PoisonError::into_inner));
+    urls.into_iter().unique().collect()
+}
+
+/// Where a resource is served by ties.
+pub fn path(hash: &str) -> String {
+    format!("/archive_resources/{hash}")
+}
+
+/// Replace image `src` attributes using the given map from old to new URLs.
+pub fn rewrite_image_urls(html: &str, paths: HashMap<String, String>) -> String {
+    let mut builder = ammonia::Builder::default();
+    builder.attribute_filter(move |element, attribute, value| {
+        if element == "img"
+            && attribute == "src"
+            && let Some(path) = paths.get(value)
+        {
+            return Some(Cow::Owned(path.clone()));
+        }
+        Some(value.into())
+    });
+
+    builder.clean(html).to_string()
+}
+
+/// Where the contents of resources are stored. Metadata is always stored in
+/// the database.
+#[derive(Clone, Debug)]
+pub enum ResourceStore {
+    Database,
+    /// One file per resource, named after its hash
+    Directory(PathBuf),
+}
+
+impl ResourceStore {
+    pub fn new(directory: Option<PathBuf>) -> Self {
+        directory.map_or(Self::Database, Self::Directory)
+    }
+
+    pub async fn save(
+        &self,
+        tx: &mut AppTx,
+        archive_id: Uuid,
+        resource: &Resource,
+    ) -> ResponseResult<()> {
+        let size = i64::try_from(resource.data.len()).context("Resource is too large")?;
+        let data = match self {
+            Self::Database => Some(resource.data.as_slice()),
+            Self::Directory(directory) => {
+                let path = file_path(directory, &resource.hash);
+                if !path.exists() {
+                    std::fs::create_dir_all(directory).with_context(|| {
+                        format!("Failed to create directory {}", directory.display())
+                    })?;
+                    std::fs::write(&path, &resource.data)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                }
+                None
+            }
+        };
+
+        db::archive_resources::insert(
+            tx,
+            archive_id,
+            &resource.url,
+            &resource.hash,
+            &resource.content_type,
+            size,
+            data,
+        )
+        .await
+    }
+
+    /// Load a resource's content type and contents.
+    pub async fn load(&self, tx: &mut AppTx, hash: &str) -> ResponseResult<(String, Vec<u8>)> {
+        let resource = db::archive_resources::by_hash(tx, hash).await?;
+        let data = match (resource.data, self) {
+            (Some(data), _) => data,
+            (None, Self::Directory(directory)) => {
+                let path = file_path(directory, &resource.hash);
+                std::fs::read(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?
+            }
+            (None, Self::Database) => {
+                return Err(anyhow::anyhow!(
+                    "Resource {hash} is stored in a directory, but no resources directory is \
+                     configured"
+                )
+                .into());
+            }
+        };
+
+        Ok((resource.content_type, data))
+    }
+
+    /// Periodically delete resources that no archive uses anymore.
+    pub async fn continuously_delete_unused(self, db_pool: sqlx::PgPool) {
+        loop {
+            if let Err(error) = self.delete_unused(&db_pool).await {
+                tracing::error!(?error, "Could not delete unused archive resources");
+            }
+            tokio::time::sleep(CLEANUP_INTERVAL).await;
+        }
+    }
+
+    /// Delete resources that no archive uses anymore.
+    pub async fn delete_unused(&self, db_pool: &sqlx::PgPool) -> anyhow::Result<()> {
+        let mut tx = db_pool.begin().await?;
+        let hashes = db::archive_resources::delete_unused(&mut tx).await?;
+        tx.commit().await?;
+
+        if let Self::Directory(directory) = self {
+            for hash in &hashes {
+                let path = file_path(directory, hash);
+                if let Err(error) = std::fs::remove_file(&path) {
+                    tracing::info!(?error, ?path, "Could not delete unused archive resource");
+                }
+            }
+        }
+        tracing::debug!(count = hashes.len(), "Deleted unused archive resources");
+
+        Ok(())
+    }
+}
+
+fn file_path(directory: &Path, hash: &str) -> PathBuf {
+    directory.join(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_image_urls() {
+        let html = r#"<p><img src="https://example.com/a.png" alt="A"></p><img src="https://example.com/b.png"><img src="https://example.com/a.png">"#;
+        assert_eq!(
+            image_urls(html),
+            vec!["https://example.com/a.png", "https://example.com/b.png"]
+        );
+
+        let paths = HashMap::from([(
+            "https://example.com/a.png".to_string(),
+            "/archive_resources/abc".to_string(),
+        )]);
+        assert_eq!(
+            rewrite_image_urls(html, paths),
+            r#"<p><img src="/archive_resources/abc" alt="A"></p><img src="https://example.com/b.png"><img src="/archive_resources/abc">"#
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Minimal parser for `robots.txt` files following
+//! [RFC 9309](https://www.rfc-editor.org/rfc/rfc9309.html).
+
+/// The rules of a `robots.txt` file that apply to one user agent.
+#[derive(Debug, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+impl Rules {
+    /// Collect the rules for the crawler with the given product token, e.g.
+    /// `ties`. Falls back to the rules for all crawlers (`*`) if no group
+    /// mentions the product token.
+    pub fn parse(robots_txt: &str, product_token: &str) -> Self {
+        let mut matching = Vec::new();
+        let mut wildcard = Vec::new();
+        let mut mentions_product = false;
+
+        // User agents of the group that's currently being parsed
+        let mut user_agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in robots_txt.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    // A user agent line after rules starts a new group
+                    if in_rules {
+                        user_agents.clear();
+                        in_rules = false;
+                    }
+                    mentions_product |= value.eq_ignore_ascii_case(product_token);
+                    user_agents.push(value.to_ascii_lowercase());
+                }
+                key @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    // An empty disallow rule allows everything
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = || Rule {
+                        allow: key == "allow",
+                        pattern: value.to_string(),
+                    };
+                    if user_agents
+                        .iter()
+                        .any(|agent| agent.eq_ignore_ascii_case(product_token))
+                    {
+                        matching.push(rule());
+                    }
+                    if user_agents.iter().any(|agent| agent == "*") {
+                        wildcard.push(rule());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            rules: if mentions_product { matching } else { wildcard },
+        }
+    }
+
+    /// Check a path, including its query. The most specific matching rule
+    /// wins, and allow rules win over disallow rules that are just as
+    /// specific.
+    pub fn is_allowed(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+}
+
+/// Match a path against a pattern that may contain `*` wildcards and end with
+/// `$` to match the end of the path.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return true;
+    };
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts = parts.collect::<Vec<_>>();
+    for (index, part) in parts.iter().enumerate() {
+        let is_last = index == parts.len() - 1;
+        if is_last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS_TXT: &str = "
+        # Keep out of the admin area
+        User-agent: *
+        Disallow: /admin
+        Allow: /admin/public
+        Disallow: /*.pdf$
+
+        User-agent: BadBot
+        User-agent: ties
+        Disallow: /private/
+        Disallow:
+    ";
+
+    #[test]
+    fn uses_group_of_product_token() {
+        let rules = Rules::parse(ROBOTS_TXT, "ties");
+        assert!(!rules.is_allowed("/private/page"));
+        // The wildcard group doesn't apply
+        assert!(rules.is_allowed("/admin"));
+        assert!(rules.is_allowed("/"));
+    }
+
+    #[test]
+    fn falls_back_to_wildcard_group() {
+        let rules = Rules::parse(ROBOTS_TXT, "otherbot");
+        assert!(!rules.is_allowed("/admin/settings"));
+        assert!(rules.is_allowed("/admin/public/page"));
+        assert!(!rules.is_allowed("/files/paper.pdf"));
+        assert!(rules.is_allowed("/files/paper.pdf?download=1"));
+        assert!(rules.is_allowed("/private/page"));
+    }
+
+    #[test]
+    fn empty_robots_txt_allows_everything() {
+        let rules = Rules::parse("", "ties");
+        assert!(rules.is_allowed("/anything"));
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(matches("/*/edit", "/posts/1/edit"));
+        assert!(matches("/posts*", "/posts"));
+        assert!(!matches("/*/edit$", "/posts/1/edit/more"));
+        assert!(matches("/", "/anything"));
+    }
+}
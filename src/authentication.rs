@@ -2,18 +2,20 @@ use anyhow::{Context, anyhow};
 use argon2::PasswordVerifier;
 use axum::{
     extract::{FromRequestParts, OptionalFromRequestParts, OriginalUri},
-    http::request::Parts,
+    http::{header, request::Parts},
     response::Redirect,
 };
 use garde::Validate;
 use percent_encoding::utf8_percent_encode;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tower_sessions::Session;
 use url::Url;
 use uuid::Uuid;
 
 use crate::{
-    db::{self, AppTx, User},
+    api_error::{ApiError, ApiResult},
+    db::{self, AppTx, User, api_tokens::Scope},
     forms::users::{CreateOidcUser, CreateUser, Credentials},
     response_error::{ResponseError, ResponseResult},
     server::AppState,
@@ -47,6 +49,22 @@ pub fn verify_password(user: &db::User, password: &str) -> ResponseResult<()> {
     Ok(())
 }
 
+/// Generate a new API token. Tokens start with `ties_` so they're easy to
+/// recognize, for example by secret scanners.
+pub fn generate_api_token() -> String {
+    format!(
+        "ties_{}{}",
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// API tokens are long random strings, so unlike passwords, a fast hash is
+/// enough to protect them. This lets us look up tokens by their hash.
+pub fn hash_api_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
 pub async fn login(tx: &mut AppTx, session: Session, creds: &Credentials) -> ResponseResult<()> {
     let user = db::users::by_username(tx, &creds.username).await?;
 
@@ -207,3 +225,56 @@ impl OptionalFromRequestParts<AppState> for AuthUser {
         Ok(Some(auth_user?))
     }
 }
+
+/// A user authenticated with an API token in the `Authorization: Bearer`
+/// header.
+#[derive(Debug)]
+pub struct ApiUser {
+    pub user_id: Uuid,
+    pub ap_user_id: Uuid,
+    pub scope: Scope,
+}
+
+impl ApiUser {
+    /// Look up the user for an API token, no matter how it was sent.
+    pub async fn from_token(state: &AppState, token: &str) -> ApiResult<Self> {
+        let mut tx = state.pool.begin().await?;
+        let user = db::api_tokens::authenticate(&mut tx, &hash_api_token(token.trim()))
+            .await?
+            .ok_or(ApiError::NotAuthenticated)?;
+        tx.commit().await?;
+
+        Ok(Self {
+            user_id: user.user_id,
+            ap_user_id: user.ap_user_id,
+            scope: user.scope,
+        })
+    }
+
+    /// Make sure the token may be used for creating, editing and deleting
+    /// things.
+    pub fn require_write(&self) -> ApiResult<()> {
+        match self.scope {
+            Scope::Write => Ok(()),
+            Scope::Read => Err(ApiError::ReadOnlyToken),
+        }
+    }
+}
+
+impl FromRequestParts<AppState> for ApiUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        req: &mut Parts,
+        state: &AppState,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let token = req
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(ApiError::NotAuthenticated)?;
+
+        Self::from_token(state, token).await
+    }
+}
@@ -1,6 +1,6 @@
 use std::{net::SocketAddr, path::PathBuf};
 
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use clap::{Args, Parser, Subcommand};
 use garde::Validate;
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
@@ -9,9 +9,9 @@ use url::Url;
 #[cfg(debug_assertions)]
 use crate::insert_demo_data::insert_demo_data;
 use crate::{
-    archive, db, federation,
+    archive, db, export, federation, feed_subscriptions,
     forms::users::CreateUser,
-    oidc,
+    import, oidc,
     server::{self, AppState},
 };
 
@@ -78,11 +78,60 @@ enum Command {
         demo_mode: bool,
         #[clap(flatten)]
         oidc_args: Option<OidcArgs>,
+        /// Number of websites to archive at the same time.
+        #[clap(long, env, default_value = "2", value_parser = clap::value_parser!(u16).range(1..))]
+        archive_workers: u16,
+        /// User agent to send when archiving websites and fetching feeds.
+        /// Defaults to `ties/<version>`, followed by a link to the ties
+        /// repository.
+        #[clap(long, env)]
+        archive_user_agent: Option<String>,
+        /// Don't archive pages that the website's robots.txt disallows for
+        /// the archive user agent.
+        #[clap(long, env, default_value = "false")]
+        archive_respect_robots_txt: bool,
+        /// Directory for storing images of archived pages. If not set, images
+        /// are stored in the database.
+        #[clap(long, env)]
+        archive_resources_dir: Option<PathBuf>,
+        /// Store the response of each archived website, so that archives can
+        /// be exported as WARC files that contain the original page.
+        #[clap(long, env, default_value = "false")]
+        archive_store_raw_responses: bool,
     },
     Db {
         #[clap(subcommand)]
         command: DbCommand,
     },
+    /// Import bookmarks from a bookmarks HTML file, as exported by browsers, an
+    /// export from Pinboard, Pocket, Raindrop.io or Linkding, or restore a
+    /// backup exported from ties. Archives for the imported bookmarks are
+    /// created by the running server.
+    Import {
+        /// User to import the bookmarks for
+        #[clap(long)]
+        username: String,
+        /// What to do with bookmarks the user already saved
+        #[clap(long, value_enum, default_value_t)]
+        duplicates: import::Duplicates,
+        #[clap(long, value_enum, default_value_t)]
+        format: import::Format,
+        /// Show what would be imported without saving anything
+        #[clap(long)]
+        dry_run: bool,
+        file: PathBuf,
+    },
+    /// Export a user's bookmarks and lists
+    Export {
+        /// User to export
+        #[clap(long)]
+        username: String,
+        #[clap(long, value_enum, default_value_t)]
+        format: ExportFormat,
+        /// Where to write the export. Prints to stdout if not set.
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
     #[cfg(debug_assertions)]
     /// Put some demo data into the database
     InsertDemoData {
@@ -119,6 +168,15 @@ impl From<AdminCredentials> for Option<CreateUser> {
     }
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default)]
+enum ExportFormat {
+    /// Bookmarks HTML file that can be imported by browsers
+    #[default]
+    Netscape,
+    /// JSON backup that can be imported by ties
+    Backup,
+}
+
 #[derive(Subcommand, Debug)]
 enum DbCommand {
     Migrate,
@@ -159,6 +217,11 @@ pub async fn run() -> Result<()> {
             tls_key,
             demo_mode,
             oidc_args,
+            archive_workers,
+            archive_user_agent,
+            archive_respect_robots_txt,
+            archive_resources_dir,
+            archive_store_raw_responses,
         } => {
             let pool = db::pool(&cli.config.database_url).await?;
 
@@ -175,17 +238,34 @@ pub async fn run() -> Result<()> {
 
             let oidc_state = oidc::State::initialize(&base_url, oidc_args).await;
 
-            let archive_queue = archive::QueueHandle::new(pool.clone());
+            let default_archive_config = archive::Config::default();
+            let archive_client = archive::Client::new(archive::Config {
+                user_agent: archive_user_agent.unwrap_or(default_archive_config.user_agent),
+                respect_robots_txt: archive_respect_robots_txt,
+            })
+            .context("Failed to create client for archiving websites")?;
+            let archive_resource_store = archive::ResourceStore::new(archive_resources_dir);
+            let archive_queue = archive::QueueHandle::new(
+                pool.clone(),
+                archive_client.clone(),
+                archive_resource_store.clone(),
+                archive_store_raw_responses,
+                usize::from(archive_workers),
+            );
 
-            let app = server::app(AppState {
+            let state = AppState {
                 pool: pool.clone(),
                 base_url: base_url.clone(),
                 demo_mode,
                 oidc_state,
                 federation_config: federation::config::new_config(pool, base_url.clone()).await?,
+                archive_client,
+                archive_resource_store,
                 archive_queue,
-            })
-            .await?;
+            };
+            feed_subscriptions::poll_in_background(state.clone());
+
+            let app = server::app(state).await?;
             server::start(listen_address, base_url.clone(), app, tls_cert, tls_key).await?;
         }
         Command::Db {
@@ -194,6 +274,62 @@ pub async fn run() -> Result<()> {
             let pool = db::pool(&cli.config.database_url).await?;
             db::migrate(&pool, &base_url, None).await?;
         }
+        Command::Import {
+            username,
+            duplicates,
+            format,
+            dry_run,
+            file,
+        } => {
+            let input = std::fs::read(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let parsed = import::parse(format, &String::from_utf8_lossy(&input))?;
+
+            let pool = db::pool(&cli.config.database_url).await?;
+            let mut tx = pool.begin().await?;
+            let user = db::users::by_username(&mut tx, &username)
+                .await
+                .with_context(|| format!("Failed to find user {username}"))?;
+            let summary = import::save(
+                &mut tx,
+                user.id,
+                user.ap_user_id,
+                &base_url,
+                parsed,
+                duplicates,
+            )
+            .await?;
+
+            if dry_run {
+                println!("Dry run, nothing was saved.");
+            } else {
+                tx.commit().await?;
+            }
+
+            print_import_summary(&summary);
+        }
+        Command::Export {
+            username,
+            format,
+            output,
+        } => {
+            let pool = db::pool(&cli.config.database_url).await?;
+            let mut tx = pool.begin().await?;
+            let user = db::users::by_username(&mut tx, &username)
+                .await
+                .with_context(|| format!("Failed to find user {username}"))?;
+            let backup = export::backup(&mut tx, &user).await?;
+            let content = match format {
+                ExportFormat::Netscape => export::netscape_html(&backup),
+                ExportFormat::Backup => serde_json::to_string_pretty(&backup)?,
+            };
+
+            match output {
+                Some(path) => std::fs::write(&path, content)
+                    .with_context(|| format!("Failed to write {}", path.display()))?,
+                None => print!("{content}"),
+            }
+        }
         #[cfg(debug_assertions)]
         Command::InsertDemoData {
             dev_user_credentials,
@@ -210,3 +346,17 @@ pub async fn run() -> Result<()> {
 
     Ok(())
 }
+
+fn print_import_summary(summary: &import::Summary) {
+    println!("Bookmarks created: {}", summary.bookmarks_created);
+    println!("Bookmarks merged: {}", summary.bookmarks_merged);
+    println!("Bookmarks skipped: {}", summary.bookmarks_skipped);
+    println!("Lists created: {}", summary.lists_created);
+    println!("Lists reused: {}", summary.lists_reused);
+    for url in &summary.invalid_urls {
+        println!("Skipped invalid URL: {url}");
+    }
+    for account in &summary.accounts_to_follow {
+        println!("Follow again: {account}");
+    }
+}
@@ -32,6 +32,17 @@ pub struct ApUser {
     pub bio: Option<String>,
 }
 
+impl ApUser {
+    /// The username including the domain, e.g. `rafael@ties.pub`. Profile
+    /// pages are available at `/user/{handle}`.
+    pub fn handle(&self) -> String {
+        match webfinger::Resource::from_name_and_url(self.username.clone(), self.ap_id.inner()) {
+            Ok(resource) => format!("{}@{}", resource.name, resource.domain),
+            Err(_) => self.username.clone(),
+        }
+    }
+}
+
 #[derive(FromRow, Debug)]
 struct ApUserRow {
     id: Uuid,
@@ -236,6 +247,7 @@ pub async fn list_followers(tx: &mut AppTx, followed_id: Uuid) -> ResponseResult
         select ap_users.* from follows
             join ap_users on ap_users.id = follows.follower_id
         where follows.following_id = $1
+            and follows.accepted
         "#,
         followed_id
     )
@@ -247,3 +259,82 @@ pub async fn list_followers(tx: &mut AppTx, followed_id: Uuid) -> ResponseResult
 
     Ok(users)
 }
+
+pub async fn list_following(tx: &mut AppTx, follower_id: Uuid) -> ResponseResult<Vec<ApUser>> {
+    let users = query_as!(
+        ApUserRow,
+        r#"
+        select ap_users.* from follows
+            join ap_users on ap_users.id = follows.following_id
+        where follows.follower_id = $1
+            and follows.accepted
+        "#,
+        follower_id
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(ApUser::try_from)
+    .collect::<Result<_, _>>()?;
+
+    Ok(users)
+}
+
+pub async fn list_muted(tx: &mut AppTx, muter_id: Uuid) -> ResponseResult<Vec<ApUser>> {
+    let users = query_as!(
+        ApUserRow,
+        r#"
+        select ap_users.* from mutes
+            join ap_users on ap_users.id = mutes.muted_id
+        where mutes.muter_id = $1
+        order by mutes.created_at
+        "#,
+        muter_id
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(ApUser::try_from)
+    .collect::<Result<_, _>>()?;
+
+    Ok(users)
+}
+
+pub async fn list_by_ids(tx: &mut AppTx, ids: &[Uuid]) -> ResponseResult<Vec<ApUser>> {
+    let users = query_as!(
+        ApUserRow,
+        r#"
+        select * from ap_users
+        where id = any($1)
+        "#,
+        ids
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(ApUser::try_from)
+    .collect::<Result<_, _>>()?;
+
+    Ok(users)
+}
+
+/// The users on other servers following a public list.
+pub async fn list_list_followers(tx: &mut AppTx, list_id: Uuid) -> ResponseResult<Vec<ApUser>> {
+    let users = query_as!(
+        ApUserRow,
+        r#"
+        select ap_users.* from list_followers
+            join ap_users on ap_users.id = list_followers.follower_id
+        where list_followers.list_id = $1
+        order by list_followers.created_at
+        "#,
+        list_id
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(ApUser::try_from)
+    .collect::<Result<_, _>>()?;
+
+    Ok(users)
+}
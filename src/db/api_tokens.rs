@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, query_as};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::{forms::api_tokens::CreateApiToken, response_error::ResponseResult};
+
+/// What requests authenticated with an API token are allowed to do.
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[sqlx(type_name = "api_token_scope")]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Only read bookmarks, lists and links
+    #[default]
+    Read,
+    /// Also create, edit and delete them
+    Write,
+}
+
+#[derive(FromRow, Debug)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub name: String,
+    pub scope: Scope,
+    pub created_at: OffsetDateTime,
+    pub last_used_at: Option<OffsetDateTime>,
+}
+
+/// The user a request was authenticated for with an API token.
+#[derive(FromRow, Debug)]
+pub struct TokenUser {
+    pub user_id: Uuid,
+    pub ap_user_id: Uuid,
+    pub scope: Scope,
+}
+
+/// Save a new token. We only store a hash of it, so the token itself can't be
+/// shown again afterwards.
+pub async fn insert(
+    tx: &mut AppTx,
+    user_id: Uuid,
+    create_token: CreateApiToken,
+    token_hash: &str,
+) -> ResponseResult<ApiToken> {
+    let token = query_as!(
+        ApiToken,
+        r#"
+        insert into api_tokens
+        (user_id, name, scope, token_hash)
+        values ($1, $2, $3, $4)
+        returning id, name, scope as "scope: _", created_at, last_used_at
+        "#,
+        user_id,
+        create_token.name,
+        create_token.scope as Scope,
+        token_hash,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(token)
+}
+
+pub async fn list_by_user_id(tx: &mut AppTx, user_id: Uuid) -> ResponseResult<Vec<ApiToken>> {
+    let tokens = query_as!(
+        ApiToken,
+        r#"
+        select id, name, scope as "scope: _", created_at, last_used_at
+        from api_tokens
+        where user_id = $1
+        order by created_at desc
+        "#,
+        user_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(tokens)
+}
+
+/// Revoke one of the user's tokens.
+pub async fn delete_by_id(tx: &mut AppTx, id: Uuid, user_id: Uuid) -> ResponseResult<ApiToken> {
+    let token = query_as!(
+        ApiToken,
+        r#"
+        delete from api_tokens
+        where id = $1 and user_id = $2
+        returning id, name, scope as "scope: _", created_at, last_used_at
+        "#,
+        id,
+        user_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(token)
+}
+
+/// Look up the user for a token hash, and remember that the token was used.
+/// The settings only show the day a token was last used, so it's updated at
+/// most once an hour instead of writing to the database on every request.
+pub async fn authenticate(tx: &mut AppTx, token_hash: &str) -> ResponseResult<Option<TokenUser>> {
+    let user = query_as!(
+        TokenUser,
+        r#"
+        with token as (
+            select api_tokens.id, api_tokens.user_id, users.ap_user_id, api_tokens.scope
+            from api_tokens
+            inner join users on users.id = api_tokens.user_id
+            where api_tokens.token_hash = $1
+        ),
+        used as (
+            update api_tokens
+            set last_used_at = current_timestamp
+            from token
+            where api_tokens.id = token.id
+                and (
+                    api_tokens.last_used_at is null
+                    or api_tokens.last_used_at < current_timestamp - interval '1 hour'
+                )
+        )
+        select token.user_id as "user_id!", token.ap_user_id as "ap_user_id!",
+            token.scope as "scope!: _"
+        from token
+        "#,
+        token_hash,
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(user)
+}
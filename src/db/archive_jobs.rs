@@ -0,0 +1,155 @@
+//! Jobs of the archive queue, see [`crate::archive::QueueHandle`]. Each
+//! pending archive has exactly one job, which is deleted once the archive is
+//! done.
+
+use std::time::Duration;
+
+use anyhow::Context;
+use sqlx::types::Json;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{archive, db::AppTx, response_error::ResponseResult};
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct Job {
+    pub archive_id: Uuid,
+
+    pub created_at: OffsetDateTime,
+    /// Including the currently running attempt
+    pub attempts: i32,
+    pub next_attempt_at: OffsetDateTime,
+    pub last_error: Option<Json<archive::Error>>,
+}
+
+pub async fn insert(tx: &mut AppTx, archive_id: Uuid) -> ResponseResult<()> {
+    sqlx::query!(
+        r#"
+        insert into archive_jobs
+        (archive_id)
+        values ($1)
+        on conflict (archive_id) do nothing
+        "#,
+        archive_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Claim the job that has been waiting the longest. Other workers won't see
+/// the claimed job until `lease` has passed, so that it's retried if this
+/// worker crashes.
+pub async fn claim_due(tx: &mut AppTx, lease: Duration) -> ResponseResult<Option<Job>> {
+    let job = sqlx::query_as!(
+        Job,
+        r#"
+        update archive_jobs
+        set attempts = attempts + 1,
+            next_attempt_at = now() + make_interval(secs => $1)
+        where archive_id = (
+            select archive_id
+            from archive_jobs
+            where next_attempt_at <= now()
+            order by next_attempt_at, created_at
+            limit 1
+            for update skip locked
+        )
+        returning archive_id, created_at, attempts, next_attempt_at, last_error as "last_error: Json<archive::Error>"
+        "#,
+        lease.as_secs_f64(),
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(job)
+}
+
+/// Schedule another attempt of a job after a failure.
+pub async fn retry_later(
+    tx: &mut AppTx,
+    archive_id: Uuid,
+    error: &archive::Error,
+    delay: Duration,
+) -> ResponseResult<()> {
+    let error = serde_json::to_value(error).context("Failed to serialize error")?;
+    sqlx::query!(
+        r#"
+        update archive_jobs
+        set next_attempt_at = now() + make_interval(secs => $2),
+            last_error = $3
+        where archive_id = $1
+        "#,
+        archive_id,
+        delay.as_secs_f64(),
+        error,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Spread out the jobs of many archives, so that other archives can still be
+/// processed in between. The first archive is processed right away.
+pub async fn stagger(
+    tx: &mut AppTx,
+    archive_ids: &[Uuid],
+    interval: Duration,
+) -> ResponseResult<()> {
+    sqlx::query!(
+        r#"
+        update archive_jobs
+        set next_attempt_at = now() + (batch.position - 1) * make_interval(secs => $2)
+        from unnest($1::uuid[]) with ordinality as batch(archive_id, position)
+        where archive_jobs.archive_id = batch.archive_id
+            and archive_jobs.attempts = 0
+        "#,
+        archive_ids,
+        interval.as_secs_f64(),
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// When the next job is due, or `None` if there are no jobs.
+pub async fn next_attempt_at(tx: &mut AppTx) -> ResponseResult<Option<OffsetDateTime>> {
+    let next = sqlx::query!(
+        r#"
+        select min(next_attempt_at) as next_attempt_at
+        from archive_jobs
+        "#,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .next_attempt_at;
+
+    Ok(next)
+}
+
+pub async fn by_archive_id(tx: &mut AppTx, archive_id: Uuid) -> ResponseResult<Option<Job>> {
+    let job = sqlx::query_as!(
+        Job,
+        r#"
+        select archive_id, created_at, attempts, next_attempt_at, last_error as "last_error: Json<archive::Error>"
+        from archive_jobs
+        where archive_id = $1
+        "#,
+        archive_id,
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(job)
+}
+
+pub async fn delete(tx: &mut AppTx, archive_id: Uuid) -> ResponseResult<()> {
+    sqlx::query!("delete from archive_jobs where archive_id = $1", archive_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
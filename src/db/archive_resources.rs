@@ -0,0 +1,150 @@
+//! Images referenced by archived pages, see [`crate::archive::ResourceStore`].
+
+use uuid::Uuid;
+
+use crate::{db::AppTx, response_error::ResponseResult};
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct Resource {
+    pub hash: String,
+    pub content_type: String,
+    /// `None` if the file is stored in the resources directory
+    pub data: Option<Vec<u8>>,
+}
+
+/// Store a resource and record that the archive uses it. Resources that
+/// already exist are not stored again.
+pub async fn insert(
+    tx: &mut AppTx,
+    archive_id: Uuid,
+    url: &str,
+    hash: &str,
+    content_type: &str,
+    size: i64,
+    data: Option<&[u8]>,
+) -> ResponseResult<()> {
+    sqlx::query!(
+        r#"
+        insert into archive_resources
+        (hash, content_type, size, data)
+        values ($1, $2, $3, $4)
+        on conflict (hash) do nothing
+        "#,
+        hash,
+        content_type,
+        size,
+        data,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        insert into archive_resource_uses
+        (archive_id, resource_hash, url)
+        values ($1, $2, $3)
+        on conflict (archive_id, resource_hash) do nothing
+        "#,
+        archive_id,
+        hash,
+        url,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn by_hash(tx: &mut AppTx, hash: &str) -> ResponseResult<Resource> {
+    let resource = sqlx::query_as!(
+        Resource,
+        r#"
+        select hash, content_type, data
+        from archive_resources
+        where hash = $1
+        "#,
+        hash,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(resource)
+}
+
+/// Whether the archive of a public bookmark, or of a bookmark of the user,
+/// uses the resource.
+pub async fn is_readable(
+    tx: &mut AppTx,
+    hash: &str,
+    ap_user_id: Option<Uuid>,
+) -> ResponseResult<bool> {
+    let readable = sqlx::query!(
+        r#"
+        select exists (
+            select null
+            from archive_resource_uses
+            inner join archives on archives.id = archive_resource_uses.archive_id
+            inner join bookmarks on bookmarks.id = archives.bookmark_id
+            where archive_resource_uses.resource_hash = $1
+                and (bookmarks.ap_user_id = $2 or bookmark_is_public(bookmarks.id))
+        ) as "readable!"
+        "#,
+        hash,
+        ap_user_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .readable;
+
+    Ok(readable)
+}
+
+/// A resource used by an archive, without its contents.
+#[derive(sqlx::FromRow, Debug)]
+pub struct Use {
+    pub hash: String,
+    pub content_type: String,
+    /// Where the resource was downloaded from, if it was recorded
+    pub url: Option<String>,
+}
+
+/// List the resources an archive uses.
+pub async fn list_by_archive_id(tx: &mut AppTx, archive_id: Uuid) -> ResponseResult<Vec<Use>> {
+    let uses = sqlx::query_as!(
+        Use,
+        r#"
+        select archive_resources.hash, archive_resources.content_type, archive_resource_uses.url
+        from archive_resource_uses
+        inner join archive_resources on archive_resources.hash = archive_resource_uses.resource_hash
+        where archive_resource_uses.archive_id = $1
+        order by archive_resources.hash
+        "#,
+        archive_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(uses)
+}
+
+/// Delete resources that no archive uses anymore, and return their hashes.
+pub async fn delete_unused(tx: &mut AppTx) -> ResponseResult<Vec<String>> {
+    let hashes = sqlx::query!(
+        r#"
+        delete from archive_resources
+        where not exists (
+            select null
+            from archive_resource_uses
+            where archive_resource_uses.resource_hash = archive_resources.hash
+        )
+        returning hash
+        "#,
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(|row| row.hash)
+    .collect();
+
+    Ok(hashes)
+}
@@ -0,0 +1,59 @@
+//! Responses of websites as they were fetched for an archive, only stored if
+//! the server is configured to keep them.
+
+use anyhow::Context;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::{archive, db::AppTx, response_error::ResponseResult};
+
+pub async fn insert(
+    tx: &mut AppTx,
+    archive_id: Uuid,
+    response: &archive::RawResponse,
+) -> ResponseResult<()> {
+    let headers = serde_json::to_value(&response.headers).context("Failed to serialize headers")?;
+    sqlx::query!(
+        r#"
+        insert into archive_responses
+        (archive_id, url, status, headers, body)
+        values ($1, $2, $3, $4, $5)
+        "#,
+        archive_id,
+        response.url,
+        i32::from(response.status),
+        headers,
+        response.body,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn by_archive_id(
+    tx: &mut AppTx,
+    archive_id: Uuid,
+) -> ResponseResult<Option<archive::RawResponse>> {
+    let row = sqlx::query!(
+        r#"
+        select url, status, headers as "headers: Json<Vec<(String, String)>>", body
+        from archive_responses
+        where archive_id = $1
+        "#,
+        archive_id,
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    Ok(Some(archive::RawResponse {
+        url: row.url,
+        status: u16::try_from(row.status).context("Invalid status code")?,
+        headers: row.headers.0,
+        body: row.body,
+    }))
+}
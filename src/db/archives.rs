@@ -1,12 +1,18 @@
 use anyhow::Context;
+use serde::{Deserialize, Serialize};
 use sqlx::types::Json;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::{archive, db::AppTx, response_error::ResponseResult};
+use crate::{
+    archive,
+    db::{self, AppTx},
+    response_error::ResponseResult,
+};
 
-#[derive(sqlx::Type, Debug, PartialEq, Eq)]
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[sqlx(type_name = "archive_status")]
+#[serde(rename_all = "lowercase")]
 pub enum Status {
     Success,
     Error,
@@ -25,6 +31,7 @@ pub struct Archive {
     pub extracted_html: Option<String>,
 }
 
+/// Insert an archive along with a job for the archive queue to process it.
 pub async fn insert_pending(tx: &mut AppTx, bookmark_id: Uuid) -> ResponseResult<Archive> {
     let id = Uuid::new_v4();
     let status = Status::Pending;
@@ -43,6 +50,40 @@ pub async fn insert_pending(tx: &mut AppTx, bookmark_id: Uuid) -> ResponseResult
     )
     .fetch_one(&mut **tx)
     .await?;
+    db::archive_jobs::insert(tx, archive.id).await?;
+
+    Ok(archive)
+}
+
+/// Insert an archive that was created elsewhere, e.g. when restoring a backup.
+pub async fn insert_restored(
+    tx: &mut AppTx,
+    bookmark_id: Uuid,
+    created_at: OffsetDateTime,
+    status: Status,
+    error: Option<&serde_json::Value>,
+    extracted_html: Option<&str>,
+) -> ResponseResult<Archive> {
+    let archive = sqlx::query_as!(
+        Archive,
+        r#"
+        insert into archives
+        (id, bookmark_id, created_at, status, error, extracted_html)
+        values ($1, $2, $3, $4, $5, $6)
+        returning id, bookmark_id, created_at, status as "status: _", error as "error: Json<archive::Error>", extracted_html
+        "#,
+        Uuid::new_v4(),
+        bookmark_id,
+        created_at,
+        status as Status,
+        error,
+        extracted_html,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+    if archive.status == Status::Pending {
+        db::archive_jobs::insert(tx, archive.id).await?;
+    }
 
     Ok(archive)
 }
@@ -81,6 +122,15 @@ pub async fn update(
     Ok(archive)
 }
 
+/// A snapshot in the archive history of a bookmark, without its contents.
+#[derive(sqlx::FromRow, Debug)]
+pub struct Snapshot {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub status: Status,
+}
+
+/// The newest snapshot of a bookmark.
 pub async fn by_bookmark_id(tx: &mut AppTx, bookmark_id: Uuid) -> ResponseResult<Option<Archive>> {
     let archive = sqlx::query_as!(
         Archive,
@@ -88,6 +138,8 @@ pub async fn by_bookmark_id(tx: &mut AppTx, bookmark_id: Uuid) -> ResponseResult
         select id, bookmark_id, created_at, status as "status: _", error as "error: Json<archive::Error>", extracted_html
         from archives
         where bookmark_id = $1
+        order by created_at desc, id desc
+        limit 1
         "#,
         bookmark_id,
     )
@@ -97,42 +149,90 @@ pub async fn by_bookmark_id(tx: &mut AppTx, bookmark_id: Uuid) -> ResponseResult
     Ok(archive)
 }
 
-pub async fn by_id(tx: &mut AppTx, archive_id: Uuid) -> ResponseResult<Archive> {
-    let archive = sqlx::query_as!(
+/// List all snapshots of a bookmark, newest first.
+pub async fn list_snapshots(tx: &mut AppTx, bookmark_id: Uuid) -> ResponseResult<Vec<Snapshot>> {
+    let snapshots = sqlx::query_as!(
+        Snapshot,
+        r#"
+        select id, created_at, status as "status: _"
+        from archives
+        where bookmark_id = $1
+        order by created_at desc, id desc
+        "#,
+        bookmark_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(snapshots)
+}
+
+/// List the archives of all bookmarks of a user.
+pub async fn list_by_ap_user_id(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Vec<Archive>> {
+    let archives = sqlx::query_as!(
         Archive,
         r#"
-        select id, bookmark_id, created_at, status as "status: _", error as "error: Json<archive::Error>", extracted_html
+        select archives.id, archives.bookmark_id, archives.created_at, archives.status as "status: _", archives.error as "error: Json<archive::Error>", archives.extracted_html
         from archives
-        where id = $1
+        inner join bookmarks on bookmarks.id = archives.bookmark_id
+        where bookmarks.ap_user_id = $1
+        order by archives.created_at, archives.id
         "#,
-        archive_id,
+        ap_user_id,
     )
-    .fetch_one(&mut **tx)
+    .fetch_all(&mut **tx)
     .await?;
 
-    Ok(archive)
+    Ok(archives)
 }
 
-pub async fn find_one_pending(tx: &mut AppTx) -> ResponseResult<Option<Uuid>> {
-    let archive = sqlx::query!(
+/// The newest successful snapshot of each bookmark linked from a list, most
+/// recently archived first.
+pub async fn list_latest_successful_by_list_id(
+    tx: &mut AppTx,
+    list_id: Uuid,
+) -> ResponseResult<Vec<Archive>> {
+    let archives = sqlx::query_as!(
+        Archive,
         r#"
-        select id
+        select id, bookmark_id, created_at, status as "status: _", error as "error: Json<archive::Error>", extracted_html
         from archives
-        where status = $1
+        where status = $2
+            and bookmark_id in (
+                select dest_bookmark_id
+                from links
+                where src_list_id = $1
+            )
+            and not exists (
+                select null
+                from archives as newer
+                where newer.bookmark_id = archives.bookmark_id
+                    and newer.status = $2
+                    and (newer.created_at, newer.id) > (archives.created_at, archives.id)
+            )
+        order by created_at desc, id desc
         "#,
-        Status::Pending as Status,
+        list_id,
+        Status::Success as Status,
     )
-    .fetch_optional(&mut **tx)
-    .await?
-    .map(|r| r.id);
+    .fetch_all(&mut **tx)
+    .await?;
 
-    Ok(archive)
+    Ok(archives)
 }
 
-pub async fn delete_by_bookmark_id(tx: &mut AppTx, bookmark_id: Uuid) -> ResponseResult<()> {
-    sqlx::query!("delete from archives where bookmark_id = $1", bookmark_id)
-        .execute(&mut **tx)
-        .await?;
+pub async fn by_id(tx: &mut AppTx, archive_id: Uuid) -> ResponseResult<Archive> {
+    let archive = sqlx::query_as!(
+        Archive,
+        r#"
+        select id, bookmark_id, created_at, status as "status: _", error as "error: Json<archive::Error>", extracted_html
+        from archives
+        where id = $1
+        "#,
+        archive_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
 
-    Ok(())
+    Ok(archive)
 }
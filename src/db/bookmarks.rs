@@ -21,6 +21,10 @@ pub struct Bookmark {
     pub url: String,
     pub title: String,
     pub ap_id: ObjectId<Bookmark>,
+    /// Markdown notes on why this bookmark was saved
+    pub notes: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
 }
 
 #[derive(FromRow, Debug)]
@@ -32,6 +36,8 @@ struct BookmarkRow {
     url: String,
     title: String,
     ap_id: String,
+    notes: Option<String>,
+    updated_at: OffsetDateTime,
 }
 
 impl TryFrom<BookmarkRow> for Bookmark {
@@ -45,6 +51,8 @@ impl TryFrom<BookmarkRow> for Bookmark {
             url: value.url,
             title: value.title,
             ap_id: value.ap_id.parse()?,
+            notes: value.notes,
+            updated_at: value.updated_at,
         })
     }
 }
@@ -59,6 +67,7 @@ impl Bookmark {
 pub struct InsertBookmark {
     pub url: String,
     pub title: String,
+    pub notes: Option<String>,
 }
 
 pub async fn insert_local(
@@ -66,6 +75,19 @@ pub async fn insert_local(
     ap_user_id: Uuid,
     create_bookmark: InsertBookmark,
     base_url: &Url,
+) -> ResponseResult<Bookmark> {
+    insert_local_at(tx, ap_user_id, create_bookmark, None, base_url).await
+}
+
+/// Insert a local bookmark that was created at the given time, e.g. in
+/// another service it was imported from. Uses the current time if
+/// `created_at` is `None`.
+pub async fn insert_local_at(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    create_bookmark: InsertBookmark,
+    created_at: Option<OffsetDateTime>,
+    base_url: &Url,
 ) -> ResponseResult<Bookmark> {
     let id = Uuid::new_v4();
     let ap_id = base_url.join("/ap/bookmark/")?.join(&id.to_string())?;
@@ -73,14 +95,16 @@ pub async fn insert_local(
         BookmarkRow,
         r#"
         insert into bookmarks
-        (id, ap_user_id, url, title, ap_id)
-        values ($1, $2, $3, $4, $5)
+        (id, ap_user_id, url, title, ap_id, notes, created_at)
+        values ($1, $2, $3, $4, $5, $6, coalesce($7, current_timestamp))
         returning *"#,
         id,
         ap_user_id,
         create_bookmark.url,
         create_bookmark.title,
-        ap_id.to_string()
+        ap_id.to_string(),
+        create_bookmark.notes,
+        created_at,
     )
     .fetch_one(&mut **tx)
     .await?;
@@ -120,6 +144,55 @@ pub async fn by_ap_id(tx: &mut AppTx, ap_id: ObjectId<db::Bookmark>) -> Response
     Bookmark::try_from(row)
 }
 
+/// Find one of the user's bookmarks with the given URL.
+pub async fn by_url(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    url: &str,
+) -> ResponseResult<Option<Bookmark>> {
+    let row = query_as!(
+        BookmarkRow,
+        r#"
+        select *
+        from bookmarks
+        where ap_user_id = $1
+            and url = $2
+        order by created_at
+        limit 1
+        "#,
+        ap_user_id,
+        url,
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    row.map(Bookmark::try_from).transpose()
+}
+
+pub async fn update(
+    tx: &mut AppTx,
+    id: Uuid,
+    update_bookmark: InsertBookmark,
+) -> ResponseResult<Bookmark> {
+    let bookmark = query_as!(
+        BookmarkRow,
+        r#"
+        update bookmarks
+        set url = $2, title = $3, notes = $4, updated_at = current_timestamp
+        where id = $1
+        returning *
+        "#,
+        id,
+        update_bookmark.url,
+        update_bookmark.title,
+        update_bookmark.notes,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    bookmark.try_into()
+}
+
 pub async fn list_unsorted(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Vec<Bookmark>> {
     let bookmarks = query_as!(
         BookmarkRow,
@@ -143,6 +216,225 @@ pub async fn list_unsorted(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<V
         .collect::<ResponseResult<Vec<_>>>()
 }
 
+/// List all bookmarks of a user, oldest first.
+pub async fn list_by_ap_user_id(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Vec<Bookmark>> {
+    let bookmarks = query_as!(
+        BookmarkRow,
+        r#"
+        select *
+        from bookmarks
+        where ap_user_id = $1
+        order by created_at, id
+        "#,
+        ap_user_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    bookmarks
+        .into_iter()
+        .map(Bookmark::try_from)
+        .collect::<ResponseResult<Vec<_>>>()
+}
+
+/// List the newest bookmarks of a user that are in at least one public list.
+/// With `before`, only bookmarks that come after that one in this order are
+/// listed, for paging through all of them.
+pub async fn list_public_by_ap_user_id(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    before: Option<Uuid>,
+    limit: i64,
+) -> ResponseResult<Vec<Bookmark>> {
+    let bookmarks = query_as!(
+        BookmarkRow,
+        r#"
+        select *
+        from bookmarks
+        where ap_user_id = $1
+        and bookmark_is_public(bookmarks.id)
+        and (
+            $2::uuid is null
+            or (created_at, id) < (select created_at, id from bookmarks where id = $2)
+        )
+        order by created_at desc, id desc
+        limit $3
+        "#,
+        ap_user_id,
+        before,
+        limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    bookmarks
+        .into_iter()
+        .map(Bookmark::try_from)
+        .collect::<ResponseResult<Vec<_>>>()
+}
+
+/// The bookmarks linked from a list, most recently linked first.
+pub async fn list_by_list_id(
+    tx: &mut AppTx,
+    list_id: Uuid,
+    limit: i64,
+) -> ResponseResult<Vec<Bookmark>> {
+    let bookmarks = query_as!(
+        BookmarkRow,
+        r#"
+        select bookmarks.*
+        from bookmarks
+        inner join links on links.dest_bookmark_id = bookmarks.id
+        where links.src_list_id = $1
+        order by links.created_at desc, bookmarks.id desc
+        limit $2
+        "#,
+        list_id,
+        limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    bookmarks
+        .into_iter()
+        .map(Bookmark::try_from)
+        .collect::<ResponseResult<Vec<_>>>()
+}
+
+/// The bookmarks of local users linked from a list, if the list is public.
+/// Remote instances may have copies of these.
+pub async fn list_local_by_public_list_id(
+    tx: &mut AppTx,
+    list_id: Uuid,
+) -> ResponseResult<Vec<Bookmark>> {
+    let bookmarks = query_as!(
+        BookmarkRow,
+        r#"
+        select bookmarks.*
+        from bookmarks
+        inner join links on links.dest_bookmark_id = bookmarks.id
+        inner join lists on lists.id = links.src_list_id
+        where links.src_list_id = $1
+            and not lists.private
+            and exists (
+                select null from users
+                where users.ap_user_id = bookmarks.ap_user_id
+            )
+        "#,
+        list_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    bookmarks
+        .into_iter()
+        .map(Bookmark::try_from)
+        .collect::<ResponseResult<Vec<_>>>()
+}
+
+pub async fn count_by_list_id(tx: &mut AppTx, list_id: Uuid) -> ResponseResult<i64> {
+    let count = query!(
+        r#"
+        select count(*) as "count!"
+        from links
+        where src_list_id = $1
+            and dest_bookmark_id is not null
+        "#,
+        list_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .count;
+
+    Ok(count)
+}
+
+/// The bookmarks of everyone a user follows and hasn't muted, newest first.
+/// Use `before` to get the bookmarks that come after the given bookmark.
+pub async fn list_timeline(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    before: Option<Uuid>,
+    limit: i64,
+) -> ResponseResult<Vec<Bookmark>> {
+    let bookmarks = query_as!(
+        BookmarkRow,
+        r#"
+        select bookmarks.*
+        from bookmarks
+        inner join follows on follows.following_id = bookmarks.ap_user_id
+        where follows.follower_id = $1
+        and follows.accepted
+        and not exists (
+            select null from mutes
+            where mutes.muter_id = $1
+                and mutes.muted_id = bookmarks.ap_user_id
+        )
+        -- We only store public bookmarks of remote users, but local users
+        -- might have private ones
+        and (
+            not exists (
+                select null from users
+                where users.ap_user_id = bookmarks.ap_user_id
+            )
+            or bookmark_is_public(bookmarks.id)
+        )
+        and (
+            $2::uuid is null
+            or (bookmarks.created_at, bookmarks.id)
+                < (select created_at, id from bookmarks where id = $2)
+        )
+        order by bookmarks.created_at desc, bookmarks.id desc
+        limit $3
+        "#,
+        ap_user_id,
+        before,
+        limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    bookmarks
+        .into_iter()
+        .map(Bookmark::try_from)
+        .collect::<ResponseResult<Vec<_>>>()
+}
+
+pub async fn count_public_by_ap_user_id(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<i64> {
+    let count = query!(
+        r#"
+        select count(*) as "count!"
+        from bookmarks
+        where ap_user_id = $1
+        and bookmark_is_public(bookmarks.id)
+        "#,
+        ap_user_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .count;
+
+    Ok(count)
+}
+
+/// Count the public bookmarks of all local users.
+pub async fn count_local_public(tx: &mut AppTx) -> ResponseResult<i64> {
+    let count = query!(
+        r#"
+        select count(*) as "count!"
+        from bookmarks
+        inner join users on users.ap_user_id = bookmarks.ap_user_id
+        where bookmark_is_public(bookmarks.id)
+        "#,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .count;
+
+    Ok(count)
+}
+
+/// Delete a bookmark along with its archives and the links pointing to it.
 pub async fn delete_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Bookmark> {
     query!(
         r#"
@@ -154,6 +446,16 @@ pub async fn delete_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Bookmark>
     .execute(&mut **tx)
     .await?;
 
+    query!(
+        r#"
+        delete from links
+        where dest_bookmark_id = $1;
+        "#,
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+
     let bookmark = query_as!(
         BookmarkRow,
         r#"
@@ -169,6 +471,9 @@ pub async fn delete_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Bookmark>
     bookmark.try_into()
 }
 
+/// Remote notes are cut off after as many characters as the forms allow.
+const MAX_NOTES_LENGTH: usize = 10000;
+
 /// Create a new UUID as primary key.
 /// Do not use this for local bookmarks as their AP ID needs to correlate with
 /// the primary key's UUID.
@@ -179,16 +484,21 @@ pub async fn upsert_remote(
     insert: InsertBookmark,
 ) -> ResponseResult<Bookmark> {
     let id = Uuid::new_v4();
+    let notes = insert
+        .notes
+        .map(|notes| notes.chars().take(MAX_NOTES_LENGTH).collect::<String>());
     let user = query_as!(
         BookmarkRow,
         r#"
         insert into bookmarks
-        (ap_id, id, ap_user_id, url, title)
-        values ($1, $2, $3, $4, $5)
+        (ap_id, id, ap_user_id, url, title, notes)
+        values ($1, $2, $3, $4, $5, $6)
         on conflict(ap_id) do update set
-            ap_user_id = $2,
-            url = $3,
-            title = $4
+            ap_user_id = $3,
+            url = $4,
+            title = $5,
+            notes = $6,
+            updated_at = current_timestamp
         returning *
         "#,
         ap_id.inner().as_str(),
@@ -196,6 +506,7 @@ pub async fn upsert_remote(
         ap_user_id,
         insert.url,
         insert.title,
+        notes,
     )
     .fetch_one(&mut **tx)
     .await?
@@ -206,18 +517,15 @@ pub async fn upsert_remote(
 
 /// Return true if at least one public list points to the given bookmark.
 pub async fn is_public(tx: &mut AppTx, bookmark_id: Uuid) -> ResponseResult<bool> {
-    let public_destination_count = query!(
+    let is_public = query!(
         r#"
-        select count(lists.id) as "count!"
-        from links
-        inner join lists on links.src_list_id = lists.id
-        where not lists.private
-            and links.dest_bookmark_id = $1
+        select bookmark_is_public($1) as "is_public!"
         "#,
         bookmark_id
     )
     .fetch_one(&mut **tx)
-    .await?;
+    .await?
+    .is_public;
 
-    Ok(public_destination_count.count > 0)
+    Ok(is_public)
 }
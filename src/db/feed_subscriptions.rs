@@ -0,0 +1,161 @@
+use sqlx::{FromRow, query, query_as};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::{forms::feed_subscriptions::CreateFeedSubscription, response_error::ResponseResult};
+
+/// An external RSS or Atom feed whose new entries are added to a list.
+#[derive(FromRow, Debug, Clone)]
+pub struct FeedSubscription {
+    pub id: Uuid,
+    pub list_id: Uuid,
+    pub url: String,
+    pub archive: bool,
+    pub created_at: OffsetDateTime,
+    pub last_polled_at: Option<OffsetDateTime>,
+    pub last_error: Option<String>,
+}
+
+pub async fn insert(
+    tx: &mut AppTx,
+    list_id: Uuid,
+    create_subscription: CreateFeedSubscription,
+) -> ResponseResult<FeedSubscription> {
+    let subscription = query_as!(
+        FeedSubscription,
+        r#"
+        insert into feed_subscriptions
+        (list_id, url, archive)
+        values ($1, $2, $3)
+        returning *
+        "#,
+        list_id,
+        create_subscription.url,
+        create_subscription.archive,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(subscription)
+}
+
+pub async fn list_by_list_id(
+    tx: &mut AppTx,
+    list_id: Uuid,
+) -> ResponseResult<Vec<FeedSubscription>> {
+    let subscriptions = query_as!(
+        FeedSubscription,
+        r#"
+        select *
+        from feed_subscriptions
+        where list_id = $1
+        order by created_at
+        "#,
+        list_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(subscriptions)
+}
+
+/// All subscriptions, starting with the ones that haven't been polled for the
+/// longest time.
+pub async fn list_all(tx: &mut AppTx) -> ResponseResult<Vec<FeedSubscription>> {
+    let subscriptions = query_as!(
+        FeedSubscription,
+        r#"
+        select *
+        from feed_subscriptions
+        order by last_polled_at nulls first
+        "#,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(subscriptions)
+}
+
+pub async fn exists(tx: &mut AppTx, list_id: Uuid, url: &str) -> ResponseResult<bool> {
+    let exists = query!(
+        r#"
+        select exists (
+            select null from feed_subscriptions
+            where list_id = $1 and url = $2
+        ) as "exists!"
+        "#,
+        list_id,
+        url,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .exists;
+
+    Ok(exists)
+}
+
+pub async fn delete_by_id(
+    tx: &mut AppTx,
+    id: Uuid,
+    list_id: Uuid,
+) -> ResponseResult<FeedSubscription> {
+    let subscription = query_as!(
+        FeedSubscription,
+        r#"
+        delete from feed_subscriptions
+        where id = $1 and list_id = $2
+        returning *
+        "#,
+        id,
+        list_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(subscription)
+}
+
+/// Remember when a subscription was polled, and why that failed if it did.
+pub async fn set_polled(tx: &mut AppTx, id: Uuid, error: Option<&str>) -> ResponseResult<()> {
+    query!(
+        r#"
+        update feed_subscriptions
+        set last_polled_at = current_timestamp, last_error = $2
+        where id = $1
+        "#,
+        id,
+        error,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Mark the given entries as seen, and return the keys of those that hadn't
+/// been seen before.
+pub async fn insert_entry_keys(
+    tx: &mut AppTx,
+    subscription_id: Uuid,
+    entry_keys: &[String],
+) -> ResponseResult<Vec<String>> {
+    let new_keys = query!(
+        r#"
+        insert into feed_subscription_entries
+        (subscription_id, entry_key)
+        select $1, unnest($2::text[])
+        on conflict do nothing
+        returning entry_key
+        "#,
+        subscription_id,
+        entry_keys,
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(|row| row.entry_key)
+    .collect();
+
+    Ok(new_keys)
+}
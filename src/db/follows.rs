@@ -1,4 +1,4 @@
-use sqlx::{prelude::FromRow, query};
+use sqlx::{prelude::FromRow, query, query_as};
 use uuid::Uuid;
 
 use crate::{db::AppTx, response_error::ResponseResult};
@@ -12,6 +12,8 @@ pub struct Follow {
     pub follower_id: Uuid,
     /// The user being followed
     pub following_id: Uuid,
+    /// False while we wait for a remote user to accept a follow request
+    pub accepted: bool,
 }
 
 pub struct Insert {
@@ -42,6 +44,86 @@ pub async fn upsert(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
     Ok(())
 }
 
+/// Remember a follow request sent to a remote user, until they accept it.
+pub async fn insert_pending(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
+    query!(
+        r"
+        insert into follows
+        (
+            follower_id,
+            following_id,
+            accepted
+        )
+        values ($1, $2, false)
+        on conflict (follower_id, following_id)
+            do nothing
+        ",
+        insert.follower_id,
+        insert.following_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn accept(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
+    query!(
+        r"
+        update follows
+        set accepted = true
+        where follower_id = $1 and following_id = $2
+        ",
+        insert.follower_id,
+        insert.following_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn by_ids(
+    tx: &mut AppTx,
+    follower_id: Uuid,
+    following_id: Uuid,
+) -> ResponseResult<Option<Follow>> {
+    let follow = query_as!(
+        Follow,
+        r"
+        select id, follower_id, following_id, accepted
+        from follows
+        where follower_id = $1 and following_id = $2
+        ",
+        follower_id,
+        following_id,
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(follow)
+}
+
+/// Check if at least one local user follows the given user.
+pub async fn has_local_followers(tx: &mut AppTx, following_id: Uuid) -> ResponseResult<bool> {
+    let exists = query!(
+        r#"
+        select exists (
+            select null from follows
+            inner join users on users.ap_user_id = follows.follower_id
+            where follows.following_id = $1
+                and follows.accepted
+        ) as "exists!"
+        "#,
+        following_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .exists;
+
+    Ok(exists)
+}
+
 pub async fn remove(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
     query!(
         r"
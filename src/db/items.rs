@@ -6,40 +6,181 @@ use sqlx::query;
 use uuid::Uuid;
 
 use super::{AppTx, LinkDestination};
-use crate::response_error::ResponseResult;
+use crate::{db, response_error::ResponseResult};
 
-// We'll use this for global search later
-#[expect(dead_code)]
+/// A page of search results, see [`db::search::search`] for the pagination
+/// around it.
+pub struct SearchPage {
+    pub results: Vec<db::search::Result>,
+    /// How many items match on all pages
+    pub total_count: i64,
+    /// The items before `after_id` in reverse order, at most `limit` of them
+    pub previous_ids: Vec<Uuid>,
+}
+
+/// Find the user's bookmarks and lists matching a search query, ordered by
+/// rank.
 pub async fn search(
     tx: &mut AppTx,
-    term: &str,
+    query: &db::search::Query,
     ap_user_id: Uuid,
-) -> ResponseResult<Vec<LinkDestination>> {
-    let jsons = query!(
+    include_bookmarks: bool,
+    include_lists: bool,
+    after_id: Option<Uuid>,
+    limit: i64,
+) -> ResponseResult<SearchPage> {
+    // Results are ordered by rank, then by id. To continue after an item,
+    // we look up its rank and compare both columns. The summary is always
+    // returned, even if the page is empty, so the items are nullable.
+    let rows = query!(
         r#"
-            select to_jsonb(bookmarks.*) as item
-            from bookmarks
-            where bookmarks.title ilike '%' || $1 || '%'
-            and bookmarks.ap_user_id = $2
-            union
-            select to_jsonb(lists.*) as item
-            from lists
-            where lists.title ilike '%' || $1 || '%'
-            and lists.ap_user_id = $2
-            limit 10
+            with matches as (
+                select *
+                from search_items($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ),
+            after as (
+                select -matches.rank as rank, matches.id
+                from matches
+                where matches.id = $13
+            ),
+            summary as (
+                select
+                    (select count(*) from matches) as total_count,
+                    array(
+                        select matches.id
+                        from matches, after
+                        where (-matches.rank, matches.id) <= (after.rank, after.id)
+                        order by matches.rank asc, matches.id desc
+                        limit $14
+                    ) as previous_ids
+            ),
+            page as (
+                select matches.*
+                from matches
+                where $13::uuid is null
+                    or (-matches.rank, matches.id) > (select after.rank, after.id from after)
+                order by matches.rank desc, matches.id asc
+                limit $14
+            ),
+            search as (
+                select websearch_to_tsquery('english', nullif($1, '')) as query
+            )
+            select
+                summary.total_count as "total_count!",
+                summary.previous_ids as "previous_ids!",
+                coalesce(to_jsonb(bookmarks.*), to_jsonb(lists.*)) as item,
+                case
+                    when archives_search_vector(archives.extracted_html) @@ search.query
+                    then ts_headline(
+                        'english',
+                        regexp_replace(left(archives.extracted_html, 250000), '<[^>]*>', ' ', 'g'),
+                        search.query,
+                        'StartSel=' || chr(2) || ', StopSel=' || chr(3)
+                            || ', MaxFragments=2, MinWords=10, MaxWords=20'
+                    )
+                    when to_tsvector('english', coalesce(lists.content, '')) @@ search.query
+                    then ts_headline(
+                        'english',
+                        lists.content,
+                        search.query,
+                        'StartSel=' || chr(2) || ', StopSel=' || chr(3)
+                            || ', MaxFragments=2, MinWords=10, MaxWords=20'
+                    )
+                end as snippet,
+                case
+                    when to_tsvector('english', coalesce(bookmarks.notes, '')) @@ search.query
+                    then ts_headline(
+                        'english',
+                        bookmarks.notes,
+                        search.query,
+                        'StartSel=' || chr(2) || ', StopSel=' || chr(3)
+                            || ', MaxFragments=2, MinWords=10, MaxWords=20'
+                    )
+                end as notes_snippet,
+                (
+                    select coalesce(jsonb_agg(to_jsonb(src.*) order by src.title), '[]')
+                    from links
+                    join lists as src on src.id = links.src_list_id
+                    where (links.dest_bookmark_id = page.id or links.dest_list_id = page.id)
+                        and src.ap_user_id = $3
+                ) as "lists!",
+                (
+                    select count(*)
+                    from links
+                    where links.src_list_id = lists.id
+                ) as "item_count!"
+            from summary
+            cross join search
+            left join page on true
+            left join bookmarks on bookmarks.id = page.id
+            left join latest_archives as archives on archives.bookmark_id = bookmarks.id
+            left join lists on lists.id = page.id
+            order by page.rank desc, page.id asc
         "#,
-        term,
-        ap_user_id
+        query.terms,
+        &query.words,
+        ap_user_id,
+        include_bookmarks,
+        include_lists,
+        &query.excluded_terms,
+        &query.lists,
+        &query.domains,
+        query.before.map(|date| date.midnight().assume_utc()),
+        query.archived as Option<db::archives::Status>,
+        query.unsorted,
+        query.private,
+        after_id,
+        limit
     )
     .fetch_all(&mut **tx)
     .await?;
 
-    let results = jsons
-        .into_iter()
-        .map(|row| Ok(serde_json::from_value(row.item.into())?))
-        .collect::<anyhow::Result<Vec<LinkDestination>>>()?;
+    let (total_count, previous_ids) = rows
+        .first()
+        .map(|row| (row.total_count, row.previous_ids.clone()))
+        .unwrap_or_default();
 
-    Ok(results)
+    let mut results = Vec::new();
+    for row in rows {
+        // An empty page only consists of the summary
+        let Some(item) = row.item else {
+            continue;
+        };
+        let item: LinkDestination =
+            serde_json::from_value(item).context("Failed to deserialize search result from DB")?;
+        let snippet = match item {
+            // Prefer the user's own notes over the archived page
+            LinkDestination::Bookmark(_) => row
+                .notes_snippet
+                .or_else(|| row.snippet.as_deref().map(decode_entities)),
+            LinkDestination::List(_) => row.snippet,
+        };
+        results.push(db::search::Result {
+            item,
+            snippet,
+            lists: serde_json::from_value(row.lists)
+                .context("Failed to deserialize lists of search result from DB")?,
+            item_count: row.item_count,
+        });
+    }
+
+    Ok(SearchPage {
+        results,
+        total_count,
+        previous_ids,
+    })
+}
+
+/// Archived pages are stored as sanitized html, so after stripping the tags
+/// from a snippet we still need to unescape the entities ammonia produces.
+fn decode_entities(snippet: &str) -> String {
+    snippet
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", r#"""#)
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
 }
 
 pub async fn by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<LinkDestination> {
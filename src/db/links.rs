@@ -61,7 +61,6 @@ impl LinkDestination {
 
 pub struct LinkWithContent {
     pub id: Uuid,
-    #[expect(dead_code)]
     pub created_at: OffsetDateTime,
     #[expect(dead_code)]
     pub user_id: Uuid,
@@ -131,6 +130,17 @@ pub async fn insert(
     tx: &mut AppTx,
     user_id: Uuid,
     create_link: CreateLink,
+) -> ResponseResult<Link> {
+    insert_at(tx, user_id, create_link, None).await
+}
+
+/// Insert a link that was created at the given time, e.g. when restoring a
+/// backup. Uses the current time if `created_at` is `None`.
+pub async fn insert_at(
+    tx: &mut AppTx,
+    user_id: Uuid,
+    create_link: CreateLink,
+    created_at: Option<OffsetDateTime>,
 ) -> ResponseResult<Link> {
     validate_private_lists_belong_to_same_owner(tx, &create_link).await?;
 
@@ -142,17 +152,20 @@ pub async fn insert(
             user_id,
             src_list_id,
             dest_bookmark_id,
-            dest_list_id
+            dest_list_id,
+            created_at
         )
         values ($1,
             (select id from lists where id = $2),
             (select id from bookmarks where id = $3),
-            (select id from lists where id = $3)
+            (select id from lists where id = $3),
+            coalesce($4, current_timestamp)
         )
         returning *"#,
         user_id,
         create_link.src,
-        create_link.dest
+        create_link.dest,
+        created_at,
     )
     .fetch_one(&mut **tx)
     .await
@@ -226,6 +239,21 @@ pub async fn list_by_list(
     Ok(results)
 }
 
+pub async fn by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Link> {
+    let link = query_as!(
+        Link,
+        r#"
+        select * from links
+        where id = $1
+        "#,
+        id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(link)
+}
+
 pub async fn delete_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Link> {
     let link = query_as!(
         Link,
@@ -241,3 +269,42 @@ pub async fn delete_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Link> {
 
     Ok(link)
 }
+
+/// Check if a link from the list `src` to the bookmark or list `dest` exists.
+pub async fn exists(tx: &mut AppTx, src: Uuid, dest: Uuid) -> ResponseResult<bool> {
+    let exists = query!(
+        r#"
+        select exists (
+            select null from links
+            where src_list_id = $1
+                and (dest_bookmark_id = $2 or dest_list_id = $2)
+        ) as "exists!"
+        "#,
+        src,
+        dest,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .exists;
+
+    Ok(exists)
+}
+
+/// List all links starting at one of the user's lists, oldest first.
+pub async fn list_by_ap_user_id(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Vec<Link>> {
+    let links = query_as!(
+        Link,
+        r#"
+        select links.*
+        from links
+        inner join lists on lists.id = links.src_list_id
+        where lists.ap_user_id = $1
+        order by links.created_at, links.id
+        "#,
+        ap_user_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(links)
+}
@@ -0,0 +1,131 @@
+use activitypub_federation::fetch::object_id::ObjectId;
+use sqlx::query;
+use url::Url;
+use uuid::Uuid;
+
+use super::{AppTx, List};
+use crate::{federation, response_error::ResponseResult};
+
+/// A public list, federated as an ActivityPub group actor so that people on
+/// other servers can follow it.
+#[derive(Debug)]
+pub struct ListActor {
+    pub list: List,
+
+    /// Has the format `{base_url}/ap/list/{list_id}`
+    pub ap_id: ObjectId<ListActor>,
+    pub inbox_url: Url,
+    pub public_key: String,
+    pub private_key: redact::Secret<String>,
+}
+
+pub fn ap_id(base_url: &Url, list_id: Uuid) -> Result<Url, url::ParseError> {
+    base_url.join("/ap/list/")?.join(&list_id.to_string())
+}
+
+/// Load the actor for a list, generating its keypair if the list hasn't been
+/// federated before. Check that the list is public before calling this.
+pub async fn by_list(tx: &mut AppTx, list: List, base_url: &Url) -> ResponseResult<ListActor> {
+    let existing = query!(
+        r#"
+        select public_key, private_key
+        from list_actors
+        where list_id = $1
+        "#,
+        list.id,
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let (public_key, private_key) = match existing {
+        Some(keys) => (keys.public_key, keys.private_key),
+        None => {
+            let keypair = federation::signing::generate_keypair()?;
+            let keys = query!(
+                r#"
+                insert into list_actors
+                (list_id, public_key, private_key)
+                values ($1, $2, $3)
+                on conflict (list_id) do update
+                    set list_id = excluded.list_id
+                returning public_key, private_key
+                "#,
+                list.id,
+                keypair.public_key,
+                keypair.private_key,
+            )
+            .fetch_one(&mut **tx)
+            .await?;
+            (keys.public_key, keys.private_key)
+        }
+    };
+
+    let ap_id = ap_id(base_url, list.id)?;
+    let inbox_url = base_url
+        .join("/ap/list/")?
+        .join(&format!("{}/inbox", list.id))?;
+
+    Ok(ListActor {
+        list,
+        ap_id: ap_id.into(),
+        inbox_url,
+        public_key,
+        private_key: redact::Secret::new(private_key),
+    })
+}
+
+pub async fn insert_follower(
+    tx: &mut AppTx,
+    list_id: Uuid,
+    follower_id: Uuid,
+) -> ResponseResult<()> {
+    query!(
+        r#"
+        insert into list_followers
+        (list_id, follower_id)
+        values ($1, $2)
+        on conflict (list_id, follower_id)
+            do nothing
+        "#,
+        list_id,
+        follower_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn remove_follower(
+    tx: &mut AppTx,
+    list_id: Uuid,
+    follower_id: Uuid,
+) -> ResponseResult<()> {
+    query!(
+        r#"
+        delete from list_followers
+        where list_id = $1 and follower_id = $2
+        "#,
+        list_id,
+        follower_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Forget all followers of a list, e.g. when it's made private.
+pub async fn remove_all_followers(tx: &mut AppTx, list_id: Uuid) -> ResponseResult<()> {
+    query!(
+        r#"
+        delete from list_followers
+        where list_id = $1
+        "#,
+        list_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
@@ -10,7 +10,6 @@ use crate::{forms::lists::CreateList, response_error::ResponseResult};
 pub struct List {
     pub id: Uuid,
     #[serde(with = "time::serde::iso8601")]
-    #[expect(dead_code)]
     pub created_at: OffsetDateTime,
     pub ap_user_id: Uuid,
 
@@ -18,6 +17,8 @@ pub struct List {
     pub content: Option<String>,
     pub private: bool,
     pub pinned: bool,
+    /// Created for a new tag through the Pinboard API
+    pub created_as_tag: bool,
 }
 
 #[derive(FromRow, Debug, Deserialize, Clone)]
@@ -51,24 +52,55 @@ pub async fn insert(
     tx: &mut AppTx,
     ap_user_id: Uuid,
     create_list: CreateList,
+) -> ResponseResult<List> {
+    insert_at(tx, ap_user_id, create_list, None).await
+}
+
+/// Insert a list that was created at the given time, e.g. when restoring a
+/// backup. Uses the current time if `created_at` is `None`.
+pub async fn insert_at(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    create_list: CreateList,
+    created_at: Option<OffsetDateTime>,
 ) -> ResponseResult<List> {
     let list = query_as!(
         List,
         r#"
         insert into lists
-        (ap_user_id, title, content, private)
-        values ($1, $2, $3, $4)
+        (ap_user_id, title, content, private, created_at)
+        values ($1, $2, $3, $4, coalesce($5, current_timestamp))
         returning *"#,
         ap_user_id,
         create_list.title,
         create_list.content,
         create_list.private,
+        created_at,
     )
     .fetch_one(&mut **tx)
     .await?;
 
     Ok(list)
 }
+
+/// Insert a private list for a new tag from the Pinboard API.
+pub async fn insert_tag(tx: &mut AppTx, ap_user_id: Uuid, title: &str) -> ResponseResult<List> {
+    let list = query_as!(
+        List,
+        r#"
+        insert into lists
+        (ap_user_id, title, private, created_as_tag)
+        values ($1, $2, true, true)
+        returning *"#,
+        ap_user_id,
+        title,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(list)
+}
+
 pub async fn edit_title(tx: &mut AppTx, list_id: Uuid, new_title: String) -> ResponseResult<()> {
     query!(
         r#"
@@ -84,6 +116,34 @@ pub async fn edit_title(tx: &mut AppTx, list_id: Uuid, new_title: String) -> Res
     Ok(())
 }
 
+/// Delete a list along with all links from and to it. The bookmarks and lists
+/// it links to are kept.
+pub async fn delete_by_id(tx: &mut AppTx, list_id: Uuid) -> ResponseResult<List> {
+    query!(
+        r#"
+        delete from links
+        where src_list_id = $1 or dest_list_id = $1
+        "#,
+        list_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let list = query_as!(
+        List,
+        r#"
+        delete from lists
+        where id = $1
+        returning *
+        "#,
+        list_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(list)
+}
+
 pub async fn by_id(tx: &mut AppTx, list_id: Uuid) -> ResponseResult<List> {
     let list = query_as!(
         List,
@@ -99,6 +159,47 @@ pub async fn by_id(tx: &mut AppTx, list_id: Uuid) -> ResponseResult<List> {
     Ok(list)
 }
 
+/// Find one of the user's lists with exactly the given title.
+pub async fn by_title(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    title: &str,
+) -> ResponseResult<Option<List>> {
+    let list = query_as!(
+        List,
+        r#"
+        select * from lists
+        where ap_user_id = $1
+            and title = $2
+        order by created_at
+        limit 1
+        "#,
+        ap_user_id,
+        title,
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(list)
+}
+
+/// List all lists of a user, oldest first.
+pub async fn list_by_ap_user_id(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Vec<List>> {
+    let lists = query_as!(
+        List,
+        r#"
+        select * from lists
+        where ap_user_id = $1
+        order by created_at, id
+        "#,
+        ap_user_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(lists)
+}
+
 pub async fn pointing_to_list(
     tx: &mut AppTx,
     list_id: Uuid,
@@ -3,14 +3,23 @@ use sqlx::PgPool;
 
 pub mod all;
 pub mod ap_users;
+pub mod api_tokens;
+pub mod archive_jobs;
+pub mod archive_resources;
+pub mod archive_responses;
 pub mod archives;
 pub use archives::Archive;
+pub mod feed_subscriptions;
 pub mod follows;
 pub mod run_migrations;
 pub use ap_users::ApUser;
 pub mod items;
 pub mod layout;
 pub mod links;
+pub mod list_actors;
+pub use list_actors::ListActor;
+pub mod mutes;
+pub mod pinboard;
 pub use links::{LinkDestination, LinkDestinationWithChildren, LinkWithContent};
 pub mod lists;
 pub use lists::{List, ListWithLinks, ListWithMetadata};
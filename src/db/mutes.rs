@@ -0,0 +1,40 @@
+use sqlx::query;
+use uuid::Uuid;
+
+use crate::{db::AppTx, response_error::ResponseResult};
+
+pub async fn insert(tx: &mut AppTx, muter_id: Uuid, muted_id: Uuid) -> ResponseResult<()> {
+    query!(
+        r"
+        insert into mutes
+        (
+            muter_id,
+            muted_id
+        )
+        values ($1, $2)
+        on conflict (muter_id, muted_id)
+            do nothing
+        ",
+        muter_id,
+        muted_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn remove(tx: &mut AppTx, muter_id: Uuid, muted_id: Uuid) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from mutes
+        where muter_id = $1 and muted_id = $2
+        ",
+        muter_id,
+        muted_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
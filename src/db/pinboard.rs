@@ -0,0 +1,153 @@
+//! Queries for the Pinboard API, which calls the titles of the lists linking
+//! to a bookmark its tags. Tags can't contain whitespace, so it's replaced by
+//! underscores.
+
+use sqlx::query;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use super::{AppTx, Bookmark};
+use crate::response_error::ResponseResult;
+
+/// Restrict the bookmarks returned by [`bookmarks`]. All filters are optional.
+#[derive(Default)]
+pub struct Filter {
+    /// Tags the bookmarks need to have, in lowercase
+    pub tags: Vec<String>,
+    pub url: Option<String>,
+    /// Only bookmarks created on this day, in UTC
+    pub date: Option<Date>,
+    pub from: Option<OffsetDateTime>,
+    pub to: Option<OffsetDateTime>,
+    /// Number of bookmarks to skip
+    pub offset: i64,
+    pub limit: Option<i64>,
+}
+
+/// A bookmark with the titles of the user's lists linking to it.
+pub struct TaggedBookmark {
+    pub bookmark: Bookmark,
+    pub list_titles: Vec<String>,
+    /// Whether any of the lists is public
+    pub in_public_list: bool,
+}
+
+/// List the user's bookmarks matching `filter`, newest first.
+pub async fn bookmarks(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    filter: &Filter,
+) -> ResponseResult<Vec<TaggedBookmark>> {
+    let rows = query!(
+        r#"
+        select
+            bookmarks.id,
+            bookmarks.created_at,
+            bookmarks.ap_user_id,
+            bookmarks.url,
+            bookmarks.title,
+            bookmarks.ap_id,
+            bookmarks.notes,
+            bookmarks.updated_at,
+            coalesce(
+                array_agg(lists.title order by lists.title) filter (where lists.id is not null),
+                '{}'
+            ) as "list_titles!",
+            coalesce(bool_or(not lists.private), false) as "in_public_list!"
+        from bookmarks
+        left join links on links.dest_bookmark_id = bookmarks.id
+        left join lists on lists.id = links.src_list_id
+            and lists.ap_user_id = $1
+        where bookmarks.ap_user_id = $1
+            and cardinality($2::text[]) = (
+                select count(distinct lower(regexp_replace(trim(tag_lists.title), '\s+', '_', 'g')))
+                from links as tag_links
+                inner join lists as tag_lists on tag_lists.id = tag_links.src_list_id
+                where tag_links.dest_bookmark_id = bookmarks.id
+                    and tag_lists.ap_user_id = $1
+                    and lower(regexp_replace(trim(tag_lists.title), '\s+', '_', 'g')) = any($2)
+            )
+            and ($3::text is null or bookmarks.url = $3)
+            and ($4::date is null or (bookmarks.created_at at time zone 'UTC')::date = $4)
+            and ($5::timestamptz is null or bookmarks.created_at >= $5)
+            and ($6::timestamptz is null or bookmarks.created_at <= $6)
+        group by bookmarks.id
+        order by bookmarks.created_at desc, bookmarks.id desc
+        offset $7
+        limit $8
+        "#,
+        ap_user_id,
+        &filter.tags,
+        filter.url,
+        filter.date,
+        filter.from,
+        filter.to,
+        filter.offset,
+        filter.limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| -> ResponseResult<_> {
+            Ok(TaggedBookmark {
+                bookmark: Bookmark {
+                    id: row.id,
+                    created_at: row.created_at,
+                    ap_user_id: row.ap_user_id,
+                    url: row.url,
+                    title: row.title,
+                    ap_id: row.ap_id.parse()?,
+                    notes: row.notes,
+                    updated_at: row.updated_at,
+                },
+                list_titles: row.list_titles,
+                in_public_list: row.in_public_list,
+            })
+        })
+        .collect()
+}
+
+/// The titles of the user's lists with the number of the user's bookmarks in
+/// each.
+pub async fn list_titles_with_counts(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+) -> ResponseResult<Vec<(String, i64)>> {
+    let rows = query!(
+        r#"
+        select lists.title, count(distinct bookmarks.id) as "count!"
+        from lists
+        inner join links on links.src_list_id = lists.id
+        inner join bookmarks on bookmarks.id = links.dest_bookmark_id
+        where lists.ap_user_id = $1
+            and bookmarks.ap_user_id = $1
+        group by lists.id
+        "#,
+        ap_user_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| (row.title, row.count)).collect())
+}
+
+/// When the user's bookmarks last changed.
+pub async fn last_update(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+) -> ResponseResult<Option<OffsetDateTime>> {
+    let last_update = query!(
+        r#"
+        select greatest(max(created_at), max(updated_at)) as last_update
+        from bookmarks
+        where ap_user_id = $1
+        "#,
+        ap_user_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .last_update;
+
+    Ok(last_update)
+}
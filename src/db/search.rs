@@ -1,104 +1,144 @@
-use sqlx::{query, query_as};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use time::Date;
 use uuid::Uuid;
 
-use super::AppTx;
-use crate::response_error::ResponseResult;
+use super::{AppTx, LinkDestination, archives};
+use crate::{db, response_error::ResponseResult};
 
 pub enum PreviousPage {
     // We already are on the first page.
     DoesNotExist,
-    /// The previous page is the first page, so we have no bookmark id to query
+    /// The previous page is the first page, so we have no item id to query
     /// "after", but still need to show the link.
     IsFirstPage,
     /// There's another page before the previous page, so we can reference the
-    /// last bookmark of that page.
-    AfterBookmarkId(Uuid),
+    /// last item of that page.
+    AfterId(Uuid),
+}
+
+/// Restrict search results to one type of item.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Bookmarks,
+    Lists,
+}
+
+/// A parsed search query, see `routes::search::SearchQuery::parse`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Query {
+    /// Words and phrases to search for, in `websearch_to_tsquery` syntax.
+    pub terms: String,
+    /// The same words and phrases without quotes, which titles also contain as
+    /// substrings.
+    pub words: Vec<String>,
+    /// Words and phrases that must not appear in results.
+    pub excluded_terms: Vec<String>,
+    /// Only find items linked from all of these lists, by lowercase title.
+    pub lists: Vec<String>,
+    /// Only find bookmarks on one of these domains or their subdomains.
+    pub domains: Vec<String>,
+    /// Only find items created before this date.
+    pub before: Option<Date>,
+    /// Only find bookmarks with an archive in this state.
+    pub archived: Option<archives::Status>,
+    /// Only find bookmarks that aren't in any list.
+    pub unsorted: bool,
+    /// Only find private lists and bookmarks that aren't in any public list.
+    pub private: bool,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QueryError {
+    #[error("There's a quote (\") without a matching closing quote.")]
+    UnterminatedQuote,
+    #[error("The \"{0}:\" filter needs a value after the colon.")]
+    MissingValue(String),
+    #[error("The \"{0}\" filter can't be negated.")]
+    NegatedFilter(String),
+    #[error("\"{0}\" is not a valid date. Dates look like this: \"before:2025-01-31\".")]
+    InvalidDate(String),
+    #[error(
+        "\"{0}\" is not a valid archive state. Use \"archived:success\", \"archived:failed\" or \
+         \"archived:pending\"."
+    )]
+    InvalidArchiveStatus(String),
 }
 
 pub struct Results {
-    pub bookmarks: Vec<Result>,
+    pub items: Vec<Result>,
     pub previous_page: PreviousPage,
-    pub next_page_after_bookmark_id: Option<Uuid>,
+    pub next_page_after_id: Option<Uuid>,
     pub total_count: i64,
 }
 
 pub struct Result {
-    pub title: String,
-    pub bookmark_id: Uuid,
-    pub bookmark_url: String,
+    pub item: LinkDestination,
+    /// Matching passages of the bookmark notes, archived page or list
+    /// description, with matched words wrapped in [`HIGHLIGHT_START`] and
+    /// [`HIGHLIGHT_END`].
+    pub snippet: Option<String>,
+    /// The searching user's lists that link to this item.
+    pub lists: Vec<db::List>,
+    /// How many bookmarks and lists a list links to. Always zero for
+    /// bookmarks.
+    pub item_count: i64,
 }
 
+/// Marks the start of a matched word in a snippet. We use control characters
+/// that won't appear in the indexed text, so the snippets can be escaped as a
+/// whole and highlighted afterwards.
+pub const HIGHLIGHT_START: char = '\u{2}';
+pub const HIGHLIGHT_END: char = '\u{3}';
+
+/// Search the user's bookmarks and lists.
+///
+/// Uses the full-text search indexes from the `add_search_indexes` migration,
+/// ranking matches in titles above matches in list descriptions and archived
+/// page content. Titles also match on substrings, but those matches rank below
+/// full-text matches. The conditions for matching items, including all
+/// filters, are in the `search_items` SQL function.
 pub async fn search(
     tx: &mut AppTx,
-    term: &str,
+    query: &Query,
     ap_user_id: Uuid,
-    after_bookmark_id: Option<Uuid>,
+    kind: Option<Kind>,
+    after_id: Option<Uuid>,
 ) -> ResponseResult<Results> {
-    let mut bookmarks = query_as!(
-        Result,
-        r#"
-            select title, url as bookmark_url, id as bookmark_id
-            from bookmarks
-            where (bookmarks.title ilike '%' || $1 || '%')
-                and bookmarks.ap_user_id = $2
-                and ($3::uuid is null or bookmarks.id > $3)
-            order by bookmarks.id asc
-            limit 51
-        "#,
-        term,
+    let include_bookmarks = kind.is_none_or(|kind| kind == Kind::Bookmarks);
+    let include_lists = kind.is_none_or(|kind| kind == Kind::Lists);
+
+    let db::items::SearchPage {
+        results: mut items,
+        total_count,
+        previous_ids,
+    } = db::items::search(
+        tx,
+        query,
         ap_user_id,
-        after_bookmark_id
+        include_bookmarks,
+        include_lists,
+        after_id,
+        51,
     )
-    .fetch_all(&mut **tx)
     .await?;
 
-    let total_count = query!(
-        r#"
-            select count(bookmarks.id) as "count!" from bookmarks
-            where (bookmarks.title ilike '%' || $1 || '%')
-                and bookmarks.ap_user_id = $2
-        "#,
-        term,
-        ap_user_id
-    )
-    .fetch_one(&mut **tx)
-    .await?
-    .count;
-
-    let next_page_exists = bookmarks.len() == 51;
+    let next_page_exists = items.len() == 51;
     if next_page_exists {
-        bookmarks.pop();
+        items.pop();
     }
-    let next_page_after_bookmark_id = next_page_exists
-        .then_some(bookmarks.last().map(|b| b.bookmark_id))
+    let next_page_after_id = next_page_exists
+        .then_some(items.last().map(|r| r.item.id()))
         .flatten();
 
-    let first_id = bookmarks.first().map(|b| b.bookmark_id);
-    // Check if there are *any* bookmarks before the first of the current page.
-    // If so, fetch the ids for the previous page and take the first one.
-    // We need to fetch multiple bookmarks because we don't know how small the
-    // previous page is.
-    let previous_bookmarks = query!(
-        r#"
-            select bookmarks.id
-            from bookmarks
-            where (bookmarks.title ilike '%' || $1 || '%')
-                and bookmarks.ap_user_id = $2
-                and ($3::uuid is null or bookmarks.id < $3)
-            order by bookmarks.id desc
-            limit 51
-        "#,
-        term,
-        ap_user_id,
-        first_id
-    )
-    .fetch_all(&mut **tx)
-    .await?;
-    let previous_page = if let Some(last) = previous_bookmarks.last() {
-        if previous_bookmarks.len() == 51 {
+    // The items before the current page, in reverse. We need to fetch multiple
+    // items because we don't know how small the previous page is.
+    let previous_page = if let Some(last) = previous_ids.last() {
+        if previous_ids.len() == 51 {
             // There's another page before the previous page, so we can reference the last
-            // bookmark of that page.
-            PreviousPage::AfterBookmarkId(last.id)
+            // item of that page.
+            PreviousPage::AfterId(*last)
         } else {
             PreviousPage::IsFirstPage
         }
@@ -107,9 +147,9 @@ pub async fn search(
     };
 
     Ok(Results {
-        bookmarks,
+        items,
         previous_page,
-        next_page_after_bookmark_id,
+        next_page_after_id,
         total_count,
     })
 }
@@ -134,6 +134,20 @@ pub async fn by_ap_user_id(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<O
     Ok(user)
 }
 
+pub async fn count(tx: &mut AppTx) -> ResponseResult<i64> {
+    let count = query!(
+        r#"
+        select count(*) as "count!"
+        from users
+        "#,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .count;
+
+    Ok(count)
+}
+
 pub async fn create_if_not_exists(
     tx: &mut AppTx,
     create: CreateUser,
@@ -0,0 +1,399 @@
+//! Exporting a user's data, either as a Netscape bookmark file for browsers
+//! and other bookmarking services, or as a JSON backup that contains
+//! everything needed to restore it in another ties instance.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    db::{self, AppTx},
+    response_error::ResponseResult,
+};
+
+/// Increased whenever the backup format changes in a way that older versions
+/// of ties can't read.
+pub const BACKUP_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Backup {
+    pub version: u32,
+    #[serde(with = "time::serde::iso8601")]
+    pub exported_at: OffsetDateTime,
+    pub username: String,
+    pub bookmarks: Vec<Bookmark>,
+    pub lists: Vec<List>,
+    pub links: Vec<Link>,
+    /// Accounts the user follows, by their AP id.
+    pub following: Vec<Url>,
+    /// Accounts following the user, by their AP id.
+    pub followers: Vec<Url>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Bookmark {
+    /// Only used to refer to this bookmark in [`Backup::links`].
+    pub id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    pub url: String,
+    pub title: String,
+    pub notes: Option<String>,
+    pub archives: Vec<Archive>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Archive {
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    pub status: db::archives::Status,
+    pub error: Option<serde_json::Value>,
+    pub extracted_html: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct List {
+    /// Only used to refer to this list in [`Backup::links`].
+    pub id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    pub title: String,
+    pub content: Option<String>,
+    pub private: bool,
+    pub pinned: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Link {
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    /// Id of a list in [`Backup::lists`].
+    pub src: Uuid,
+    /// Id of a bookmark or list in the backup.
+    pub dest: Uuid,
+}
+
+/// Collect all bookmarks, lists, links, archives and follows of a user.
+pub async fn backup(tx: &mut AppTx, user: &db::User) -> ResponseResult<Backup> {
+    let mut archives: HashMap<Uuid, Vec<Archive>> = HashMap::new();
+    for archive in db::archives::list_by_ap_user_id(tx, user.ap_user_id).await? {
+        archives
+            .entry(archive.bookmark_id)
+            .or_default()
+            .push(Archive {
+                created_at: archive.created_at,
+                status: archive.status,
+                error: archive
+                    .error
+                    .map(|error| serde_json::to_value(error.0))
+                    .transpose()
+                    .context("Failed to serialize archive error")?,
+                extracted_html: archive.extracted_html,
+            });
+    }
+
+    let bookmarks = db::bookmarks::list_by_ap_user_id(tx, user.ap_user_id)
+        .await?
+        .into_iter()
+        .map(|bookmark| Bookmark {
+            archives: archives.remove(&bookmark.id).unwrap_or_default(),
+            id: bookmark.id,
+            created_at: bookmark.created_at,
+            url: bookmark.url,
+            title: bookmark.title,
+            notes: bookmark.notes,
+        })
+        .collect();
+
+    let lists = db::lists::list_by_ap_user_id(tx, user.ap_user_id)
+        .await?
+        .into_iter()
+        .map(|list| List {
+            id: list.id,
+            created_at: list.created_at,
+            title: list.title,
+            content: list.content,
+            private: list.private,
+            pinned: list.pinned,
+        })
+        .collect();
+
+    let links = db::links::list_by_ap_user_id(tx, user.ap_user_id)
+        .await?
+        .into_iter()
+        .filter_map(|link| {
+            Some(Link {
+                created_at: link.created_at,
+                src: link.src_list_id?,
+                dest: link.dest_bookmark_id.or(link.dest_list_id)?,
+            })
+        })
+        .collect();
+
+    let following = db::ap_users::list_following(tx, user.ap_user_id)
+        .await?
+        .into_iter()
+        .map(|ap_user| ap_user.ap_id.inner().clone())
+        .collect();
+    let followers = db::ap_users::list_followers(tx, user.ap_user_id)
+        .await?
+        .into_iter()
+        .map(|ap_user| ap_user.ap_id.inner().clone())
+        .collect();
+
+    Ok(Backup {
+        version: BACKUP_VERSION,
+        exported_at: OffsetDateTime::now_utc(),
+        username: user.username.clone(),
+        bookmarks,
+        lists,
+        links,
+        following,
+        followers,
+    })
+}
+
+/// Write a backup as a Netscape bookmark file.
+///
+/// Lists become folders. Since folders form a tree, lists that aren't linked
+/// from any other list are written at the top level, and lists and bookmarks
+/// linked from multiple lists appear in each of them. Bookmarks that aren't in
+/// any list are written at the top level as well.
+pub fn netscape_html(backup: &Backup) -> String {
+    let lists: HashMap<Uuid, &List> = backup.lists.iter().map(|list| (list.id, list)).collect();
+    let bookmarks: HashMap<Uuid, &Bookmark> = backup
+        .bookmarks
+        .iter()
+        .map(|bookmark| (bookmark.id, bookmark))
+        .collect();
+    let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    let mut linked = HashSet::new();
+    for link in &backup.links {
+        children.entry(link.src).or_default().push(link.dest);
+        linked.insert(link.dest);
+    }
+
+    let mut writer = NetscapeWriter {
+        html: String::new(),
+        lists,
+        bookmarks,
+        children,
+        written_lists: HashSet::new(),
+        path: Vec::new(),
+    };
+
+    writer.html.push_str(NETSCAPE_HEADER);
+    for list in &backup.lists {
+        if !linked.contains(&list.id) {
+            writer.list(list, 1);
+        }
+    }
+    // Lists that are only linked from each other in a cycle
+    for list in &backup.lists {
+        if !writer.written_lists.contains(&list.id) {
+            writer.list(list, 1);
+        }
+    }
+    for bookmark in &backup.bookmarks {
+        if !linked.contains(&bookmark.id) {
+            writer.bookmark(bookmark, 1);
+        }
+    }
+    writer.html.push_str("</DL><p>\n");
+
+    writer.html
+}
+
+const NETSCAPE_HEADER: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+"#;
+
+struct NetscapeWriter<'a> {
+    html: String,
+    lists: HashMap<Uuid, &'a List>,
+    bookmarks: HashMap<Uuid, &'a Bookmark>,
+    /// Ids of the bookmarks and lists each list links to
+    children: HashMap<Uuid, Vec<Uuid>>,
+    written_lists: HashSet<Uuid>,
+    /// The lists containing the list that's currently being written, to
+    /// avoid following cycles forever.
+    path: Vec<Uuid>,
+}
+
+impl NetscapeWriter<'_> {
+    fn list(&mut self, list: &List, depth: usize) {
+        self.written_lists.insert(list.id);
+        self.path.push(list.id);
+
+        let indent = "    ".repeat(depth);
+        let _ = writeln!(
+            self.html,
+            "{indent}<DT><H3 ADD_DATE=\"{}\">{}</H3>",
+            list.created_at.unix_timestamp(),
+            escape(&list.title)
+        );
+        if let Some(content) = list.content.as_deref().filter(|c| !c.is_empty()) {
+            let _ = writeln!(self.html, "{indent}<DD>{}", escape(content));
+        }
+        let _ = writeln!(self.html, "{indent}<DL><p>");
+
+        let children = self.children.get(&list.id).cloned().unwrap_or_default();
+        for child in children {
+            if let Some(child_list) = self.lists.get(&child).copied() {
+                if !self.path.contains(&child) {
+                    self.list(child_list, depth + 1);
+                }
+            } else if let Some(bookmark) = self.bookmarks.get(&child).copied() {
+                self.bookmark(bookmark, depth + 1);
+            }
+        }
+
+        let _ = writeln!(self.html, "{indent}</DL><p>");
+        self.path.pop();
+    }
+
+    fn bookmark(&mut self, bookmark: &Bookmark, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let _ = writeln!(
+            self.html,
+            "{indent}<DT><A HREF=\"{}\" ADD_DATE=\"{}\">{}</A>",
+            escape(&bookmark.url),
+            bookmark.created_at.unix_timestamp(),
+            escape(&bookmark.title)
+        );
+        if let Some(notes) = bookmark.notes.as_deref().filter(|n| !n.is_empty()) {
+            let _ = writeln!(self.html, "{indent}<DD>{}", escape(notes));
+        }
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::import::{self, Format, Parsed};
+
+    fn list(title: &str) -> List {
+        List {
+            id: Uuid::new_v4(),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            title: title.to_string(),
+            content: None,
+            private: false,
+            pinned: false,
+        }
+    }
+
+    fn bookmark(url: &str, notes: Option<&str>) -> Bookmark {
+        Bookmark {
+            id: Uuid::new_v4(),
+            created_at: OffsetDateTime::UNIX_EPOCH + time::Duration::days(1),
+            url: url.to_string(),
+            title: format!("<{url}> & more"),
+            notes: notes.map(ToString::to_string),
+            archives: Vec::new(),
+        }
+    }
+
+    fn link(src: Uuid, dest: Uuid) -> Link {
+        Link {
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            src,
+            dest,
+        }
+    }
+
+    #[test]
+    fn netscape_html_can_be_imported() -> Result<(), import::Error> {
+        let rust = list("Rust");
+        let books = list("Books \"2025\"");
+        // These two lists link to each other without any other list linking
+        // to them
+        let cycle_a = list("Cycle A");
+        let cycle_b = list("Cycle B");
+        let in_list = bookmark("https://doc.rust-lang.org/book/", Some("Chapter 4"));
+        let unsorted = bookmark("https://example.com/", None);
+        let backup = Backup {
+            version: BACKUP_VERSION,
+            exported_at: OffsetDateTime::UNIX_EPOCH,
+            username: "test".to_string(),
+            links: vec![
+                link(rust.id, books.id),
+                link(books.id, in_list.id),
+                link(cycle_a.id, cycle_b.id),
+                link(cycle_b.id, cycle_a.id),
+            ],
+            lists: vec![rust, books, cycle_a, cycle_b],
+            bookmarks: vec![in_list, unsorted],
+            following: Vec::new(),
+            followers: Vec::new(),
+        };
+
+        let Parsed::Bookmarks(parsed) = import::parse(Format::Netscape, &netscape_html(&backup))?
+        else {
+            panic!("Expected bookmarks");
+        };
+
+        let lists = parsed
+            .lists
+            .iter()
+            .map(|list| (list.title.as_str(), list.parent))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lists,
+            vec![
+                ("Rust", None),
+                ("Books \"2025\"", Some(0)),
+                ("Cycle A", None),
+                ("Cycle B", Some(2)),
+            ]
+        );
+        assert_eq!(
+            parsed.bookmarks,
+            vec![
+                import::Bookmark {
+                    url: "https://doc.rust-lang.org/book/".to_string(),
+                    title: "<https://doc.rust-lang.org/book/> & more".to_string(),
+                    notes: Some("Chapter 4".to_string()),
+                    created_at: Some(OffsetDateTime::UNIX_EPOCH + time::Duration::days(1)),
+                    lists: vec![1],
+                },
+                import::Bookmark {
+                    url: "https://example.com/".to_string(),
+                    title: "<https://example.com/> & more".to_string(),
+                    notes: None,
+                    created_at: Some(OffsetDateTime::UNIX_EPOCH + time::Duration::days(1)),
+                    lists: vec![],
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
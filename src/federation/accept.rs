@@ -1,5 +1,9 @@
 use activitypub_federation::{
-    config::Data, fetch::object_id::ObjectId, kinds::activity::AcceptType, traits::ActivityHandler,
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::activity::AcceptType,
+    protocol::verification::{verify_is_remote_object, verify_urls_match},
+    traits::ActivityHandler,
 };
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -53,11 +57,28 @@ impl ActivityHandler for Accept {
         self.actor.inner()
     }
 
-    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_is_remote_object(&self.actor, data)?;
+        // Only the followed user can accept a follow request
+        verify_urls_match(self.actor.inner(), self.object.object.inner())?;
         Ok(())
     }
 
-    async fn receive(self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
-        Err(ResponseError::NotFound)
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let follower = self.object.actor.dereference_local(data).await?;
+        let followed = self.actor.dereference(data).await?;
+
+        let mut tx = data.db_pool.begin().await?;
+        db::follows::accept(
+            &mut tx,
+            db::follows::Insert {
+                follower_id: follower.id,
+                following_id: followed.id,
+            },
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
     }
 }
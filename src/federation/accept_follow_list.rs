@@ -0,0 +1,66 @@
+use activitypub_federation::{
+    config::Data, fetch::object_id::ObjectId, kinds::activity::AcceptType, traits::ActivityHandler,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db,
+    federation::{activity, follow_list::FollowList},
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// Sent by a public list to accept a new follower. Lists accept all followers
+/// right away.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptFollowList {
+    actor: ObjectId<db::ListActor>,
+    object: FollowList,
+    #[serde(rename = "type")]
+    kind: AcceptType,
+    id: Url,
+}
+
+impl AcceptFollowList {
+    pub async fn send(
+        list: &db::ListActor,
+        object: FollowList,
+        context: &Data<super::context::Context>,
+    ) -> ResponseResult<()> {
+        let id = super::activity::generate_id(context)?;
+        let follower = object.actor.dereference(context).await?;
+        let accept = AcceptFollowList {
+            actor: list.ap_id.clone(),
+            object,
+            kind: AcceptType::Accept,
+            id,
+        };
+        activity::send(list, accept, &[&follower], context).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for AcceptFollowList {
+    type DataType = super::context::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn receive(self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        // Local users can't follow lists yet
+        Err(ResponseError::NotFound)
+    }
+}
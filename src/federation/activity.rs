@@ -2,6 +2,7 @@ use std::fmt::Debug;
 
 use activitypub_federation::{
     activity_queue::queue_activity,
+    kinds::public,
     protocol::context::WithContext,
     traits::{ActivityHandler, Actor},
 };
@@ -30,6 +31,15 @@ where
     Ok(())
 }
 
+/// Whether an activity is addressed to everyone, and not only to followers or
+/// mentioned users. The public collection may also be written as `as:Public`.
+pub fn is_public(to: &[Url], cc: &[Url]) -> bool {
+    let public = public();
+    to.iter()
+        .chain(cc)
+        .any(|url| *url == public || url.as_str() == "as:Public")
+}
+
 pub fn generate_id(context: &Data) -> Result<Url, url::ParseError> {
     context
         .base_url
@@ -0,0 +1,130 @@
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::{activity::AnnounceType, public},
+    protocol::{helpers::deserialize_one_or_many, verification::verify_domains_match},
+    traits::ActivityHandler,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db,
+    federation::{self, CreateBookmark, group},
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// Sent by a public list to its followers when a bookmark is added to it. The
+/// list wraps the `Create` activity of the bookmark, like Lemmy communities
+/// do for new posts.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnounceBookmark {
+    pub actor: ObjectId<db::ListActor>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    #[serde(deserialize_with = "deserialize_one_or_many", default)]
+    pub cc: Vec<Url>,
+    pub object: CreateBookmark,
+    #[serde(rename = "type")]
+    pub kind: AnnounceType,
+    pub id: Url,
+}
+
+impl AnnounceBookmark {
+    /// The activity for a bookmark in the list's outbox. Its id is derived
+    /// from the list and bookmark, so it stays the same each time the outbox
+    /// is read.
+    pub async fn from_bookmark(
+        list: &db::ListActor,
+        bookmark: db::Bookmark,
+        context: &federation::Data,
+    ) -> ResponseResult<Self> {
+        let mut id = list.ap_id.inner().clone();
+        id.set_fragment(Some(&bookmark.id.to_string()));
+
+        Self::new(list, bookmark, id, context).await
+    }
+
+    async fn new(
+        list: &db::ListActor,
+        bookmark: db::Bookmark,
+        id: Url,
+        context: &federation::Data,
+    ) -> ResponseResult<Self> {
+        let mut tx = context.db_pool.begin().await?;
+        let author = db::ap_users::read_by_id(&mut tx, bookmark.ap_user_id).await?;
+        drop(tx);
+
+        let mut create = CreateBookmark::from_bookmark(&author, bookmark, context).await?;
+        // Tells Lemmy which community the post belongs to
+        create.object.audience = Some(list.ap_id.inner().clone());
+
+        Ok(AnnounceBookmark {
+            actor: list.ap_id.clone(),
+            to: vec![public()],
+            cc: vec![group::followers_url(&context.base_url, list.list.id)?],
+            object: create,
+            kind: AnnounceType::Announce,
+            id,
+        })
+    }
+
+    /// Announce a bookmark that was just added to a list. Does nothing for
+    /// private lists.
+    pub async fn send_to_followers(
+        list: &db::List,
+        bookmark: db::Bookmark,
+        context: &federation::Data,
+    ) -> ResponseResult<()> {
+        if list.private {
+            return Ok(());
+        }
+
+        let mut tx = context.db_pool.begin().await?;
+        let followers = db::ap_users::list_list_followers(&mut tx, list.id).await?;
+        if followers.is_empty() {
+            return Ok(());
+        }
+        let list = db::list_actors::by_list(&mut tx, list.clone(), &context.base_url).await?;
+        tx.commit().await?;
+
+        let id = super::activity::generate_id(context)?;
+        let announce = Self::new(&list, bookmark, id, context).await?;
+        super::activity::send(
+            &list,
+            announce,
+            &followers.iter().collect::<Vec<_>>(),
+            context,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for AnnounceBookmark {
+    type DataType = super::context::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &federation::Data) -> Result<(), Self::Error> {
+        verify_domains_match(self.actor.inner(), &self.id)?;
+        // Lists only announce bookmarks that were created by their authors
+        verify_domains_match(self.object.actor.inner(), self.object.object.id.inner())?;
+
+        Ok(())
+    }
+
+    async fn receive(self, _data: &federation::Data) -> Result<(), Self::Error> {
+        // Local users can't follow lists yet
+        Err(ResponseError::NotFound)
+    }
+}
@@ -2,18 +2,24 @@ use activitypub_federation::{
     config::Data,
     fetch::object_id::ObjectId,
     kinds::{link::LinkType, object::NoteType, public},
-    protocol::verification::{verify_domains_match, verify_is_remote_object},
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_domains_match, verify_is_remote_object},
+    },
     traits::Object,
 };
 use anyhow::anyhow;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use url::Url;
 
 use crate::{
     db::{self, bookmarks::InsertBookmark},
+    markdown,
     response_error::{ResponseError, into_option},
 };
 
+const MARKDOWN_MEDIA_TYPE: &str = "text/markdown";
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BookmarkJson {
@@ -27,8 +33,43 @@ pub struct BookmarkJson {
     pub content: Option<String>,
     /// The title
     pub name: Option<String>,
-    #[serde(default)]
+    /// The markdown notes, so ties instances can edit them without converting
+    /// `content` back from HTML
+    pub source: Option<Source>,
+    /// Betula calls this `attachment`, as in the ActivityStreams vocabulary
+    #[serde(default, alias = "attachment", deserialize_with = "deserialize_links")]
     pub(crate) attachments: Vec<Link>,
+    /// The list that announced this bookmark, see [`super::AnnounceBookmark`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<Url>,
+}
+
+impl BookmarkJson {
+    /// Posts from other platforms, like Mastodon statuses, are notes without
+    /// a title or link.
+    pub fn is_bookmark(&self) -> bool {
+        self.name.is_some() && !self.attachments.is_empty()
+    }
+}
+
+/// Skip attachments that aren't links, like the images of Mastodon statuses.
+fn deserialize_links<'de, D>(deserializer: D) -> Result<Vec<Link>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let attachments: Vec<serde_json::Value> = deserialize_one_or_many(deserializer)?;
+
+    Ok(attachments
+        .into_iter()
+        .filter_map(|attachment| serde_json::from_value(attachment).ok())
+        .collect())
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Source {
+    pub content: String,
+    pub media_type: String,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -55,6 +96,10 @@ impl TryFrom<BookmarkJson> for InsertBookmark {
         let create_bookmark = InsertBookmark {
             url,
             title: value.name.ok_or_else(|| anyhow!("Missing title"))?,
+            notes: value
+                .source
+                .filter(|source| source.media_type == MARKDOWN_MEDIA_TYPE)
+                .map(|source| source.content),
         };
 
         // TODO how to validate InsertBookmark?
@@ -93,10 +138,21 @@ impl Object for db::Bookmark {
             media_type: None,
             kind: LinkType::Link,
         }];
+        let notes = self
+            .notes
+            .as_deref()
+            .map(markdown::to_html)
+            .unwrap_or_default();
         let content = format!(
-            r#"<p>{}</p><a href="{}">{}</p>"#,
-            self.title, self.url, self.url
+            r#"<p>{}</p>{notes}<p><a href="{}">{}</a></p>"#,
+            ammonia::clean_text(&self.title),
+            ammonia::clean_text(&self.url),
+            ammonia::clean_text(&self.url)
         );
+        let source = self.notes.map(|notes| Source {
+            content: notes,
+            media_type: MARKDOWN_MEDIA_TYPE.to_string(),
+        });
         Ok(BookmarkJson {
             id: self.ap_id,
             kind: NoteType::Note,
@@ -104,7 +160,9 @@ impl Object for db::Bookmark {
             to: vec![public()],
             content: Some(content),
             name: Some(self.title),
+            source,
             attachments,
+            audience: None,
         })
     }
 
@@ -132,3 +190,28 @@ impl Object for db::Bookmark {
         Ok(new_bookmark)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mastodon_statuses_are_not_bookmarks() {
+        let status: BookmarkJson = serde_json::from_value(serde_json::json!({
+            "id": "https://mastodon.example/users/alice/statuses/1",
+            "type": "Note",
+            "attributedTo": "https://mastodon.example/users/alice",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"],
+            "content": "<p>Look at this</p>",
+            "attachment": [{
+                "type": "Document",
+                "mediaType": "image/png",
+                "url": "https://mastodon.example/media/1.png"
+            }]
+        }))
+        .unwrap();
+
+        assert!(status.attachments.is_empty());
+        assert!(!status.is_bookmark());
+    }
+}
@@ -0,0 +1,39 @@
+use activitypub_federation::{
+    fetch::object_id::ObjectId, kinds::collection::OrderedCollectionType,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+
+use crate::db;
+
+/// Actors related to a local user, like their followers. Unlike the outbox,
+/// these aren't split into pages.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ActorCollection {
+    pub id: Url,
+    #[serde(rename = "type")]
+    pub kind: OrderedCollectionType,
+    pub total_items: usize,
+    pub ordered_items: Vec<ObjectId<db::ApUser>>,
+}
+
+impl ActorCollection {
+    pub fn new(id: Url, actors: Vec<db::ApUser>) -> Self {
+        Self {
+            id,
+            kind: OrderedCollectionType::OrderedCollection,
+            total_items: actors.len(),
+            ordered_items: actors.into_iter().map(|actor| actor.ap_id).collect(),
+        }
+    }
+}
+
+pub fn followers_url(base_url: &Url, ap_user_id: Uuid) -> Result<Url, url::ParseError> {
+    base_url.join(&format!("/ap/user/{ap_user_id}/followers"))
+}
+
+pub fn following_url(base_url: &Url, ap_user_id: Uuid) -> Result<Url, url::ParseError> {
+    base_url.join(&format!("/ap/user/{ap_user_id}/following"))
+}
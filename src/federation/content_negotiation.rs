@@ -0,0 +1,144 @@
+//! Serve users, bookmarks and lists as ActivityPub objects and as HTML pages
+//! from the same URLs, depending on the `Accept` header.
+//!
+//! The ActivityPub handlers keep living at their `/ap/...` routes, which are
+//! used as the objects' IDs. This middleware rewrites requests for the HTML
+//! pages that ask for ActivityPub JSON to these routes, and redirects browsers
+//! that open an `/ap/...` URL to the HTML page.
+
+use anyhow::Context;
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, Method, Uri, header},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use uuid::Uuid;
+
+use crate::{
+    db,
+    response_error::{ResponseError, ResponseResult},
+    server::AppState,
+};
+
+/// Media types that ActivityPub servers send when fetching objects.
+const ACTIVITY_MEDIA_TYPES: [&str; 2] = ["application/activity+json", "application/ld+json"];
+
+pub async fn negotiate(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> ResponseResult<Response> {
+    if !matches!(*request.method(), Method::GET | Method::HEAD) {
+        return Ok(next.run(request).await);
+    }
+
+    let path = request.uri().path().to_string();
+    let negotiated = if accepts_activity_json(request.headers()) {
+        if let Some(ap_path) = ap_path(&state, &path).await? {
+            *request.uri_mut() = rewrite_path(request.uri(), &ap_path)?;
+        }
+        is_negotiated(&path)
+    } else if accepts_html(request.headers())
+        && let Some(html_path) = html_path(&state, &path).await?
+    {
+        return Ok(Redirect::to(&html_path).into_response());
+    } else {
+        is_negotiated(&path)
+    };
+
+    let mut response = next.run(request).await;
+    if negotiated {
+        // Tell caches to store the HTML and JSON responses separately
+        response
+            .headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept"));
+    }
+
+    Ok(response)
+}
+
+fn accepts_activity_json(headers: &HeaderMap) -> bool {
+    media_types(headers).any(|media_type| ACTIVITY_MEDIA_TYPES.contains(&media_type))
+}
+
+fn accepts_html(headers: &HeaderMap) -> bool {
+    media_types(headers).any(|media_type| media_type == "text/html")
+}
+
+/// The media types listed in the `Accept` header, without parameters.
+fn media_types(headers: &HeaderMap) -> impl Iterator<Item = &str> {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_range| media_range.split(';').next())
+        .map(str::trim)
+}
+
+/// Whether the path is one of the pages that has an ActivityPub
+/// representation.
+fn is_negotiated(path: &str) -> bool {
+    ["/user/", "/bookmarks/", "/lists/", "/ap/"]
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+}
+
+/// Find the ActivityPub route for the HTML page at `path`.
+async fn ap_path(state: &AppState, path: &str) -> ResponseResult<Option<String>> {
+    if let Some(username) = path.strip_prefix("/user/") {
+        // Remote users are only shown as HTML
+        let mut tx = state.pool.begin().await?;
+        return match db::users::by_username(&mut tx, username).await {
+            Ok(user) => Ok(Some(format!("/ap/user/{}", user.ap_user_id))),
+            Err(ResponseError::NotFound) => Ok(None),
+            Err(other) => Err(other),
+        };
+    }
+
+    if let Some(id) = path.strip_prefix("/bookmarks/").and_then(parse_id) {
+        return Ok(Some(format!("/ap/bookmark/{id}")));
+    }
+
+    if let Some(id) = path.strip_prefix("/lists/").and_then(parse_id) {
+        return Ok(Some(format!("/ap/list/{id}")));
+    }
+
+    Ok(None)
+}
+
+/// Find the HTML page for the ActivityPub route at `path`.
+async fn html_path(state: &AppState, path: &str) -> ResponseResult<Option<String>> {
+    if let Some(id) = path.strip_prefix("/ap/user/").and_then(parse_id) {
+        let mut tx = state.pool.begin().await?;
+        return match db::users::by_ap_user_id(&mut tx, id).await? {
+            Some(user) => Ok(Some(format!("/user/{}", user.username))),
+            None => Ok(None),
+        };
+    }
+
+    if let Some(id) = path.strip_prefix("/ap/bookmark/").and_then(parse_id) {
+        return Ok(Some(format!("/bookmarks/{id}")));
+    }
+
+    if let Some(id) = path.strip_prefix("/ap/list/").and_then(parse_id) {
+        return Ok(Some(format!("/lists/{id}")));
+    }
+
+    Ok(None)
+}
+
+/// Only match the object itself, not e.g. `/lists/{id}/feed`
+fn parse_id(rest: &str) -> Option<Uuid> {
+    Uuid::parse_str(rest).ok()
+}
+
+fn rewrite_path(uri: &Uri, path: &str) -> ResponseResult<Uri> {
+    let path_and_query = match uri.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_string(),
+    };
+
+    Ok(Uri::try_from(path_and_query).context("Failed to rewrite URI")?)
+}
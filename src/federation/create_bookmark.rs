@@ -1,6 +1,6 @@
 use activitypub_federation::{
     fetch::object_id::ObjectId,
-    kinds::activity::CreateType,
+    kinds::{activity::CreateType, public},
     protocol::{
         helpers::deserialize_one_or_many,
         verification::{verify_domains_match, verify_is_remote_object},
@@ -11,7 +11,8 @@ use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::{
-    db, federation,
+    db,
+    federation::{self, activity, collection},
     response_error::{ResponseError, ResponseResult},
 };
 
@@ -21,6 +22,8 @@ pub struct CreateBookmark {
     pub actor: ObjectId<db::ApUser>,
     #[serde(deserialize_with = "deserialize_one_or_many")]
     pub to: Vec<Url>,
+    #[serde(deserialize_with = "deserialize_one_or_many", default)]
+    pub cc: Vec<Url>,
     pub object: federation::BookmarkJson,
     #[serde(rename = "type")]
     pub kind: CreateType,
@@ -28,6 +31,26 @@ pub struct CreateBookmark {
 }
 
 impl CreateBookmark {
+    /// The activity for a bookmark in its author's outbox. Its id is derived
+    /// from the bookmark, so it stays the same each time the outbox is read.
+    pub async fn from_bookmark(
+        actor: &db::ApUser,
+        bookmark: db::Bookmark,
+        context: &super::Data,
+    ) -> ResponseResult<Self> {
+        let mut id = bookmark.ap_id.inner().clone();
+        id.set_fragment(Some("create"));
+
+        Ok(CreateBookmark {
+            actor: actor.ap_id.clone(),
+            to: vec![public()],
+            cc: vec![collection::followers_url(&context.base_url, actor.id)?],
+            object: bookmark.into_json(context).await?,
+            kind: CreateType::Create,
+            id,
+        })
+    }
+
     pub async fn send_to_followers(
         actor: &db::ApUser,
         bookmark: db::Bookmark,
@@ -38,13 +61,10 @@ impl CreateBookmark {
 
         let mut tx = context.db_pool.begin().await?;
         let followers = db::ap_users::list_followers(&mut tx, actor.id).await?;
-        let to = followers
-            .iter()
-            .map(|ap_user| ap_user.ap_id.clone().into_inner())
-            .collect();
         let create = CreateBookmark {
             actor: actor.ap_id.clone(),
-            to,
+            to: vec![public()],
+            cc: vec![collection::followers_url(&context.base_url, actor.id)?],
             object,
             kind: CreateType::Create,
             id,
@@ -83,7 +103,40 @@ impl ActivityHandler for CreateBookmark {
         Ok(())
     }
 
-    async fn receive(self, _data: &super::Data) -> Result<(), Self::Error> {
-        Err(ResponseError::NotFound)
+    async fn receive(self, data: &super::Data) -> Result<(), Self::Error> {
+        let is_public = activity::is_public(&self.to, &self.cc);
+        receive_bookmark(&self.actor, is_public, self.object, data).await
     }
 }
+
+/// Store a bookmark that was created or updated by a remote user. Bookmarks
+/// that aren't public and posts that aren't bookmarks are ignored.
+pub(super) async fn receive_bookmark(
+    actor: &ObjectId<db::ApUser>,
+    is_public: bool,
+    object: federation::BookmarkJson,
+    data: &super::Data,
+) -> ResponseResult<()> {
+    if !is_public {
+        tracing::debug!(id = %object.id.inner(), "Ignoring bookmark that isn't public");
+        return Ok(());
+    }
+    if !object.is_bookmark() {
+        tracing::debug!(id = %object.id.inner(), "Ignoring post that isn't a bookmark");
+        return Ok(());
+    }
+
+    let author = actor.dereference(data).await?;
+
+    // Only store bookmarks of users that someone on this server follows
+    let mut tx = data.db_pool.begin().await?;
+    if !db::follows::has_local_followers(&mut tx, author.id).await? {
+        tracing::debug!(?author, "Ignoring bookmark of user without local followers");
+        return Ok(());
+    }
+    drop(tx);
+
+    db::Bookmark::from_json(object, data).await?;
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::{activity::DeleteType, object::TombstoneType, public},
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_domains_match, verify_is_remote_object},
+    },
+    traits::ActivityHandler,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db,
+    federation::collection,
+    response_error::{ResponseError, ResponseResult, into_option},
+};
+
+/// Sent when a bookmark is deleted, so that remote instances remove their
+/// copies of it.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteBookmark {
+    pub actor: ObjectId<db::ApUser>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    #[serde(deserialize_with = "deserialize_one_or_many", default)]
+    pub cc: Vec<Url>,
+    pub object: Tombstone,
+    #[serde(rename = "type")]
+    pub kind: DeleteType,
+    pub id: Url,
+}
+
+/// Replaces a deleted object.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Tombstone {
+    pub id: ObjectId<db::Bookmark>,
+    #[serde(rename = "type")]
+    pub kind: TombstoneType,
+}
+
+impl DeleteBookmark {
+    pub async fn send_to_followers(
+        actor: &db::ApUser,
+        bookmark: &db::Bookmark,
+        context: &super::Data,
+    ) -> ResponseResult<()> {
+        let id = super::activity::generate_id(context)?;
+
+        let mut tx = context.db_pool.begin().await?;
+        let followers = db::ap_users::list_followers(&mut tx, actor.id).await?;
+        let delete = DeleteBookmark {
+            actor: actor.ap_id.clone(),
+            to: vec![public()],
+            cc: vec![collection::followers_url(&context.base_url, actor.id)?],
+            object: Tombstone {
+                id: bookmark.ap_id.clone(),
+                kind: TombstoneType::Tombstone,
+            },
+            kind: DeleteType::Delete,
+            id,
+        };
+
+        super::activity::send(
+            actor,
+            delete,
+            &followers.iter().collect::<Vec<_>>(),
+            context,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for DeleteBookmark {
+    type DataType = super::context::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &super::Data) -> Result<(), Self::Error> {
+        verify_is_remote_object(&self.actor, data)?;
+        verify_domains_match(self.actor.inner(), self.object.id.inner())?;
+
+        Ok(())
+    }
+
+    async fn receive(self, data: &super::Data) -> Result<(), Self::Error> {
+        let mut tx = data.db_pool.begin().await?;
+
+        // We might never have received the bookmark in the first place
+        let Some(bookmark) = into_option(db::bookmarks::by_ap_id(&mut tx, self.object.id).await)?
+        else {
+            return Ok(());
+        };
+
+        let author = db::ap_users::read_by_id(&mut tx, bookmark.ap_user_id).await?;
+        if author.ap_id.inner() != self.actor.inner() {
+            return Err(ResponseError::NotFound);
+        }
+
+        db::bookmarks::delete_by_id(&mut tx, bookmark.id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
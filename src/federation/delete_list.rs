@@ -0,0 +1,80 @@
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::{activity::DeleteType, public},
+    protocol::helpers::deserialize_one_or_many,
+    traits::ActivityHandler,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db,
+    federation::{self, group},
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// Sent by a public list to its followers when it's deleted or made private,
+/// so that remote instances forget about it. Like an account deleting itself,
+/// the list is both the actor and the object.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteList {
+    pub actor: ObjectId<db::ListActor>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    #[serde(deserialize_with = "deserialize_one_or_many", default)]
+    pub cc: Vec<Url>,
+    pub object: ObjectId<db::ListActor>,
+    #[serde(rename = "type")]
+    pub kind: DeleteType,
+    pub id: Url,
+}
+
+impl DeleteList {
+    /// The followers have to be read before they're removed along with the
+    /// list.
+    pub async fn send(
+        list: &db::ListActor,
+        followers: &[db::ApUser],
+        context: &federation::Data,
+    ) -> ResponseResult<()> {
+        if followers.is_empty() {
+            return Ok(());
+        }
+
+        let delete = DeleteList {
+            actor: list.ap_id.clone(),
+            to: vec![public()],
+            cc: vec![group::followers_url(&context.base_url, list.list.id)?],
+            object: list.ap_id.clone(),
+            kind: DeleteType::Delete,
+            id: super::activity::generate_id(context)?,
+        };
+        super::activity::send(list, delete, &followers.iter().collect::<Vec<_>>(), context).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for DeleteList {
+    type DataType = super::context::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &federation::Data) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn receive(self, _data: &federation::Data) -> Result<(), Self::Error> {
+        // Local users can't follow lists yet
+        Err(ResponseError::NotFound)
+    }
+}
@@ -0,0 +1,55 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::activity::FollowType,
+    protocol::verification::{verify_domains_match, verify_is_remote_object},
+    traits::ActivityHandler,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{db, federation, response_error::ResponseError};
+
+/// Sent by users on other servers to follow a public list.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowList {
+    pub actor: ObjectId<db::ApUser>,
+    pub object: ObjectId<db::ListActor>,
+    #[serde(rename = "type")]
+    pub kind: FollowType,
+    pub id: Url,
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for FollowList {
+    type DataType = super::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_is_remote_object(&self.actor, data)?;
+        verify_domains_match(self.actor.inner(), &self.id)?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let actor = self.actor.dereference(data).await?;
+        let list = self.object.dereference_local(data).await?;
+
+        let mut tx = data.db_pool.begin().await?;
+        db::list_actors::insert_follower(&mut tx, list.list.id, actor.id).await?;
+        tx.commit().await?;
+
+        federation::AcceptFollowList::send(&list, self, data).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+//! Adapter to make [`db::ListActor`] compatible with the
+//! [`activitypub_federation`] crate.
+//!
+//! Public lists are modeled after Lemmy's communities, see
+//! [FEP-1b12](https://codeberg.org/fediverse/fep/src/branch/main/fep/1b12/fep-1b12.md).
+//! People on other servers can follow a list, and the list then announces all
+//! bookmarks that are added to it. Private lists are never federated.
+
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::{actor::GroupType, collection::OrderedCollectionType},
+    protocol::{public_key::PublicKey, verification::verify_domains_match},
+    traits::{Actor, Object},
+};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    db, federation, markdown,
+    response_error::{ResponseError, ResponseResult, into_option},
+};
+
+/// Number of bookmarks in the outbox of a list.
+const OUTBOX_SIZE: i64 = 20;
+
+/// Lists as we send them to other instances.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Group {
+    pub id: ObjectId<db::ListActor>,
+    #[serde(rename = "type")]
+    pub kind: GroupType,
+    /// The list's ID, since titles aren't unique
+    pub preferred_username: String,
+    /// The title
+    pub name: String,
+    /// The list's description, rendered as HTML
+    pub summary: Option<String>,
+    pub inbox: Url,
+    pub outbox: Url,
+    pub followers: Url,
+    pub public_key: PublicKey,
+    /// The list page
+    pub url: Url,
+    /// Tells Lemmy that only the list's owner can add posts
+    pub posting_restricted_to_mods: bool,
+}
+
+/// The announcements of the bookmarks most recently added to a list. Lemmy
+/// reads these when someone subscribes to a list for the first time.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupOutbox {
+    pub id: Url,
+    #[serde(rename = "type")]
+    pub kind: OrderedCollectionType,
+    pub total_items: i64,
+    pub ordered_items: Vec<federation::AnnounceBookmark>,
+}
+
+pub async fn outbox(
+    tx: &mut db::AppTx,
+    list: &db::ListActor,
+    context: &federation::Data,
+) -> ResponseResult<GroupOutbox> {
+    let bookmarks = db::bookmarks::list_by_list_id(tx, list.list.id, OUTBOX_SIZE).await?;
+    let mut ordered_items = Vec::with_capacity(bookmarks.len());
+    for bookmark in bookmarks {
+        ordered_items
+            .push(federation::AnnounceBookmark::from_bookmark(list, bookmark, context).await?);
+    }
+
+    Ok(GroupOutbox {
+        id: outbox_url(&context.base_url, list.list.id)?,
+        kind: OrderedCollectionType::OrderedCollection,
+        total_items: db::bookmarks::count_by_list_id(tx, list.list.id).await?,
+        ordered_items,
+    })
+}
+
+pub fn outbox_url(base_url: &Url, list_id: Uuid) -> Result<Url, url::ParseError> {
+    base_url.join(&format!("/ap/list/{list_id}/outbox"))
+}
+
+pub fn followers_url(base_url: &Url, list_id: Uuid) -> Result<Url, url::ParseError> {
+    base_url.join(&format!("/ap/list/{list_id}/followers"))
+}
+
+/// Load a public local list as an actor. Returns [`ResponseError::NotFound`]
+/// for private lists.
+pub async fn read_public_list(
+    tx: &mut db::AppTx,
+    list_id: Uuid,
+    base_url: &Url,
+) -> Result<db::ListActor, ResponseError> {
+    let list = db::lists::by_id(tx, list_id).await?;
+    if list.private {
+        return Err(ResponseError::NotFound);
+    }
+
+    db::list_actors::by_list(tx, list, base_url).await
+}
+
+#[async_trait::async_trait]
+impl Object for db::ListActor {
+    type DataType = super::Context;
+    type Kind = Group;
+    type Error = ResponseError;
+
+    async fn read_from_id(object_id: Url, data: &super::Data) -> Result<Option<Self>, Self::Error> {
+        // We only know about local lists
+        let prefix = data.base_url.join("/ap/list/")?;
+        let Some(list_id) = object_id
+            .as_str()
+            .strip_prefix(prefix.as_str())
+            .and_then(|id| Uuid::parse_str(id).ok())
+        else {
+            return Ok(None);
+        };
+
+        let mut tx = data.db_pool.begin().await?;
+        let list_actor = read_public_list(&mut tx, list_id, &data.base_url).await;
+        tx.commit().await?;
+        into_option(list_actor)
+    }
+
+    async fn into_json(self, context: &super::Data) -> Result<Self::Kind, Self::Error> {
+        let public_key = self.public_key();
+        let url = context.base_url.join(&self.list.path())?;
+        Ok(Group {
+            id: self.ap_id,
+            kind: GroupType::Group,
+            preferred_username: self.list.id.to_string(),
+            name: self.list.title,
+            summary: self.list.content.as_deref().map(markdown::to_html),
+            inbox: self.inbox_url,
+            outbox: outbox_url(&context.base_url, self.list.id)?,
+            followers: followers_url(&context.base_url, self.list.id)?,
+            public_key,
+            url,
+            posting_restricted_to_mods: true,
+        })
+    }
+
+    async fn verify(
+        json: &Self::Kind,
+        expected_domain: &Url,
+        _data: &super::Data,
+    ) -> Result<(), Self::Error> {
+        verify_domains_match(json.id.inner(), expected_domain)?;
+        Ok(())
+    }
+
+    async fn from_json(_json: Self::Kind, _data: &super::Data) -> Result<Self, Self::Error> {
+        // TODO allow following lists on other servers
+        Err(anyhow!("Lists on other servers are not supported").into())
+    }
+}
+
+impl Actor for db::ListActor {
+    fn id(&self) -> Url {
+        self.ap_id.inner().clone()
+    }
+
+    fn public_key_pem(&self) -> &str {
+        &self.public_key
+    }
+
+    fn private_key_pem(&self) -> Option<String> {
+        Some(self.private_key.expose_secret().clone())
+    }
+
+    fn inbox(&self) -> Url {
+        self.inbox_url.clone()
+    }
+}
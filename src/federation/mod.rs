@@ -1,18 +1,36 @@
 pub mod accept;
+pub mod accept_follow_list;
 pub mod activity;
+pub mod announce_bookmark;
 pub mod bookmark;
+pub mod collection;
 pub mod config;
+pub mod content_negotiation;
 pub mod context;
 pub mod create_bookmark;
+pub mod delete_bookmark;
+pub mod delete_list;
 pub mod follow;
+pub mod follow_list;
+pub mod group;
+pub mod outbox;
 pub mod person;
 pub mod signing;
 pub mod undo_follow;
+pub mod undo_follow_list;
+pub mod update_bookmark;
 pub mod webfinger;
 
 pub use accept::Accept;
+pub use accept_follow_list::AcceptFollowList;
+pub use announce_bookmark::AnnounceBookmark;
 pub use bookmark::BookmarkJson;
 pub use context::{Context, Data};
 pub use create_bookmark::CreateBookmark;
+pub use delete_bookmark::DeleteBookmark;
+pub use delete_list::DeleteList;
 pub use follow::Follow;
+pub use follow_list::FollowList;
 pub use undo_follow::UndoFollow;
+pub use undo_follow_list::UndoFollowList;
+pub use update_bookmark::UpdateBookmark;
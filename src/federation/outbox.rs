@@ -0,0 +1,111 @@
+use activitypub_federation::kinds::collection::{OrderedCollectionPageType, OrderedCollectionType};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    db::{self, AppTx},
+    federation::CreateBookmark,
+    response_error::ResponseResult,
+};
+
+/// Number of activities on each page of an outbox.
+const PAGE_SIZE: usize = 20;
+
+/// The `Create` activities for all public bookmarks of a local user, so that
+/// new followers can see what they bookmarked before following them.
+/// The activities themselves are split into [`OutboxPage`]s.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Outbox {
+    pub id: Url,
+    #[serde(rename = "type")]
+    pub kind: OrderedCollectionType,
+    pub total_items: i64,
+    pub first: Url,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxPage {
+    pub id: Url,
+    #[serde(rename = "type")]
+    pub kind: OrderedCollectionPageType,
+    pub part_of: Url,
+    pub ordered_items: Vec<CreateBookmark>,
+    /// Missing on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<Url>,
+}
+
+pub fn url(base_url: &Url, ap_user_id: Uuid) -> Result<Url, url::ParseError> {
+    base_url.join("/ap/outbox/")?.join(&ap_user_id.to_string())
+}
+
+/// The URL of the page that starts after the bookmark `before`, or the first
+/// page if that's `None`.
+fn page_url(outbox_url: &Url, before: Option<Uuid>) -> Url {
+    let mut url = outbox_url.clone();
+    url.query_pairs_mut().append_pair("page", "true");
+    if let Some(before) = before {
+        url.query_pairs_mut()
+            .append_pair("before", &before.to_string());
+    }
+    url
+}
+
+pub async fn outbox(
+    tx: &mut AppTx,
+    ap_user: &db::ApUser,
+    context: &super::Data,
+) -> ResponseResult<Outbox> {
+    let id = url(&context.base_url, ap_user.id)?;
+
+    Ok(Outbox {
+        first: page_url(&id, None),
+        id,
+        kind: OrderedCollectionType::OrderedCollection,
+        total_items: db::bookmarks::count_public_by_ap_user_id(tx, ap_user.id).await?,
+    })
+}
+
+/// The newest activities in the outbox, or the ones that come after the
+/// bookmark `before`.
+pub async fn page(
+    tx: &mut AppTx,
+    ap_user: &db::ApUser,
+    before: Option<Uuid>,
+    context: &super::Data,
+) -> ResponseResult<OutboxPage> {
+    let outbox_url = url(&context.base_url, ap_user.id)?;
+
+    // Fetch one more bookmark than needed to find out if there's a next page
+    let mut bookmarks = db::bookmarks::list_public_by_ap_user_id(
+        tx,
+        ap_user.id,
+        before,
+        i64::try_from(PAGE_SIZE + 1).unwrap_or(i64::MAX),
+    )
+    .await?;
+    let next = if bookmarks.len() > PAGE_SIZE {
+        bookmarks.truncate(PAGE_SIZE);
+        bookmarks
+            .last()
+            .map(|last| page_url(&outbox_url, Some(last.id)))
+    } else {
+        None
+    };
+
+    let mut ordered_items = Vec::with_capacity(bookmarks.len());
+    for bookmark in bookmarks {
+        ordered_items.push(CreateBookmark::from_bookmark(ap_user, bookmark, context).await?);
+    }
+
+    Ok(OutboxPage {
+        id: page_url(&outbox_url, before),
+        kind: OrderedCollectionPageType::OrderedCollectionPage,
+        part_of: outbox_url,
+        ordered_items,
+        next,
+    })
+}
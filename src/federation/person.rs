@@ -34,6 +34,9 @@ pub struct Person {
     pub summary: Option<String>,
     pub inbox: Url,
     pub outbox: Url,
+    /// Optional for actors on other servers
+    pub followers: Option<Url>,
+    pub following: Option<Url>,
     pub public_key: PublicKey,
     /// "Identifies one or more links to representations of the object"
     pub url: Url,
@@ -58,10 +61,7 @@ impl Object for db::ApUser {
     async fn into_json(self, context: &super::Data) -> Result<Self::Kind, Self::Error> {
         let public_key = self.public_key();
         let url = context.base_url.join("/user/")?.join(&self.username)?;
-        let outbox = context
-            .base_url
-            .join("/ap/outbox/")?
-            .join(&self.id.to_string())?;
+        let outbox = super::outbox::url(&context.base_url, self.id)?;
         Ok(Person {
             id: self.ap_id,
             name: self.display_name,
@@ -69,6 +69,14 @@ impl Object for db::ApUser {
             kind: PersonType::Person,
             inbox: self.inbox_url,
             outbox,
+            followers: Some(super::collection::followers_url(
+                &context.base_url,
+                self.id,
+            )?),
+            following: Some(super::collection::following_url(
+                &context.base_url,
+                self.id,
+            )?),
             public_key,
             summary: self.bio,
             url,
@@ -0,0 +1,57 @@
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::activity::UndoType,
+    protocol::verification::{verify_is_remote_object, verify_urls_match},
+    traits::ActivityHandler,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db,
+    federation::{self, follow_list::FollowList},
+    response_error::ResponseError,
+};
+
+/// Sent by users on other servers to unfollow a public list.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoFollowList {
+    pub(crate) actor: ObjectId<db::ApUser>,
+    pub(crate) object: FollowList,
+    #[serde(rename = "type")]
+    pub(crate) kind: UndoType,
+    pub(crate) id: Url,
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for UndoFollowList {
+    type DataType = super::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &federation::Data) -> Result<(), Self::Error> {
+        verify_urls_match(self.actor.inner(), self.object.actor.inner())?;
+        verify_is_remote_object(&self.actor, data)?;
+        self.object.verify(data).await?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &federation::Data) -> Result<(), Self::Error> {
+        let follower = self.actor.dereference(data).await?;
+        let list = self.object.object.dereference_local(data).await?;
+
+        let mut tx = data.db_pool.begin().await?;
+        db::list_actors::remove_follower(&mut tx, list.list.id, follower.id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::{activity::UpdateType, public},
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_domains_match, verify_is_remote_object},
+    },
+    traits::{ActivityHandler, Object},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db,
+    federation::{self, activity, collection, create_bookmark::receive_bookmark},
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// Sent when a bookmark is edited, so that remote copies of it stay up to
+/// date.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateBookmark {
+    pub actor: ObjectId<db::ApUser>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    #[serde(deserialize_with = "deserialize_one_or_many", default)]
+    pub cc: Vec<Url>,
+    pub object: federation::BookmarkJson,
+    #[serde(rename = "type")]
+    pub kind: UpdateType,
+    pub id: Url,
+}
+
+impl UpdateBookmark {
+    pub async fn send_to_followers(
+        actor: &db::ApUser,
+        bookmark: db::Bookmark,
+        context: &super::Data,
+    ) -> ResponseResult<()> {
+        let object = bookmark.into_json(context).await?;
+        let id = super::activity::generate_id(context)?;
+
+        let mut tx = context.db_pool.begin().await?;
+        let followers = db::ap_users::list_followers(&mut tx, actor.id).await?;
+        let update = UpdateBookmark {
+            actor: actor.ap_id.clone(),
+            to: vec![public()],
+            cc: vec![collection::followers_url(&context.base_url, actor.id)?],
+            object,
+            kind: UpdateType::Update,
+            id,
+        };
+
+        super::activity::send(
+            actor,
+            update,
+            &followers.iter().collect::<Vec<_>>(),
+            context,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for UpdateBookmark {
+    type DataType = super::context::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &super::Data) -> Result<(), Self::Error> {
+        verify_is_remote_object(&self.actor, data)?;
+        verify_domains_match(self.actor.inner(), self.object.id.inner())?;
+        db::Bookmark::verify(&self.object, self.actor.inner(), data).await?;
+
+        Ok(())
+    }
+
+    /// Also stores bookmarks we didn't know about yet, e.g. ones that were
+    /// just added to a public list.
+    async fn receive(self, data: &super::Data) -> Result<(), Self::Error> {
+        let is_public = activity::is_public(&self.to, &self.cc);
+        receive_bookmark(&self.actor, is_public, self.object, data).await
+    }
+}
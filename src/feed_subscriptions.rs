@@ -0,0 +1,234 @@
+//! Lists can subscribe to external RSS and Atom feeds. A background task polls
+//! all feeds regularly and adds their new entries to the subscribed lists as
+//! bookmarks.
+//!
+//! - Feeds are fetched with the same restrictions as archived pages, see
+//!   [`archive::Client::fetch_feed`], including the per-host rate limit.
+//! - Entries are recognized by their GUID, or their URL if they don't have one,
+//!   so that each entry is only added once.
+//! - If the list's owner already bookmarked an entry's URL, the existing
+//!   bookmark is linked instead of creating a new one.
+//! - Feeds are polled one after another, without parallelism.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use itertools::Itertools;
+use tokio::time::MissedTickBehavior;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    archive,
+    db::{self, AppTx, bookmarks::InsertBookmark, feed_subscriptions::FeedSubscription},
+    federation,
+    forms::links::CreateLink,
+    response_error::ResponseResult,
+    server::AppState,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Feeds often contain many older entries, so we only add the newest ones
+/// when subscribing instead of flooding the list.
+const MAX_NEW_ENTRIES: usize = 20;
+
+/// Poll all subscribed feeds now, and again after every [`POLL_INTERVAL`].
+pub fn poll_in_background(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            if let Err(error) = poll_all(&state).await {
+                tracing::error!(?error, "Could not poll feeds");
+            }
+        }
+    });
+}
+
+/// Poll a new subscription right away, so that its entries show up without
+/// waiting for the next scheduled poll.
+pub fn poll_once_in_background(state: AppState, subscription: FeedSubscription) {
+    tokio::spawn(async move {
+        if let Err(error) = poll(&state, &subscription).await {
+            tracing::error!(?error, ?subscription, "Could not poll feed");
+        }
+    });
+}
+
+async fn poll_all(state: &AppState) -> Result<()> {
+    let mut tx = state.pool.begin().await?;
+    let subscriptions = db::feed_subscriptions::list_all(&mut tx).await?;
+    tx.commit().await?;
+
+    tracing::debug!(count = subscriptions.len(), "Polling feeds");
+    for subscription in subscriptions {
+        if let Err(error) = poll(state, &subscription).await {
+            tracing::error!(?error, ?subscription, "Could not poll feed");
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetch a feed and add its new entries to the subscribed list. Failures to
+/// fetch the feed are stored so they can be shown to the user.
+async fn poll(state: &AppState, subscription: &FeedSubscription) -> Result<()> {
+    tracing::info!(url = %subscription.url, "Polling feed");
+    let feed = fetch(&state.archive_client, &subscription.url).await;
+
+    let mut tx = state.pool.begin().await?;
+    let archive_ids = match feed {
+        Ok(feed) => {
+            let archive_ids = add_entries(&mut tx, state, subscription, feed).await?;
+            db::feed_subscriptions::set_polled(&mut tx, subscription.id, None).await?;
+            archive_ids
+        }
+        Err(error) => {
+            tracing::info!(?error, "Could not fetch feed");
+            db::feed_subscriptions::set_polled(&mut tx, subscription.id, Some(&error.to_string()))
+                .await?;
+            Vec::new()
+        }
+    };
+    tx.commit().await?;
+
+    state.archive_queue.archive_all_in_background(archive_ids);
+
+    Ok(())
+}
+
+async fn fetch(client: &archive::Client, url: &str) -> Result<feed_rs::model::Feed> {
+    let body = client.fetch_feed(url).await?;
+
+    Ok(parse(&body)?)
+}
+
+pub fn parse(body: &[u8]) -> Result<feed_rs::model::Feed, feed_rs::parser::ParseFeedError> {
+    feed_rs::parser::Builder::new()
+        // By default, feed-rs makes up IDs for entries without a GUID, but we want to
+        // recognize those by their URL instead
+        .id_generator(|_links, _title, _uri| String::new())
+        .build()
+        .parse(body)
+}
+
+struct NewEntry {
+    /// The entry's GUID, or its URL if it doesn't have one
+    key: String,
+    url: String,
+    title: String,
+}
+
+impl NewEntry {
+    /// Returns `None` for entries that don't link to a website.
+    fn from_entry(entry: feed_rs::model::Entry) -> Option<Self> {
+        let link = entry
+            .links
+            .iter()
+            .find(|link| link.rel.as_deref().is_none_or(|rel| rel == "alternate"))
+            .or(entry.links.first())?;
+        let url = Url::parse(&link.href).ok()?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return None;
+        }
+        let url = url.to_string();
+
+        let title = entry
+            .title
+            .map(|title| title.content.trim().to_string())
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| url.clone());
+        let key = if entry.id.is_empty() {
+            url.clone()
+        } else {
+            entry.id
+        };
+
+        Some(Self { key, url, title })
+    }
+}
+
+/// Add the entries of a feed that haven't been seen before to the subscribed
+/// list. Returns the pending archives for newly created bookmarks.
+pub async fn add_entries(
+    tx: &mut AppTx,
+    state: &AppState,
+    subscription: &FeedSubscription,
+    feed: feed_rs::model::Feed,
+) -> ResponseResult<Vec<Uuid>> {
+    let list = db::lists::by_id(tx, subscription.list_id).await?;
+    let Some(user) = db::users::by_ap_user_id(tx, list.ap_user_id).await? else {
+        tracing::warn!(?list, "Subscribed list doesn't belong to a local user");
+        return Ok(Vec::new());
+    };
+
+    let mut entries = feed
+        .entries
+        .into_iter()
+        .filter_map(NewEntry::from_entry)
+        .unique_by(|entry| entry.key.clone())
+        .collect::<Vec<_>>();
+    let keys = entries
+        .iter()
+        .map(|entry| entry.key.clone())
+        .collect::<Vec<_>>();
+    let new_keys = db::feed_subscriptions::insert_entry_keys(tx, subscription.id, &keys).await?;
+    // Feeds usually list their newest entries first
+    entries.retain(|entry| new_keys.contains(&entry.key));
+    entries.truncate(MAX_NEW_ENTRIES);
+
+    let actor = db::ap_users::read_by_id(tx, list.ap_user_id).await?;
+    let federation_data = state.federation_config.to_request_data();
+    let mut archive_ids = Vec::new();
+    for entry in entries {
+        let (bookmark, created) =
+            match db::bookmarks::by_url(tx, user.ap_user_id, &entry.url).await? {
+                Some(bookmark) => (bookmark, false),
+                None => {
+                    let bookmark = db::bookmarks::insert_local(
+                        tx,
+                        user.ap_user_id,
+                        InsertBookmark {
+                            url: entry.url,
+                            title: entry.title,
+                            notes: None,
+                        },
+                        &state.base_url,
+                    )
+                    .await?;
+                    (bookmark, true)
+                }
+            };
+
+        if db::links::exists(tx, list.id, bookmark.id).await? {
+            continue;
+        }
+        db::links::insert(
+            tx,
+            user.id,
+            CreateLink {
+                src: list.id,
+                dest: bookmark.id,
+            },
+        )
+        .await?;
+
+        federation::AnnounceBookmark::send_to_followers(&list, bookmark.clone(), &federation_data)
+            .await?;
+        if !created {
+            continue;
+        }
+        if subscription.archive {
+            archive_ids.push(db::archives::insert_pending(tx, bookmark.id).await?.id);
+        }
+        if !list.private {
+            federation::CreateBookmark::send_to_followers(&actor, bookmark, &federation_data)
+                .await?;
+        }
+    }
+
+    Ok(archive_ids)
+}
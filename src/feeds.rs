@@ -0,0 +1,206 @@
+//! RSS 2.0 and Atom feeds of public bookmarks, so people can follow lists and
+//! profiles with a feed reader.
+
+use std::fmt::Write;
+
+use time::{
+    OffsetDateTime,
+    format_description::well_known::{Rfc2822, Rfc3339},
+};
+use url::Url;
+
+use crate::{export::escape, markdown};
+
+/// Feed readers usually only show recent entries, so we don't include more.
+pub const MAX_ENTRIES: usize = 50;
+
+pub struct Feed {
+    pub title: String,
+    pub description: String,
+    /// The HTML page this feed belongs to
+    pub url: Url,
+    /// Where this feed can be downloaded
+    pub feed_url: Url,
+    pub entries: Vec<Entry>,
+}
+
+pub struct Entry {
+    /// Unique for each entry, e.g. the URL of the bookmark on this instance
+    pub id: Url,
+    pub title: String,
+    /// The bookmarked URL
+    pub url: String,
+    pub notes: Option<String>,
+    pub date: OffsetDateTime,
+}
+
+impl Feed {
+    /// The date of the newest entry, or the current time for empty feeds.
+    fn updated(&self) -> OffsetDateTime {
+        self.entries
+            .iter()
+            .map(|entry| entry.date)
+            .max()
+            .unwrap_or_else(OffsetDateTime::now_utc)
+    }
+}
+
+pub fn rss(feed: &Feed) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n<channel>\n");
+    let _ = writeln!(xml, "<title>{}</title>", escape(&feed.title));
+    let _ = writeln!(xml, "<link>{}</link>", escape(feed.url.as_str()));
+    let _ = writeln!(
+        xml,
+        "<description>{}</description>",
+        escape(&feed.description)
+    );
+    let _ = writeln!(
+        xml,
+        "<atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>",
+        escape(feed.feed_url.as_str())
+    );
+    let _ = writeln!(
+        xml,
+        "<lastBuildDate>{}</lastBuildDate>",
+        format_date(feed.updated(), &Rfc2822)
+    );
+
+    for entry in &feed.entries {
+        xml.push_str("<item>\n");
+        let _ = writeln!(xml, "<title>{}</title>", escape(&entry.title));
+        let _ = writeln!(xml, "<link>{}</link>", escape(&entry.url));
+        let _ = writeln!(
+            xml,
+            "<guid isPermaLink=\"false\">{}</guid>",
+            escape(entry.id.as_str())
+        );
+        let _ = writeln!(
+            xml,
+            "<pubDate>{}</pubDate>",
+            format_date(entry.date, &Rfc2822)
+        );
+        if let Some(notes) = &entry.notes {
+            let _ = writeln!(
+                xml,
+                "<description>{}</description>",
+                escape(&markdown::to_html(notes))
+            );
+        }
+        xml.push_str("</item>\n");
+    }
+
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+pub fn atom(feed: &Feed) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    let _ = writeln!(xml, "<id>{}</id>", escape(feed.feed_url.as_str()));
+    let _ = writeln!(xml, "<title>{}</title>", escape(&feed.title));
+    let _ = writeln!(xml, "<subtitle>{}</subtitle>", escape(&feed.description));
+    let _ = writeln!(
+        xml,
+        "<link href=\"{}\" rel=\"alternate\" type=\"text/html\"/>",
+        escape(feed.url.as_str())
+    );
+    let _ = writeln!(
+        xml,
+        "<link href=\"{}\" rel=\"self\" type=\"application/atom+xml\"/>",
+        escape(feed.feed_url.as_str())
+    );
+    let _ = writeln!(
+        xml,
+        "<updated>{}</updated>",
+        format_date(feed.updated(), &Rfc3339)
+    );
+
+    for entry in &feed.entries {
+        xml.push_str("<entry>\n");
+        let _ = writeln!(xml, "<id>{}</id>", escape(entry.id.as_str()));
+        let _ = writeln!(xml, "<title>{}</title>", escape(&entry.title));
+        let _ = writeln!(
+            xml,
+            "<link href=\"{}\" rel=\"alternate\"/>",
+            escape(&entry.url)
+        );
+        let _ = writeln!(
+            xml,
+            "<updated>{}</updated>",
+            format_date(entry.date, &Rfc3339)
+        );
+        if let Some(notes) = &entry.notes {
+            let _ = writeln!(
+                xml,
+                "<content type=\"html\">{}</content>",
+                escape(&markdown::to_html(notes))
+            );
+        }
+        xml.push_str("</entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn format_date(
+    date: OffsetDateTime,
+    format: &(impl time::formatting::Formattable + ?Sized),
+) -> String {
+    date.format(format).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn feed() -> anyhow::Result<Feed> {
+        let base_url = Url::parse("https://ties.example")?;
+        Ok(Feed {
+            title: "Rust & friends".to_string(),
+            description: "Bookmarks in the list Rust & friends".to_string(),
+            url: base_url.join("/lists/1")?,
+            feed_url: base_url.join("/lists/1/feed.atom")?,
+            entries: vec![Entry {
+                id: base_url.join("/bookmarks/2")?,
+                title: "<Tokio>".to_string(),
+                url: "https://tokio.rs/?a=1&b=2".to_string(),
+                notes: Some("An *async* runtime".to_string()),
+                date: OffsetDateTime::UNIX_EPOCH,
+            }],
+        })
+    }
+
+    #[test]
+    fn writes_rss() -> anyhow::Result<()> {
+        let rss = rss(&feed()?);
+
+        assert!(rss.contains("<title>Rust &amp; friends</title>"));
+        assert!(rss.contains(
+            "<item>\n<title>&lt;Tokio&gt;</title>\n<link>https://tokio.rs/?a=1&amp;b=2</link>\n"
+        ));
+        assert!(rss.contains("<pubDate>Thu, 01 Jan 1970 00:00:00 +0000</pubDate>"));
+        assert!(rss.contains(
+            "<description>&lt;p&gt;An &lt;em&gt;async&lt;/em&gt; runtime&lt;/p&gt;\n</description>"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn writes_atom() -> anyhow::Result<()> {
+        let atom = atom(&feed()?);
+
+        assert!(atom.contains("<id>https://ties.example/lists/1/feed.atom</id>"));
+        assert!(atom.contains("<updated>1970-01-01T00:00:00Z</updated>"));
+        assert_eq!(atom.matches("<entry>").count(), 1);
+        assert!(atom.contains("<link href=\"https://tokio.rs/?a=1&amp;b=2\" rel=\"alternate\"/>"));
+
+        Ok(())
+    }
+}
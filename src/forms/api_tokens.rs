@@ -0,0 +1,14 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::db::api_tokens::Scope;
+
+#[derive(Validate, Default, Serialize, Deserialize, Debug)]
+pub struct CreateApiToken {
+    #[garde(length(min = 1, max = 100))]
+    pub name: String,
+    /// Left out when the "allow changes" checkbox is unchecked
+    #[garde(skip)]
+    #[serde(default)]
+    pub scope: Scope,
+}
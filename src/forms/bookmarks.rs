@@ -1,5 +1,5 @@
 use garde::Validate;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{db::bookmarks::InsertBookmark, form_errors::FormErrors};
@@ -16,6 +16,8 @@ pub struct CreateBookmark {
     pub url: String,
     #[garde(custom(not_empty))]
     pub title: String,
+    #[garde(length(chars, max = 10000))]
+    pub notes: Option<String>,
     #[garde(length(max = 100))]
     pub list_search_term: Option<String>,
     #[garde(skip)]
@@ -36,10 +38,41 @@ impl TryFrom<CreateBookmark> for InsertBookmark {
         Ok(InsertBookmark {
             url: value.url,
             title: value.title,
+            notes: non_empty_notes(value.notes),
         })
     }
 }
 
+#[derive(Validate, Default, Serialize, Deserialize, Clone, Debug)]
+pub struct EditBookmark {
+    #[garde(url)]
+    pub url: String,
+    #[garde(custom(not_empty))]
+    pub title: String,
+    #[garde(length(chars, max = 10000))]
+    pub notes: Option<String>,
+}
+
+impl TryFrom<EditBookmark> for InsertBookmark {
+    type Error = FormErrors;
+
+    fn try_from(value: EditBookmark) -> Result<Self, Self::Error> {
+        value.validate()?;
+
+        Ok(InsertBookmark {
+            url: value.url,
+            title: value.title,
+            notes: non_empty_notes(value.notes),
+        })
+    }
+}
+
+/// Empty text areas are submitted as empty strings, but we don't want to store
+/// them.
+fn non_empty_notes(notes: Option<String>) -> Option<String> {
+    notes.filter(|notes| !notes.trim().is_empty())
+}
+
 #[expect(clippy::trivially_copy_pass_by_ref)]
 fn not_empty(value: &str, _: &()) -> garde::Result {
     if value.is_empty() {
@@ -0,0 +1,21 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Validate, Serialize, Deserialize, Debug)]
+pub struct CreateFeedSubscription {
+    #[garde(url)]
+    pub url: String,
+    /// Left out when the "archive" checkbox is unchecked
+    #[garde(skip)]
+    #[serde(default)]
+    pub archive: bool,
+}
+
+impl Default for CreateFeedSubscription {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            archive: true,
+        }
+    }
+}
@@ -1,5 +1,7 @@
 pub mod ap_users;
+pub mod api_tokens;
 pub mod bookmarks;
+pub mod feed_subscriptions;
 pub mod links;
 pub mod lists;
 pub mod users;
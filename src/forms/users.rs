@@ -48,3 +48,10 @@ pub struct CreateOidcUser {
     #[garde(pattern("^[a-zA-Z0-9_]+$"), length(min = 3, max = 50))]
     pub username: String,
 }
+
+#[derive(Serialize, Deserialize, Validate, Debug, Default)]
+pub struct LookupUser {
+    /// E.g. `rafael@ties.pub`, with or without a leading `@`
+    #[garde(length(min = 1, max = 255))]
+    pub handle: String,
+}
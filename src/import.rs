@@ -0,0 +1,437 @@
+//! Importing bookmarks exported from browsers and other bookmarking services.
+//!
+//! Each file is [`parse`]d, and then saved for a user with [`save`]. Imports
+//! are private: lists from bookmark files are created as private lists, and
+//! nothing is sent to followers of the user. Only bookmarks added to existing
+//! public lists are announced to the lists' followers.
+//!
+//! Folders, collections and tags all become lists linking to their bookmarks.
+//! ties has no read state, so services that track it put their unread
+//! bookmarks in the [`UNREAD_LIST`].
+
+mod backup;
+mod linkding;
+mod netscape;
+mod pinboard;
+mod pocket;
+mod raindrop;
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    db::{self, AppTx, bookmarks::InsertBookmark},
+    export::Backup,
+    forms::{links::CreateLink, lists::CreateList},
+    response_error::ResponseResult,
+};
+
+/// The file formats we can import.
+#[derive(Deserialize, Serialize, clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Bookmarks HTML file, as exported by browsers
+    #[default]
+    Netscape,
+    /// JSON export from Pinboard
+    Pinboard,
+    /// CSV or HTML export from Pocket
+    Pocket,
+    /// CSV export from Raindrop.io
+    Raindrop,
+    /// JSON from the bookmarks endpoint of the Linkding API
+    Linkding,
+    /// JSON backup exported by ties
+    Backup,
+}
+
+/// A parsed import file.
+#[derive(Debug)]
+pub enum Parsed {
+    Bookmarks(Import),
+    Backup(Box<Backup>),
+}
+
+/// Lists and bookmarks read from an export file, before they're saved.
+#[derive(Debug, Default)]
+pub struct Import {
+    pub lists: Vec<List>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// Imported bookmarks that haven't been read yet are linked from a list with
+/// this title.
+pub const UNREAD_LIST: &str = "Unread";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct List {
+    pub title: String,
+    /// Index of the list linking to this one in [`Import::lists`]. Parents
+    /// always come before their children.
+    pub parent: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Bookmark {
+    pub url: String,
+    pub title: String,
+    pub notes: Option<String>,
+    pub created_at: Option<OffsetDateTime>,
+    /// Indices of the lists linking to this bookmark in [`Import::lists`].
+    pub lists: Vec<usize>,
+}
+
+impl Import {
+    /// Index of the top-level list with this title, adding it if there's none
+    /// yet. Used for services with tags, where each tag becomes a list.
+    fn tag_list(&mut self, title: &str) -> usize {
+        if let Some(index) = self
+            .lists
+            .iter()
+            .position(|list| list.parent.is_none() && list.title == title)
+        {
+            return index;
+        }
+
+        self.lists.push(List {
+            title: title.to_string(),
+            parent: None,
+        });
+        self.lists.len() - 1
+    }
+
+    /// Add a bookmark to the lists for its tags, and to the [`UNREAD_LIST`]
+    /// if it's unread.
+    fn push_tagged<'a>(
+        &mut self,
+        mut bookmark: Bookmark,
+        tags: impl IntoIterator<Item = &'a str>,
+        unread: bool,
+    ) {
+        for tag in tags
+            .into_iter()
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+        {
+            let index = self.tag_list(tag);
+            if !bookmark.lists.contains(&index) {
+                bookmark.lists.push(index);
+            }
+        }
+        if unread {
+            let index = self.tag_list(UNREAD_LIST);
+            bookmark.lists.push(index);
+        }
+        if bookmark.title.trim().is_empty() {
+            bookmark.title.clone_from(&bookmark.url);
+        }
+
+        self.bookmarks.push(bookmark);
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{0}")]
+    InvalidFormat(String),
+    #[error(
+        "This backup was created by a newer version of ties (backup version {0}). Please update \
+         ties to import it."
+    )]
+    UnsupportedBackupVersion(u32),
+}
+
+/// What to do with imported bookmarks whose URL the user already bookmarked.
+#[derive(Deserialize, Serialize, clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Duplicates {
+    /// Leave the existing bookmark untouched.
+    #[default]
+    Skip,
+    /// Add the existing bookmark to the imported lists, and use the imported
+    /// notes if it has none.
+    Merge,
+}
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub bookmarks_created: usize,
+    pub bookmarks_merged: usize,
+    pub bookmarks_skipped: usize,
+    pub lists_created: usize,
+    /// Imported lists that had the same title as an existing list.
+    pub lists_reused: usize,
+    /// URLs that couldn't be imported.
+    pub invalid_urls: Vec<String>,
+    /// Accounts followed in a restored backup. Following them again needs to
+    /// be confirmed by them, so we can't restore follows automatically.
+    pub accounts_to_follow: Vec<Url>,
+    /// Pending archives for the new bookmarks.
+    pub archive_ids: Vec<Uuid>,
+    /// Bookmarks newly linked from public lists, as `(list_id, bookmark_id)`,
+    /// to announce to the lists' followers.
+    pub announcements: Vec<(Uuid, Uuid)>,
+    /// Bookmarks that became public by being linked from public lists, to
+    /// send to the followers of their author.
+    pub published: Vec<Uuid>,
+}
+
+pub fn parse(format: Format, input: &str) -> Result<Parsed, Error> {
+    match format {
+        Format::Netscape => netscape::parse(input).map(Parsed::Bookmarks),
+        Format::Pinboard => pinboard::parse(input).map(Parsed::Bookmarks),
+        Format::Pocket => pocket::parse(input).map(Parsed::Bookmarks),
+        Format::Raindrop => raindrop::parse(input).map(Parsed::Bookmarks),
+        Format::Linkding => linkding::parse(input).map(Parsed::Bookmarks),
+        Format::Backup => backup::parse(input).map(|backup| Parsed::Backup(Box::new(backup))),
+    }
+}
+
+/// Save a parsed file for a user, see [`save_bookmarks`] and
+/// [`backup::restore`].
+pub async fn save(
+    tx: &mut AppTx,
+    user_id: Uuid,
+    ap_user_id: Uuid,
+    base_url: &Url,
+    parsed: Parsed,
+    duplicates: Duplicates,
+) -> ResponseResult<Summary> {
+    match parsed {
+        Parsed::Bookmarks(import) => {
+            save_bookmarks(tx, user_id, ap_user_id, base_url, import, duplicates).await
+        }
+        Parsed::Backup(backup) => {
+            backup::restore(tx, user_id, ap_user_id, base_url, *backup, duplicates).await
+        }
+    }
+}
+
+/// Save imported lists and bookmarks for a user.
+///
+/// Lists are matched to existing lists by title. Bookmarks are matched to
+/// existing bookmarks by URL, and handled according to `duplicates`. A URL
+/// that appears multiple times in the import is saved as one bookmark in all
+/// of its lists.
+async fn save_bookmarks(
+    tx: &mut AppTx,
+    user_id: Uuid,
+    ap_user_id: Uuid,
+    base_url: &Url,
+    import: Import,
+    duplicates: Duplicates,
+) -> ResponseResult<Summary> {
+    let mut summary = Summary::default();
+
+    let mut list_ids = Vec::with_capacity(import.lists.len());
+    for list in import.lists {
+        let create_list = CreateList {
+            title: list.title,
+            content: None,
+            private: true,
+        };
+        let id =
+            find_or_create_list(tx, ap_user_id, create_list, None, false, &mut summary).await?;
+
+        if let Some(parent_id) = list.parent.and_then(|parent| list_ids.get(parent)) {
+            link_if_missing(tx, user_id, *parent_id, id, None).await?;
+        }
+        list_ids.push(id);
+    }
+    let public_list_ids = db::lists::list_by_id(tx, &list_ids)
+        .await?
+        .into_iter()
+        .filter(|list| !list.private)
+        .map(|list| list.id)
+        .collect::<HashSet<_>>();
+
+    // Bookmarks saved in this import by URL, so that duplicates within the
+    // import end up in a single bookmark.
+    let mut imported: HashMap<String, Uuid> = HashMap::new();
+    let mut previously_private = Vec::new();
+    for bookmark in import.bookmarks {
+        if !is_web_url(&bookmark.url) {
+            summary.invalid_urls.push(bookmark.url);
+            continue;
+        }
+
+        let lists = bookmark
+            .lists
+            .iter()
+            .filter_map(|index| list_ids.get(*index).copied());
+
+        let bookmark_id = if let Some(bookmark_id) = imported.get(&bookmark.url) {
+            *bookmark_id
+        } else {
+            let duplicate = handle_duplicate(
+                tx,
+                ap_user_id,
+                &bookmark.url,
+                bookmark.notes.as_deref(),
+                duplicates,
+                &mut summary,
+            )
+            .await?;
+            match duplicate {
+                Duplicate::Skipped => continue,
+                Duplicate::Merged(existing_id) => {
+                    if !db::bookmarks::is_public(tx, existing_id).await? {
+                        previously_private.push(existing_id);
+                    }
+                    existing_id
+                }
+                Duplicate::NotFound => {
+                    let created = db::bookmarks::insert_local_at(
+                        tx,
+                        ap_user_id,
+                        InsertBookmark {
+                            url: bookmark.url.clone(),
+                            title: bookmark.title,
+                            notes: bookmark.notes,
+                        },
+                        bookmark.created_at,
+                        base_url,
+                    )
+                    .await?;
+                    let archive = db::archives::insert_pending(tx, created.id).await?;
+                    summary.archive_ids.push(archive.id);
+                    summary.bookmarks_created += 1;
+                    previously_private.push(created.id);
+                    created.id
+                }
+            }
+        };
+        imported.insert(bookmark.url, bookmark_id);
+
+        for list_id in lists {
+            if link_if_missing(tx, user_id, list_id, bookmark_id, None).await?
+                && public_list_ids.contains(&list_id)
+            {
+                summary.announcements.push((list_id, bookmark_id));
+            }
+        }
+    }
+
+    for bookmark_id in previously_private {
+        if db::bookmarks::is_public(tx, bookmark_id).await? {
+            summary.published.push(bookmark_id);
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Parse the RFC 3339 dates used by most JSON and CSV exports.
+fn parse_date(value: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(value.trim(), &Rfc3339).ok()
+}
+
+/// Join the non-empty descriptions and notes of a bookmark into its notes.
+fn join_notes<'a>(parts: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let notes = parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    (!notes.is_empty()).then_some(notes)
+}
+
+fn is_web_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Reuse the user's list with the same title, or create a new one.
+async fn find_or_create_list(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    mut create_list: CreateList,
+    created_at: Option<OffsetDateTime>,
+    pinned: bool,
+    summary: &mut Summary,
+) -> ResponseResult<Uuid> {
+    create_list.title = create_list.title.chars().take(100).collect();
+    if let Some(existing) = db::lists::by_title(tx, ap_user_id, &create_list.title).await? {
+        summary.lists_reused += 1;
+        return Ok(existing.id);
+    }
+
+    let created = db::lists::insert_at(tx, ap_user_id, create_list, created_at).await?;
+    if pinned {
+        db::lists::set_pinned(tx, created.id, true).await?;
+    }
+    summary.lists_created += 1;
+
+    Ok(created.id)
+}
+
+enum Duplicate {
+    /// The user has no bookmark with this URL yet.
+    NotFound,
+    Skipped,
+    /// The imported bookmark was merged into the existing bookmark with this
+    /// id.
+    Merged(Uuid),
+}
+
+/// Look for a bookmark the user already saved with the same URL, and skip or
+/// merge the imported bookmark according to `duplicates`.
+async fn handle_duplicate(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    url: &str,
+    notes: Option<&str>,
+    duplicates: Duplicates,
+    summary: &mut Summary,
+) -> ResponseResult<Duplicate> {
+    let Some(existing) = db::bookmarks::by_url(tx, ap_user_id, url).await? else {
+        return Ok(Duplicate::NotFound);
+    };
+
+    match duplicates {
+        Duplicates::Skip => {
+            summary.bookmarks_skipped += 1;
+            Ok(Duplicate::Skipped)
+        }
+        Duplicates::Merge => {
+            if existing.notes.is_none()
+                && let Some(notes) = notes
+            {
+                db::bookmarks::update(
+                    tx,
+                    existing.id,
+                    InsertBookmark {
+                        url: existing.url,
+                        title: existing.title,
+                        notes: Some(notes.to_string()),
+                    },
+                )
+                .await?;
+            }
+            summary.bookmarks_merged += 1;
+            Ok(Duplicate::Merged(existing.id))
+        }
+    }
+}
+
+/// Returns whether the link was created.
+async fn link_if_missing(
+    tx: &mut AppTx,
+    user_id: Uuid,
+    src: Uuid,
+    dest: Uuid,
+    created_at: Option<OffsetDateTime>,
+) -> ResponseResult<bool> {
+    if db::links::exists(tx, src, dest).await? {
+        return Ok(false);
+    }
+    db::links::insert_at(tx, user_id, CreateLink { src, dest }, created_at).await?;
+
+    Ok(true)
+}
@@ -0,0 +1,140 @@
+//! Restoring JSON backups created by [`crate::export::backup`].
+
+use std::collections::HashMap;
+
+use url::Url;
+use uuid::Uuid;
+
+use super::{Duplicate, Duplicates, Error, Summary};
+use crate::{
+    db::{self, AppTx, bookmarks::InsertBookmark},
+    export::{BACKUP_VERSION, Backup},
+    forms::lists::CreateList,
+    response_error::ResponseResult,
+};
+
+pub fn parse(input: &str) -> Result<Backup, Error> {
+    // Check the version first, so that newer backups with a different format
+    // get a helpful error message.
+    #[derive(serde::Deserialize)]
+    struct Version {
+        version: u32,
+    }
+    let version: Version = serde_json::from_str(input).map_err(|_| {
+        Error::InvalidFormat("This doesn't look like a backup exported from ties.".to_string())
+    })?;
+    if version.version > BACKUP_VERSION {
+        return Err(Error::UnsupportedBackupVersion(version.version));
+    }
+
+    serde_json::from_str(input)
+        .map_err(|e| Error::InvalidFormat(format!("This backup could not be read: {e}")))
+}
+
+/// Restore a backup for a user.
+///
+/// Lists and bookmarks get new ids, and the links between them are restored
+/// using those new ids. Lists are matched to existing lists by title, and
+/// bookmarks are matched to existing bookmarks by URL like for other imports.
+/// Follows can't be restored, and are returned in
+/// [`Summary::accounts_to_follow`] instead.
+pub async fn restore(
+    tx: &mut AppTx,
+    user_id: Uuid,
+    ap_user_id: Uuid,
+    base_url: &Url,
+    backup: Backup,
+    duplicates: Duplicates,
+) -> ResponseResult<Summary> {
+    let mut summary = Summary::default();
+    // Ids from the backup mapped to ids of the restored lists and bookmarks
+    let mut ids: HashMap<Uuid, Uuid> = HashMap::new();
+
+    for list in backup.lists {
+        let create_list = CreateList {
+            title: list.title,
+            content: list.content,
+            private: list.private,
+        };
+        let id = super::find_or_create_list(
+            tx,
+            ap_user_id,
+            create_list,
+            Some(list.created_at),
+            list.pinned,
+            &mut summary,
+        )
+        .await?;
+        ids.insert(list.id, id);
+    }
+
+    for bookmark in backup.bookmarks {
+        if !super::is_web_url(&bookmark.url) {
+            summary.invalid_urls.push(bookmark.url);
+            continue;
+        }
+
+        let duplicate = super::handle_duplicate(
+            tx,
+            ap_user_id,
+            &bookmark.url,
+            bookmark.notes.as_deref(),
+            duplicates,
+            &mut summary,
+        )
+        .await?;
+        match duplicate {
+            Duplicate::Skipped => continue,
+            Duplicate::Merged(existing_id) => {
+                ids.insert(bookmark.id, existing_id);
+                continue;
+            }
+            Duplicate::NotFound => {}
+        }
+
+        let created = db::bookmarks::insert_local_at(
+            tx,
+            ap_user_id,
+            InsertBookmark {
+                url: bookmark.url,
+                title: bookmark.title,
+                notes: bookmark.notes,
+            },
+            Some(bookmark.created_at),
+            base_url,
+        )
+        .await?;
+        ids.insert(bookmark.id, created.id);
+        summary.bookmarks_created += 1;
+
+        if bookmark.archives.is_empty() {
+            let archive = db::archives::insert_pending(tx, created.id).await?;
+            summary.archive_ids.push(archive.id);
+        }
+        for archive in bookmark.archives {
+            let restored = db::archives::insert_restored(
+                tx,
+                created.id,
+                archive.created_at,
+                archive.status,
+                archive.error.as_ref(),
+                archive.extracted_html.as_deref(),
+            )
+            .await?;
+            if restored.status == db::archives::Status::Pending {
+                summary.archive_ids.push(restored.id);
+            }
+        }
+    }
+
+    for link in backup.links {
+        let (Some(src), Some(dest)) = (ids.get(&link.src), ids.get(&link.dest)) else {
+            continue;
+        };
+        super::link_if_missing(tx, user_id, *src, *dest, Some(link.created_at)).await?;
+    }
+
+    summary.accounts_to_follow = backup.following;
+
+    Ok(summary)
+}
@@ -0,0 +1,182 @@
+//! Bookmarks from the `/api/bookmarks/` endpoint of the Linkding REST API.
+//! Linkding's export is a bookmarks HTML file, which can be imported as such,
+//! but only the API response includes the read state of each bookmark:
+//!
+//! ```json
+//! {
+//!     "count": 1,
+//!     "next": null,
+//!     "results": [{
+//!         "url": "https://example.com",
+//!         "title": "Title",
+//!         "description": "Description",
+//!         "notes": "Notes",
+//!         "tag_names": ["rust", "async"],
+//!         "date_added": "2024-01-31T12:00:00.000000Z",
+//!         "unread": true
+//!     }]
+//! }
+//! ```
+//!
+//! Since results are paginated, we also accept a plain array with the
+//! combined results of all pages.
+
+use serde::Deserialize;
+
+use super::{Bookmark, Import};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Response {
+    Page { results: Vec<LinkdingBookmark> },
+    Bookmarks(Vec<LinkdingBookmark>),
+}
+
+#[derive(Deserialize)]
+struct LinkdingBookmark {
+    url: String,
+    #[serde(default)]
+    title: String,
+    /// Scraped title, used when the title is empty
+    #[serde(default)]
+    website_title: Option<String>,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    notes: String,
+    #[serde(default)]
+    tag_names: Vec<String>,
+    #[serde(default)]
+    date_added: String,
+    #[serde(default)]
+    unread: bool,
+}
+
+/// Read bookmarks from a Linkding API response. Tags become lists,
+/// descriptions and notes become bookmark notes.
+pub fn parse(input: &str) -> Result<Import, super::Error> {
+    let response: Response = serde_json::from_str(input).map_err(|_| {
+        super::Error::InvalidFormat(
+            "This doesn't look like a response from the Linkding API.".to_string(),
+        )
+    })?;
+    let (Response::Page { results: bookmarks } | Response::Bookmarks(bookmarks)) = response;
+
+    let mut import = Import::default();
+    for bookmark in bookmarks {
+        let title = if bookmark.title.is_empty() {
+            bookmark.website_title.unwrap_or_default()
+        } else {
+            bookmark.title
+        };
+        import.push_tagged(
+            Bookmark {
+                notes: super::join_notes([bookmark.description.as_str(), bookmark.notes.as_str()]),
+                created_at: super::parse_date(&bookmark.date_added),
+                url: bookmark.url,
+                title,
+                lists: Vec::new(),
+            },
+            bookmark.tag_names.iter().map(String::as_str),
+            bookmark.unread,
+        );
+    }
+
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use time::OffsetDateTime;
+
+    use super::*;
+    use crate::import::List;
+
+    #[test]
+    fn parses_api_response() -> Result<(), crate::import::Error> {
+        let import = parse(
+            r#"{
+                "count": 2,
+                "next": null,
+                "previous": null,
+                "results": [
+                    {
+                        "id": 1,
+                        "url": "https://tokio.rs/",
+                        "title": "",
+                        "description": "An async runtime",
+                        "notes": "Use this",
+                        "website_title": "Tokio",
+                        "website_description": "Build reliable network applications",
+                        "is_archived": false,
+                        "unread": true,
+                        "shared": false,
+                        "tag_names": ["rust", "async"],
+                        "date_added": "2023-11-14T22:13:20.000000Z",
+                        "date_modified": "2024-01-01T00:00:00.000000Z"
+                    },
+                    {
+                        "id": 2,
+                        "url": "https://doc.rust-lang.org/book/",
+                        "title": "The Rust Book",
+                        "tag_names": []
+                    }
+                ]
+            }"#,
+        )?;
+
+        assert_eq!(
+            import.lists,
+            vec![
+                List {
+                    title: "rust".to_string(),
+                    parent: None,
+                },
+                List {
+                    title: "async".to_string(),
+                    parent: None,
+                },
+                List {
+                    title: "Unread".to_string(),
+                    parent: None,
+                },
+            ]
+        );
+        assert_eq!(
+            import.bookmarks,
+            vec![
+                Bookmark {
+                    url: "https://tokio.rs/".to_string(),
+                    title: "Tokio".to_string(),
+                    notes: Some("An async runtime\n\nUse this".to_string()),
+                    created_at: OffsetDateTime::from_unix_timestamp(1_700_000_000).ok(),
+                    lists: vec![0, 1, 2],
+                },
+                Bookmark {
+                    url: "https://doc.rust-lang.org/book/".to_string(),
+                    title: "The Rust Book".to_string(),
+                    notes: None,
+                    created_at: None,
+                    lists: vec![],
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn accepts_arrays_of_bookmarks() -> Result<(), crate::import::Error> {
+        let import = parse(r#"[{"url": "https://tokio.rs/", "title": "Tokio"}]"#)?;
+        assert_eq!(import.bookmarks.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(parse(r#"{"count": 1}"#).is_err());
+        assert!(parse("url,title").is_err());
+    }
+}
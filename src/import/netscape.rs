@@ -0,0 +1,404 @@
+//! The Netscape bookmark file format, exported by all major browsers and
+//! many bookmark services. It's loosely structured HTML with unclosed tags:
+//!
+//! ```html
+//! <!DOCTYPE NETSCAPE-Bookmark-file-1>
+//! <DL><p>
+//!     <DT><H3 ADD_DATE="1700000000">Folder</H3>
+//!     <DL><p>
+//!         <DT><A HREF="https://example.com" ADD_DATE="1700000000">Title</A>
+//!         <DD>Description
+//!     </DL><p>
+//! </DL><p>
+//! ```
+//!
+//! Instead of building a DOM, we read the tags in order and keep track of the
+//! folder we're in.
+
+use time::OffsetDateTime;
+
+use super::{Bookmark, Import, List};
+
+/// Read bookmarks and folders from a Netscape bookmark file. Folders become
+/// lists, nested folders are linked from their parent folder's list.
+pub fn parse(input: &str) -> Result<Import, super::Error> {
+    let mut import = Import::default();
+    // The list of each `<DL>` we're currently inside of, `None` for the top
+    // level.
+    let mut folders: Vec<Option<usize>> = Vec::new();
+    // A folder title opens a new list, which the following `<DL>` belongs to.
+    let mut next_folder: Option<usize> = None;
+    let mut found_list = false;
+    // Descriptions follow the bookmark they belong to.
+    let mut last_bookmark: Option<usize> = None;
+
+    let mut tags = Tags { rest: input };
+    while let Some(tag) = tags.next() {
+        match (tag.name.as_str(), tag.closing) {
+            ("dl", false) => {
+                found_list = true;
+                folders.push(next_folder.take());
+            }
+            ("dl", true) => {
+                folders.pop();
+            }
+            ("h3", false) => {
+                let title = tags.text_until("h3");
+                import.lists.push(List {
+                    title: if title.is_empty() {
+                        "Untitled folder".to_string()
+                    } else {
+                        title
+                    },
+                    parent: folders.last().copied().flatten(),
+                });
+                next_folder = Some(import.lists.len() - 1);
+                last_bookmark = None;
+            }
+            ("a", false) => {
+                let title = tags.text_until("a");
+                let Some(url) = tag.attribute("href") else {
+                    continue;
+                };
+                import.bookmarks.push(Bookmark {
+                    title: if title.is_empty() { url.clone() } else { title },
+                    url,
+                    notes: None,
+                    created_at: tag
+                        .attribute("add_date")
+                        .as_deref()
+                        .and_then(parse_timestamp),
+                    lists: folders.last().copied().flatten().into_iter().collect(),
+                });
+                last_bookmark = Some(import.bookmarks.len() - 1);
+            }
+            ("dd", false) => {
+                let description = tags.text_until_tag();
+                if let Some(bookmark) = last_bookmark.and_then(|i| import.bookmarks.get_mut(i))
+                    && !description.is_empty()
+                {
+                    bookmark.notes = Some(description);
+                }
+                last_bookmark = None;
+            }
+            _ => {}
+        }
+    }
+
+    if !found_list {
+        return Err(super::Error::InvalidFormat(
+            "This doesn't look like a bookmarks HTML file.".to_string(),
+        ));
+    }
+
+    Ok(import)
+}
+
+/// Dates are stored as seconds since the unix epoch, but some exporters use
+/// milliseconds or microseconds.
+pub(super) fn parse_timestamp(value: &str) -> Option<OffsetDateTime> {
+    let mut timestamp: i64 = value.trim().parse().ok()?;
+    while timestamp > 100_000_000_000 {
+        timestamp /= 1000;
+    }
+    if timestamp <= 0 {
+        return None;
+    }
+
+    OffsetDateTime::from_unix_timestamp(timestamp).ok()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct Tag {
+    /// Lowercase tag name
+    pub(super) name: String,
+    pub(super) closing: bool,
+    /// Lowercase attribute names and their decoded values
+    attributes: Vec<(String, String)>,
+}
+
+impl Tag {
+    /// Stands in for comments, which can't contain any relevant tags.
+    fn ignored() -> Self {
+        Tag {
+            name: String::new(),
+            closing: false,
+            attributes: Vec::new(),
+        }
+    }
+
+    pub(super) fn attribute(&self, name: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    }
+}
+
+/// Iterates over the tags of an html document, skipping comments and the text
+/// between tags.
+pub(super) struct Tags<'a> {
+    pub(super) rest: &'a str,
+}
+
+impl Tags<'_> {
+    /// Read the text up to the closing tag with the given name.
+    pub(super) fn text_until(&mut self, name: &str) -> String {
+        let mut text = String::new();
+        loop {
+            let (before, tag) = self.next_with_text();
+            text.push_str(before);
+            match tag {
+                Some(tag) if tag.name == name && tag.closing => break,
+                Some(_) => {}
+                None => break,
+            }
+        }
+        decode_entities(text.trim())
+    }
+
+    /// Read the text up to the next tag, keeping line breaks.
+    fn text_until_tag(&mut self) -> String {
+        let end = self.rest.find('<').unwrap_or(self.rest.len());
+        let (text, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        decode_entities(text.trim())
+    }
+
+    /// Return the next tag and the text preceding it.
+    fn next_with_text(&mut self) -> (&str, Option<Tag>) {
+        let rest = self.rest;
+        let Some(start) = rest.find('<') else {
+            self.rest = "";
+            return (rest, None);
+        };
+        let text = &rest[..start];
+        let after = &rest[start + 1..];
+
+        if let Some(comment) = after.strip_prefix("!--") {
+            let end = comment.find("-->").map_or(comment.len(), |end| end + 3);
+            self.rest = &comment[end..];
+            return (text, Some(Tag::ignored()));
+        }
+
+        let (tag, rest) = parse_tag(after);
+        self.rest = rest;
+        (text, Some(tag))
+    }
+}
+
+impl Iterator for Tags<'_> {
+    type Item = Tag;
+
+    fn next(&mut self) -> Option<Tag> {
+        self.next_with_text().1
+    }
+}
+
+/// Parse a tag, starting after its `<`. Returns the tag and the input after
+/// its `>`.
+fn parse_tag(input: &str) -> (Tag, &str) {
+    let mut chars = input.char_indices().peekable();
+    let closing = chars.next_if(|(_, c)| *c == '/').is_some();
+
+    let mut name = String::new();
+    while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '!') {
+        name.push(c.to_ascii_lowercase());
+    }
+
+    let mut attributes = Vec::new();
+    loop {
+        while chars
+            .next_if(|(_, c)| c.is_whitespace() || *c == '/')
+            .is_some()
+        {}
+        let Some(&(index, c)) = chars.peek() else {
+            return (
+                Tag {
+                    name,
+                    closing,
+                    attributes,
+                },
+                "",
+            );
+        };
+        if c == '>' {
+            return (
+                Tag {
+                    name,
+                    closing,
+                    attributes,
+                },
+                &input[index + 1..],
+            );
+        }
+
+        let mut key = String::new();
+        while let Some((_, c)) =
+            chars.next_if(|(_, c)| !c.is_whitespace() && !matches!(c, '=' | '>' | '/'))
+        {
+            key.push(c.to_ascii_lowercase());
+        }
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        let mut value = String::new();
+        if chars.next_if(|(_, c)| *c == '=').is_some() {
+            while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+            match chars.next_if(|(_, c)| matches!(c, '"' | '\'')) {
+                Some((_, quote)) => {
+                    for (_, c) in chars.by_ref() {
+                        if c == quote {
+                            break;
+                        }
+                        value.push(c);
+                    }
+                }
+                None => {
+                    while let Some((_, c)) = chars.next_if(|(_, c)| !c.is_whitespace() && *c != '>')
+                    {
+                        value.push(c);
+                    }
+                }
+            }
+        }
+        attributes.push((key, decode_entities(&value)));
+    }
+}
+
+/// Decode the html entities that appear in exported titles and urls.
+fn decode_entities(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            }?;
+            Some((c, end))
+        });
+
+        if let Some((c, end)) = decoded {
+            output.push(c);
+            rest = &rest[end + 1..];
+        } else {
+            output.push('&');
+            rest = &rest[1..];
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const FIREFOX_EXPORT: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<!-- This is an automatically generated file.
+     It will be read and overwritten.
+     DO NOT EDIT! -->
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<meta http-equiv="Content-Security-Policy"
+      content="default-src 'self'; script-src 'none'; img-src data: *; object-src 'none'"></meta>
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks Menu</H1>
+
+<DL><p>
+    <DT><A HREF="https://top.example.com/" ADD_DATE="1700000000" LAST_MODIFIED="1700000001">Top &amp; level</A>
+    <DT><H3 ADD_DATE="1600000000" LAST_MODIFIED="1600000001">Rust</H3>
+    <DL><p>
+        <DT><A HREF="https://doc.rust-lang.org/book/" ADD_DATE="1650000000000" ICON_URI="https://doc.rust-lang.org/favicon.ico">The Rust Book</A>
+        <DD>Read chapter &lt;4&gt; again
+        <DT><H3>Async</H3>
+        <DL><p>
+            <DT><A HREF="https://tokio.rs" TAGS="rust,async"></A>
+        </DL><p>
+    </DL><p>
+    <DT><A HREF="https://after.example.com">After the folder</A>
+</DL>
+"#;
+
+    #[test]
+    fn parses_folders_and_bookmarks() -> Result<(), crate::import::Error> {
+        let import = parse(FIREFOX_EXPORT)?;
+
+        assert_eq!(
+            import.lists,
+            vec![
+                List {
+                    title: "Rust".to_string(),
+                    parent: None,
+                },
+                List {
+                    title: "Async".to_string(),
+                    parent: Some(0),
+                },
+            ]
+        );
+        assert_eq!(
+            import.bookmarks,
+            vec![
+                Bookmark {
+                    url: "https://top.example.com/".to_string(),
+                    title: "Top & level".to_string(),
+                    notes: None,
+                    created_at: OffsetDateTime::from_unix_timestamp(1_700_000_000).ok(),
+                    lists: vec![],
+                },
+                Bookmark {
+                    url: "https://doc.rust-lang.org/book/".to_string(),
+                    title: "The Rust Book".to_string(),
+                    notes: Some("Read chapter <4> again".to_string()),
+                    created_at: OffsetDateTime::from_unix_timestamp(1_650_000_000).ok(),
+                    lists: vec![0],
+                },
+                Bookmark {
+                    url: "https://tokio.rs".to_string(),
+                    title: "https://tokio.rs".to_string(),
+                    notes: None,
+                    created_at: None,
+                    lists: vec![1],
+                },
+                Bookmark {
+                    url: "https://after.example.com".to_string(),
+                    title: "After the folder".to_string(),
+                    notes: None,
+                    created_at: None,
+                    lists: vec![],
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(parse("<html><body><p>Hello</p></body></html>").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn decodes_entities() {
+        assert_eq!(
+            decode_entities("a &amp; b &#39;c&#x27; &unknown; & d"),
+            "a & b 'c' &unknown; & d"
+        );
+    }
+}
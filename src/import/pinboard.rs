@@ -0,0 +1,138 @@
+//! The JSON export from Pinboard's settings page, which is an array of posts
+//! like this:
+//!
+//! ```json
+//! [{
+//!     "href": "https://example.com",
+//!     "description": "Title",
+//!     "extended": "Description",
+//!     "time": "2024-01-31T12:00:00Z",
+//!     "toread": "yes",
+//!     "tags": "rust async"
+//! }]
+//! ```
+
+use serde::Deserialize;
+
+use super::{Bookmark, Import};
+
+#[derive(Deserialize)]
+struct Post {
+    href: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    extended: String,
+    #[serde(default)]
+    time: String,
+    #[serde(default)]
+    toread: String,
+    /// Space-separated
+    #[serde(default)]
+    tags: String,
+}
+
+/// Read bookmarks from a Pinboard JSON export. Tags become lists.
+pub fn parse(input: &str) -> Result<Import, super::Error> {
+    let posts: Vec<Post> = serde_json::from_str(input).map_err(|_| {
+        super::Error::InvalidFormat("This doesn't look like a Pinboard JSON export.".to_string())
+    })?;
+
+    let mut import = Import::default();
+    for post in posts {
+        import.push_tagged(
+            Bookmark {
+                notes: super::join_notes([post.extended.as_str()]),
+                created_at: super::parse_date(&post.time),
+                url: post.href,
+                title: post.description,
+                lists: Vec::new(),
+            },
+            post.tags.split_whitespace(),
+            post.toread == "yes",
+        );
+    }
+
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use time::OffsetDateTime;
+
+    use super::*;
+    use crate::import::List;
+
+    #[test]
+    fn parses_posts() -> Result<(), crate::import::Error> {
+        let import = parse(
+            r#"[
+                {
+                    "href": "https://tokio.rs/",
+                    "description": "Tokio",
+                    "extended": "An async runtime",
+                    "meta": "0123",
+                    "hash": "4567",
+                    "time": "2023-11-14T22:13:20Z",
+                    "shared": "no",
+                    "toread": "yes",
+                    "tags": "rust async"
+                },
+                {
+                    "href": "https://doc.rust-lang.org/book/",
+                    "description": "",
+                    "extended": "",
+                    "time": "not a date",
+                    "toread": "no",
+                    "tags": "rust"
+                }
+            ]"#,
+        )?;
+
+        assert_eq!(
+            import.lists,
+            vec![
+                List {
+                    title: "rust".to_string(),
+                    parent: None,
+                },
+                List {
+                    title: "async".to_string(),
+                    parent: None,
+                },
+                List {
+                    title: "Unread".to_string(),
+                    parent: None,
+                },
+            ]
+        );
+        assert_eq!(
+            import.bookmarks,
+            vec![
+                Bookmark {
+                    url: "https://tokio.rs/".to_string(),
+                    title: "Tokio".to_string(),
+                    notes: Some("An async runtime".to_string()),
+                    created_at: OffsetDateTime::from_unix_timestamp(1_700_000_000).ok(),
+                    lists: vec![0, 1, 2],
+                },
+                Bookmark {
+                    url: "https://doc.rust-lang.org/book/".to_string(),
+                    title: "https://doc.rust-lang.org/book/".to_string(),
+                    notes: None,
+                    created_at: None,
+                    lists: vec![0],
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(parse("<!DOCTYPE NETSCAPE-Bookmark-file-1>").is_err());
+        assert!(parse(r#"{"href": "https://example.com"}"#).is_err());
+    }
+}
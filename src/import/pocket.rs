@@ -0,0 +1,226 @@
+//! Pocket exports. Newer exports are CSV files with these columns:
+//!
+//! ```csv
+//! title,url,time_added,tags,status
+//! Title,https://example.com,1700000000,rust|async,unread
+//! ```
+//!
+//! Older exports are HTML files with one section for unread and one for
+//! archived links:
+//!
+//! ```html
+//! <h1>Unread</h1>
+//! <ul>
+//!     <li><a href="https://example.com" time_added="1700000000" tags="rust,async">Title</a></li>
+//! </ul>
+//! <h1>Read Archive</h1>
+//! <ul>...</ul>
+//! ```
+
+use serde::Deserialize;
+
+use super::{
+    Bookmark, Import,
+    netscape::{self, Tags},
+};
+
+/// Read bookmarks from a Pocket CSV or HTML export. Tags become lists.
+pub fn parse(input: &str) -> Result<Import, super::Error> {
+    if input.trim_start().starts_with('<') {
+        parse_html(input)
+    } else {
+        parse_csv(input)
+    }
+}
+
+#[derive(Deserialize)]
+struct Row {
+    #[serde(default)]
+    title: String,
+    url: String,
+    #[serde(default)]
+    time_added: String,
+    /// Separated by `|`
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    status: String,
+}
+
+fn parse_csv(input: &str) -> Result<Import, super::Error> {
+    let mut reader = csv::Reader::from_reader(input.as_bytes());
+    let headers = reader.headers().map_err(|_| invalid_format())?;
+    if !headers.iter().any(|header| header == "url") {
+        return Err(invalid_format());
+    }
+
+    let mut import = Import::default();
+    for row in reader.deserialize() {
+        let row: Row = row.map_err(|_| invalid_format())?;
+        import.push_tagged(
+            Bookmark {
+                url: row.url,
+                title: row.title,
+                notes: None,
+                created_at: netscape::parse_timestamp(&row.time_added),
+                lists: Vec::new(),
+            },
+            row.tags.split('|'),
+            row.status == "unread",
+        );
+    }
+
+    Ok(import)
+}
+
+fn parse_html(input: &str) -> Result<Import, super::Error> {
+    let mut import = Import::default();
+    let mut found_list = false;
+    let mut unread = true;
+
+    let mut tags = Tags { rest: input };
+    while let Some(tag) = tags.next() {
+        match (tag.name.as_str(), tag.closing) {
+            ("h1", false) => {
+                unread = tags.text_until("h1") != "Read Archive";
+            }
+            ("ul", false) => found_list = true,
+            ("a", false) => {
+                let title = tags.text_until("a");
+                let Some(url) = tag.attribute("href") else {
+                    continue;
+                };
+                let pocket_tags = tag.attribute("tags").unwrap_or_default();
+                import.push_tagged(
+                    Bookmark {
+                        url,
+                        title,
+                        notes: None,
+                        created_at: tag
+                            .attribute("time_added")
+                            .as_deref()
+                            .and_then(netscape::parse_timestamp),
+                        lists: Vec::new(),
+                    },
+                    pocket_tags.split(','),
+                    unread,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    if !found_list {
+        return Err(invalid_format());
+    }
+
+    Ok(import)
+}
+
+fn invalid_format() -> super::Error {
+    super::Error::InvalidFormat("This doesn't look like a Pocket export.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use time::OffsetDateTime;
+
+    use super::*;
+    use crate::import::List;
+
+    #[test]
+    fn parses_csv() -> Result<(), crate::import::Error> {
+        let import = parse(
+            r#"title,url,time_added,tags,status
+Tokio,https://tokio.rs/,1700000000,rust|async,unread
+"Rust, the book",https://doc.rust-lang.org/book/,1600000000,,archive
+"#,
+        )?;
+
+        assert_eq!(
+            import.lists,
+            vec![
+                List {
+                    title: "rust".to_string(),
+                    parent: None,
+                },
+                List {
+                    title: "async".to_string(),
+                    parent: None,
+                },
+                List {
+                    title: "Unread".to_string(),
+                    parent: None,
+                },
+            ]
+        );
+        assert_eq!(
+            import.bookmarks,
+            vec![
+                Bookmark {
+                    url: "https://tokio.rs/".to_string(),
+                    title: "Tokio".to_string(),
+                    notes: None,
+                    created_at: OffsetDateTime::from_unix_timestamp(1_700_000_000).ok(),
+                    lists: vec![0, 1, 2],
+                },
+                Bookmark {
+                    url: "https://doc.rust-lang.org/book/".to_string(),
+                    title: "Rust, the book".to_string(),
+                    notes: None,
+                    created_at: OffsetDateTime::from_unix_timestamp(1_600_000_000).ok(),
+                    lists: vec![],
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parses_html() -> Result<(), crate::import::Error> {
+        let import = parse(
+            r#"<!DOCTYPE html>
+<html>
+    <head><title>Pocket Export</title></head>
+    <body>
+        <h1>Unread</h1>
+        <ul>
+            <li><a href="https://tokio.rs/" time_added="1700000000" tags="rust,async">Tokio</a></li>
+        </ul>
+
+        <h1>Read Archive</h1>
+        <ul>
+            <li><a href="https://doc.rust-lang.org/book/" time_added="1600000000" tags="rust">The Rust Book</a></li>
+        </ul>
+    </body>
+</html>"#,
+        )?;
+
+        assert_eq!(
+            import
+                .lists
+                .iter()
+                .map(|list| list.title.as_str())
+                .collect::<Vec<_>>(),
+            vec!["rust", "async", "Unread"]
+        );
+        assert_eq!(
+            import
+                .bookmarks
+                .iter()
+                .map(|bookmark| (bookmark.title.as_str(), bookmark.lists.clone()))
+                .collect::<Vec<_>>(),
+            vec![("Tokio", vec![0, 1, 2]), ("The Rust Book", vec![0])]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(parse("<html><body><p>Hello</p></body></html>").is_err());
+        assert!(parse("just some text").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! The CSV export from Raindrop.io, with these columns:
+//!
+//! ```csv
+//! id,title,note,excerpt,url,folder,tags,created,cover,highlights,favorite
+//! 1,Title,Note,Excerpt,https://example.com,Collection,"rust, async",2024-01-31T12:00:00.000Z,,,false
+//! ```
+
+use serde::Deserialize;
+
+use super::{Bookmark, Import};
+
+#[derive(Deserialize)]
+struct Row {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    note: String,
+    #[serde(default)]
+    excerpt: String,
+    url: String,
+    /// Title of the collection, or "Unsorted"
+    #[serde(default)]
+    folder: String,
+    /// Separated by `,`
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    created: String,
+}
+
+/// Read bookmarks from a Raindrop.io CSV export. Collections and tags become
+/// lists, notes and excerpts become bookmark notes.
+pub fn parse(input: &str) -> Result<Import, super::Error> {
+    let mut reader = csv::Reader::from_reader(input.as_bytes());
+    let headers = reader.headers().map_err(|_| invalid_format())?;
+    if !headers.iter().any(|header| header == "url") {
+        return Err(invalid_format());
+    }
+
+    let mut import = Import::default();
+    for row in reader.deserialize() {
+        let row: Row = row.map_err(|_| invalid_format())?;
+        let folder = Some(row.folder.as_str()).filter(|folder| *folder != "Unsorted");
+        import.push_tagged(
+            Bookmark {
+                notes: super::join_notes([row.note.as_str(), row.excerpt.as_str()]),
+                created_at: super::parse_date(&row.created),
+                url: row.url,
+                title: row.title,
+                lists: Vec::new(),
+            },
+            folder.into_iter().chain(row.tags.split(',')),
+            false,
+        );
+    }
+
+    Ok(import)
+}
+
+fn invalid_format() -> super::Error {
+    super::Error::InvalidFormat("This doesn't look like a Raindrop.io CSV export.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use time::OffsetDateTime;
+
+    use super::*;
+    use crate::import::List;
+
+    #[test]
+    fn parses_rows() -> Result<(), crate::import::Error> {
+        let import = parse(
+            r#"id,title,note,excerpt,url,folder,tags,created,cover,highlights,favorite
+1,Tokio,Use this,An async runtime,https://tokio.rs/,Rust,"async, rust",2023-11-14T22:13:20.000Z,,,false
+2,The Rust Book,,,https://doc.rust-lang.org/book/,Unsorted,,2020-09-13T12:26:40.000Z,,,true
+"#,
+        )?;
+
+        assert_eq!(
+            import.lists,
+            vec![
+                List {
+                    title: "Rust".to_string(),
+                    parent: None,
+                },
+                List {
+                    title: "async".to_string(),
+                    parent: None,
+                },
+                List {
+                    title: "rust".to_string(),
+                    parent: None,
+                },
+            ]
+        );
+        assert_eq!(
+            import.bookmarks,
+            vec![
+                Bookmark {
+                    url: "https://tokio.rs/".to_string(),
+                    title: "Tokio".to_string(),
+                    notes: Some("Use this\n\nAn async runtime".to_string()),
+                    created_at: OffsetDateTime::from_unix_timestamp(1_700_000_000).ok(),
+                    lists: vec![0, 1, 2],
+                },
+                Bookmark {
+                    url: "https://doc.rust-lang.org/book/".to_string(),
+                    title: "The Rust Book".to_string(),
+                    notes: None,
+                    created_at: OffsetDateTime::from_unix_timestamp(1_600_000_000).ok(),
+                    lists: vec![],
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(parse("title,link\nTokio,https://tokio.rs").is_err());
+        assert!(parse("").is_err());
+    }
+}
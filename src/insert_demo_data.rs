@@ -114,9 +114,11 @@ async fn create_bookmarks(
         let title: String = fake::faker::lorem::en::Words(1..5)
             .fake::<Vec<_>>()
             .join(" ");
+        let notes: Option<Vec<_>> = fake::faker::lorem::en::Paragraphs(1..2).fake();
         let insert_bookmark = InsertBookmark {
             url: format!("https://{word}.{tld}"),
             title,
+            notes: notes.map(|n| n.join("\n\n")),
         };
 
         let bookmark =
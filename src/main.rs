@@ -4,6 +4,7 @@
 #![expect(clippy::missing_errors_doc)]
 #![expect(clippy::redundant_closure_for_method_calls)]
 
+mod api_error;
 mod archive;
 mod authentication;
 pub mod cli;
@@ -18,10 +19,15 @@ pub mod server;
 mod views;
 
 mod date_time;
+mod export;
 mod federation;
+mod feed_subscriptions;
+mod feeds;
 mod htmf_response;
+mod import;
 #[cfg(debug_assertions)]
 mod insert_demo_data;
+mod markdown;
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,32 @@
+use pulldown_cmark::{Options, Parser};
+
+/// Render user-written markdown, like bookmark notes, to sanitized HTML.
+pub fn to_html(markdown: &str) -> String {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH);
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+
+    ammonia::clean(&html)
+}
+
+#[cfg(test)]
+mod test {
+    use super::to_html;
+
+    #[test]
+    fn renders_markdown() {
+        assert_eq!(
+            to_html("Read *this* [later](https://example.com)"),
+            "<p>Read <em>this</em> <a href=\"https://example.com\" rel=\"noopener \
+             noreferrer\">later</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn removes_scripts() {
+        assert_eq!(
+            to_html("<script>alert(1)</script>\n\n<b onclick=\"alert(1)\">bold</b>"),
+            "\n<p><b>bold</b></p>\n"
+        );
+    }
+}
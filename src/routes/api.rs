@@ -0,0 +1,97 @@
+//! The JSON API under `/api/v1`, for scripts and browser extensions.
+//!
+//! Requests are authenticated with API tokens that users create on their
+//! settings page, see [`crate::authentication::ApiUser`]. Tokens with the read
+//! scope can only be used for `GET` requests.
+//!
+//! Clients written for Pinboard can use the compatible endpoints under
+//! `/api/pinboard/v1` instead, see [`pinboard`].
+
+mod bookmarks;
+mod links;
+mod lists;
+mod pinboard;
+mod search;
+
+use axum::Router;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{db, server::AppState};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .nest(
+            "/api/v1",
+            Router::new()
+                .merge(bookmarks::router())
+                .merge(lists::router())
+                .merge(links::router())
+                .merge(search::router()),
+        )
+        .nest("/api/pinboard/v1", pinboard::router())
+}
+
+#[derive(Serialize)]
+struct Bookmark {
+    id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    created_at: OffsetDateTime,
+    url: String,
+    title: String,
+    notes: Option<String>,
+}
+
+impl From<db::Bookmark> for Bookmark {
+    fn from(bookmark: db::Bookmark) -> Self {
+        Self {
+            id: bookmark.id,
+            created_at: bookmark.created_at,
+            url: bookmark.url,
+            title: bookmark.title,
+            notes: bookmark.notes,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct List {
+    id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    created_at: OffsetDateTime,
+    title: String,
+    content: Option<String>,
+    private: bool,
+    pinned: bool,
+}
+
+impl From<db::List> for List {
+    fn from(list: db::List) -> Self {
+        Self {
+            id: list.id,
+            created_at: list.created_at,
+            title: list.title,
+            content: list.content,
+            private: list.private,
+            pinned: list.pinned,
+        }
+    }
+}
+
+/// A bookmark or list, with a `type` field to tell them apart.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Item {
+    Bookmark(Bookmark),
+    List(List),
+}
+
+impl From<db::LinkDestination> for Item {
+    fn from(item: db::LinkDestination) -> Self {
+        match item {
+            db::LinkDestination::Bookmark(bookmark) => Item::Bookmark(bookmark.into()),
+            db::LinkDestination::List(list) => Item::List(list.into()),
+        }
+    }
+}
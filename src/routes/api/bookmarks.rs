@@ -0,0 +1,174 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Bookmark, List};
+use crate::{
+    api_error::{ApiError, ApiResult},
+    authentication::ApiUser,
+    db::{self, bookmarks::InsertBookmark},
+    extract::{self},
+    federation,
+    forms::{bookmarks::EditBookmark, links::CreateLink},
+    server::AppState,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/bookmarks", get(get_bookmarks).post(post_bookmark))
+        .route(
+            "/bookmarks/{id}",
+            get(get_bookmark).put(put_bookmark).delete(delete_bookmark),
+        )
+}
+
+#[derive(Serialize)]
+struct BookmarkWithLists {
+    #[serde(flatten)]
+    bookmark: Bookmark,
+    /// Lists linking to this bookmark that the user can see
+    lists: Vec<List>,
+}
+
+#[derive(Deserialize)]
+struct CreateBookmark {
+    #[serde(flatten)]
+    bookmark: EditBookmark,
+    /// Ids of the user's lists to add the bookmark to
+    #[serde(default)]
+    lists: Vec<Uuid>,
+}
+
+async fn get_bookmarks(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+) -> ApiResult<Json<Vec<Bookmark>>> {
+    let bookmarks = db::bookmarks::list_by_ap_user_id(&mut tx, api_user.ap_user_id).await?;
+
+    Ok(Json(bookmarks.into_iter().map(Bookmark::from).collect()))
+}
+
+async fn post_bookmark(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+    State(state): State<AppState>,
+    federation_data: federation::Data,
+    Json(input): Json<CreateBookmark>,
+) -> ApiResult<(StatusCode, Json<Bookmark>)> {
+    api_user.require_write()?;
+    let insert_bookmark = InsertBookmark::try_from(input.bookmark)?;
+
+    let mut lists = Vec::with_capacity(input.lists.len());
+    for list_id in &input.lists {
+        let list = db::lists::by_id(&mut tx, *list_id).await?;
+        if list.ap_user_id != api_user.ap_user_id {
+            return Err(ApiError::NotFound);
+        }
+        lists.push(list);
+    }
+
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx,
+        api_user.ap_user_id,
+        insert_bookmark,
+        &state.base_url,
+    )
+    .await?;
+
+    for list in &lists {
+        db::links::insert(
+            &mut tx,
+            api_user.user_id,
+            CreateLink {
+                src: list.id,
+                dest: bookmark.id,
+            },
+        )
+        .await?;
+    }
+
+    if db::bookmarks::is_public(&mut tx, bookmark.id).await? {
+        federation::CreateBookmark::send_to_followers(
+            &db::ap_users::read_by_id(&mut tx, api_user.ap_user_id).await?,
+            bookmark.clone(),
+            &federation_data,
+        )
+        .await?;
+    }
+    for list in &lists {
+        federation::AnnounceBookmark::send_to_followers(list, bookmark.clone(), &federation_data)
+            .await?;
+    }
+
+    db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    tx.commit().await?;
+
+    state.archive_queue.wake_worker();
+
+    Ok((StatusCode::CREATED, Json(bookmark.into())))
+}
+
+async fn get_bookmark(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<BookmarkWithLists>> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+
+    if bookmark.ap_user_id != api_user.ap_user_id {
+        return Err(ApiError::NotFound);
+    }
+
+    let lists =
+        db::lists::pointing_to_bookmark(&mut tx, bookmark.id, Some(api_user.ap_user_id)).await?;
+
+    Ok(Json(BookmarkWithLists {
+        bookmark: bookmark.into(),
+        lists: lists.into_iter().map(List::from).collect(),
+    }))
+}
+
+async fn put_bookmark(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+    State(state): State<AppState>,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+    Json(input): Json<EditBookmark>,
+) -> ApiResult<Json<Bookmark>> {
+    api_user.require_write()?;
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+
+    if bookmark.ap_user_id != api_user.ap_user_id {
+        return Err(ApiError::NotFound);
+    }
+
+    let updated = crate::routes::bookmarks::update_bookmark(
+        tx,
+        &state,
+        &federation_data,
+        bookmark,
+        InsertBookmark::try_from(input)?,
+    )
+    .await?;
+
+    Ok(Json(updated.into()))
+}
+
+async fn delete_bookmark(
+    extract::Tx(tx): extract::Tx,
+    api_user: ApiUser,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    api_user.require_write()?;
+    crate::routes::bookmarks::delete_owned_bookmark(tx, api_user.ap_user_id, &federation_data, id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
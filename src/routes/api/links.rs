@@ -0,0 +1,110 @@
+use axum::{
+    Json, Router,
+    extract::Path,
+    http::StatusCode,
+    routing::{delete, post},
+};
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    api_error::{ApiError, ApiResult},
+    authentication::ApiUser,
+    db::{self, LinkDestination},
+    extract::{self},
+    federation,
+    forms::links::CreateLink,
+    server::AppState,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/links", post(post_link))
+        .route("/links/{id}", delete(delete_link))
+}
+
+#[derive(Serialize)]
+struct Link {
+    id: Uuid,
+    #[serde(with = "time::serde::iso8601")]
+    created_at: OffsetDateTime,
+    /// The list this link starts at
+    src: Uuid,
+    /// The bookmark or list this link points to
+    dest: Uuid,
+}
+
+/// Link one of the user's lists to a bookmark or list.
+async fn post_link(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+    federation_data: federation::Data,
+    Json(input): Json<CreateLink>,
+) -> ApiResult<(StatusCode, Json<Link>)> {
+    api_user.require_write()?;
+
+    let src = db::lists::by_id(&mut tx, input.src).await?;
+    if src.ap_user_id != api_user.ap_user_id {
+        return Err(ApiError::NotFound);
+    }
+
+    let dest = db::items::by_id(&mut tx, input.dest).await?;
+    let visible = match &dest {
+        LinkDestination::Bookmark(bookmark) => {
+            bookmark.ap_user_id == api_user.ap_user_id
+                || db::bookmarks::is_public(&mut tx, bookmark.id).await?
+        }
+        LinkDestination::List(list) => !list.private || list.ap_user_id == api_user.ap_user_id,
+    };
+    if !visible {
+        return Err(ApiError::NotFound);
+    }
+    if let LinkDestination::List(list) = &dest
+        && list.private
+        && !src.private
+    {
+        return Err(ApiError::Invalid(
+            "Can't link from a public list to a private list".to_string(),
+        ));
+    }
+
+    let src = LinkDestination::List(src);
+    let link =
+        crate::routes::links::insert_link(tx, api_user.user_id, &federation_data, &src, &dest)
+            .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(Link {
+            id: link.id,
+            created_at: link.created_at,
+            src: src.id(),
+            dest: dest.id(),
+        }),
+    ))
+}
+
+/// Remove a link starting at one of the user's lists. The linked bookmark or
+/// list is kept.
+async fn delete_link(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    api_user.require_write()?;
+
+    let link = db::links::by_id(&mut tx, id).await?;
+    let src = match link.src_list_id {
+        Some(src) => db::lists::by_id(&mut tx, src).await?,
+        None => return Err(ApiError::NotFound),
+    };
+    if src.ap_user_id != api_user.ap_user_id {
+        return Err(ApiError::NotFound);
+    }
+
+    crate::routes::links::delete_link(tx, &federation_data, id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,156 @@
+use axum::{Json, Router, extract::Path, http::StatusCode, routing::get};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Item, List};
+use crate::{
+    api_error::{ApiError, ApiResult},
+    authentication::ApiUser,
+    db::{self, LinkDestination, LinkDestinationWithChildren},
+    extract::{self},
+    federation,
+    forms::lists::CreateList,
+    server::AppState,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/lists", get(get_lists).post(post_list))
+        .route(
+            "/lists/{id}",
+            get(get_list).patch(patch_list).delete(delete_list),
+        )
+}
+
+#[derive(Serialize)]
+struct ListWithItems {
+    #[serde(flatten)]
+    list: List,
+    /// Bookmarks and lists this list links to, newest first
+    items: Vec<LinkedItem>,
+}
+
+#[derive(Serialize)]
+struct LinkedItem {
+    /// Id of the link, for removing the item from the list
+    link_id: Uuid,
+    #[serde(flatten)]
+    item: Item,
+}
+
+/// Changes to a list. Fields that are left out stay unchanged.
+#[derive(Validate, Deserialize)]
+struct EditList {
+    #[garde(length(min = 1, max = 100))]
+    title: Option<String>,
+    #[garde(skip)]
+    private: Option<bool>,
+    #[garde(skip)]
+    pinned: Option<bool>,
+}
+
+async fn get_lists(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+) -> ApiResult<Json<Vec<List>>> {
+    let lists = db::lists::list_by_ap_user_id(&mut tx, api_user.ap_user_id).await?;
+
+    Ok(Json(lists.into_iter().map(List::from).collect()))
+}
+
+async fn post_list(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+    Json(input): Json<CreateList>,
+) -> ApiResult<(StatusCode, Json<List>)> {
+    api_user.require_write()?;
+    input.validate()?;
+
+    let list = db::lists::insert(&mut tx, api_user.ap_user_id, input).await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(list.into())))
+}
+
+async fn get_list(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ListWithItems>> {
+    let list = db::lists::by_id(&mut tx, id).await?;
+
+    if list.private && list.ap_user_id != api_user.ap_user_id {
+        return Err(ApiError::NotFound);
+    }
+
+    let links = db::links::list_by_list(&mut tx, id, Some(api_user.ap_user_id)).await?;
+    let items = links
+        .into_iter()
+        .map(|link| LinkedItem {
+            link_id: link.id,
+            item: match link.dest {
+                LinkDestinationWithChildren::Bookmark(bookmark) => {
+                    LinkDestination::Bookmark(bookmark)
+                }
+                LinkDestinationWithChildren::List(list) => LinkDestination::List(list.list),
+            }
+            .into(),
+        })
+        .collect();
+
+    Ok(Json(ListWithItems {
+        list: list.into(),
+        items,
+    }))
+}
+
+async fn patch_list(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+    Json(input): Json<EditList>,
+) -> ApiResult<Json<List>> {
+    api_user.require_write()?;
+    input.validate()?;
+    let mut list = db::lists::by_id(&mut tx, id).await?;
+
+    if list.ap_user_id != api_user.ap_user_id {
+        return Err(ApiError::NotFound);
+    }
+
+    if let Some(title) = input.title {
+        db::lists::edit_title(&mut tx, id, title.clone()).await?;
+        list.title = title;
+    }
+    if let Some(pinned) = input.pinned {
+        list = db::lists::set_pinned(&mut tx, id, pinned).await?;
+    }
+    match input.private {
+        Some(private) => {
+            list = crate::routes::lists::set_private(tx, &federation_data, &list, private).await?;
+        }
+        None => tx.commit().await?,
+    }
+
+    Ok(Json(list.into()))
+}
+
+async fn delete_list(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    api_user.require_write()?;
+    let list = db::lists::by_id(&mut tx, id).await?;
+
+    if list.ap_user_id != api_user.ap_user_id {
+        return Err(ApiError::NotFound);
+    }
+
+    crate::routes::lists::delete_list(tx, &federation_data, &list).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,619 @@
+//! A subset of the [Pinboard v1 API](https://pinboard.in/api/) under
+//! `/api/pinboard/v1`, so existing Pinboard apps and scripts can be used with
+//! ties.
+//!
+//! Clients send an API token as the `auth_token` parameter, either on its own
+//! or as `username:token` like Pinboard does. Tags are the titles of the user's
+//! lists, with spaces replaced by underscores, and bookmarks that are "to
+//! read" are in the [`import::UNREAD_LIST`]. Lists created for new tags are
+//! private, so the `shared` parameter is ignored. Only JSON responses are
+//! supported, so requests need to ask for them with `format=json`.
+
+use std::collections::BTreeMap;
+
+use axum::{
+    Json, Router,
+    extract::{FromRequestParts, State},
+    http::request::Parts,
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use serde_qs::web::QsQuery;
+use sha2::{Digest, Sha256};
+use time::{
+    Date, OffsetDateTime, UtcOffset,
+    format_description::{self, well_known::Rfc3339},
+};
+
+use crate::{
+    api_error::{ApiError, ApiResult},
+    authentication::ApiUser,
+    db::{self, AppTx, bookmarks::InsertBookmark},
+    extract::{self},
+    federation,
+    forms::{bookmarks::EditBookmark, links::CreateLink},
+    import,
+    response_error::{ResponseError, ResponseResult},
+    routes::bookmarks::VisibilityChange,
+    server::AppState,
+};
+
+/// Pinboard's default for `posts/recent`
+const DEFAULT_RECENT_COUNT: u32 = 15;
+const MAX_RECENT_COUNT: u32 = 100;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/posts/update", get(get_update))
+        .route("/posts/add", get(get_add))
+        .route("/posts/delete", get(get_delete))
+        .route("/posts/get", get(get_posts))
+        .route("/posts/recent", get(get_recent))
+        .route("/posts/all", get(get_all))
+        .route("/tags/get", get(get_tags))
+}
+
+/// A user authenticated with the `auth_token` query parameter, for a request
+/// that asks for JSON.
+struct PinboardUser(ApiUser);
+
+/// The query parameters shared by all requests.
+#[derive(Deserialize)]
+struct CommonParams {
+    auth_token: Option<String>,
+    format: Option<String>,
+}
+
+impl FromRequestParts<AppState> for PinboardUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        req: &mut Parts,
+        state: &AppState,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let params = serde_qs::from_str::<CommonParams>(req.uri.query().unwrap_or_default()).ok();
+        let auth_token = params
+            .as_ref()
+            .and_then(|params| params.auth_token.as_deref())
+            .ok_or(ApiError::NotAuthenticated)?;
+        // Pinboard tokens look like `username:token`
+        let token = auth_token
+            .rsplit_once(':')
+            .map_or(auth_token, |(_username, token)| token);
+        let api_user = ApiUser::from_token(state, token).await?;
+
+        // Pinboard responds with XML by default, which we don't support
+        if params.and_then(|params| params.format).as_deref() != Some("json") {
+            return Err(ApiError::Invalid(
+                "Only JSON responses are supported, add format=json to the request".to_string(),
+            ));
+        }
+
+        Ok(Self(api_user))
+    }
+}
+
+/// Pinboard's response for requests that change something.
+#[derive(Serialize)]
+struct ResultCode {
+    result_code: &'static str,
+}
+
+impl ResultCode {
+    fn done() -> Json<Self> {
+        Json(Self {
+            result_code: "done",
+        })
+    }
+}
+
+/// A bookmark as Pinboard clients expect it.
+#[derive(Serialize)]
+struct Post {
+    href: String,
+    description: String,
+    extended: String,
+    /// Changes whenever the bookmark changes
+    meta: String,
+    /// Pinboard uses an MD5 hash of the URL here, but clients only need
+    /// something unique with the same format.
+    hash: String,
+    time: String,
+    shared: &'static str,
+    toread: &'static str,
+    tags: String,
+}
+
+#[derive(Serialize)]
+struct Posts {
+    date: String,
+    user: String,
+    posts: Vec<Post>,
+}
+
+/// A bookmark with the lists linking to it.
+struct Entry {
+    bookmark: db::Bookmark,
+    tags: Vec<String>,
+    shared: bool,
+    toread: bool,
+}
+
+impl From<db::pinboard::TaggedBookmark> for Entry {
+    fn from(tagged: db::pinboard::TaggedBookmark) -> Self {
+        Entry {
+            tags: tagged
+                .list_titles
+                .iter()
+                .filter(|title| *title != import::UNREAD_LIST)
+                .map(|title| tag(title))
+                .collect(),
+            shared: tagged.in_public_list,
+            toread: tagged
+                .list_titles
+                .iter()
+                .any(|title| title == import::UNREAD_LIST),
+            bookmark: tagged.bookmark,
+        }
+    }
+}
+
+impl Entry {
+    fn post(&self) -> Post {
+        let tags = self.tags.join(" ");
+        let notes = self.bookmark.notes.clone().unwrap_or_default();
+        let meta = Sha256::digest(format!("{}\n{notes}\n{tags}", self.bookmark.title));
+
+        Post {
+            href: self.bookmark.url.clone(),
+            description: self.bookmark.title.clone(),
+            extended: notes,
+            meta: format!("{meta:x}")[..32].to_string(),
+            hash: self.bookmark.id.simple().to_string(),
+            time: format_time(self.bookmark.created_at),
+            shared: yes_no(self.shared),
+            toread: yes_no(self.toread),
+            tags,
+        }
+    }
+}
+
+/// Load the user's bookmarks matching `filter` with their tags, newest first.
+async fn entries(
+    tx: &mut AppTx,
+    ap_user_id: uuid::Uuid,
+    filter: &db::pinboard::Filter,
+) -> ResponseResult<Vec<Entry>> {
+    Ok(db::pinboard::bookmarks(tx, ap_user_id, filter)
+        .await?
+        .into_iter()
+        .map(Entry::from)
+        .collect())
+}
+
+async fn username(tx: &mut AppTx, ap_user_id: uuid::Uuid) -> ResponseResult<String> {
+    let user = db::users::by_ap_user_id(tx, ap_user_id)
+        .await?
+        .ok_or(ResponseError::NotFound)?;
+
+    Ok(user.username)
+}
+
+#[derive(Serialize)]
+struct UpdateTime {
+    update_time: String,
+}
+
+/// Clients check this to decide whether to download all bookmarks again.
+async fn get_update(
+    extract::Tx(mut tx): extract::Tx,
+    PinboardUser(api_user): PinboardUser,
+) -> ApiResult<Json<UpdateTime>> {
+    let last_update = db::pinboard::last_update(&mut tx, api_user.ap_user_id)
+        .await?
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+
+    Ok(Json(UpdateTime {
+        update_time: format_time(last_update),
+    }))
+}
+
+#[derive(Deserialize)]
+struct AddPost {
+    url: String,
+    /// The title of the bookmark
+    description: String,
+    /// The notes of the bookmark
+    extended: Option<String>,
+    tags: Option<String>,
+    dt: Option<String>,
+    replace: Option<String>,
+    toread: Option<String>,
+}
+
+async fn get_add(
+    extract::Tx(mut tx): extract::Tx,
+    PinboardUser(api_user): PinboardUser,
+    State(state): State<AppState>,
+    federation_data: federation::Data,
+    QsQuery(input): QsQuery<AddPost>,
+) -> ApiResult<Json<ResultCode>> {
+    api_user.require_write()?;
+
+    let existing = db::bookmarks::by_url(&mut tx, api_user.ap_user_id, &input.url).await?;
+    if existing.is_some() && input.replace.as_deref() == Some("no") {
+        return Ok(Json(ResultCode {
+            result_code: "item already exists",
+        }));
+    }
+
+    let created_at = input.dt.as_deref().map(parse_time).transpose()?;
+    let insert_bookmark = InsertBookmark::try_from(EditBookmark {
+        url: input.url,
+        title: input.description,
+        notes: input.extended,
+    })?;
+
+    let mut tags = split_tags(input.tags.as_deref().unwrap_or_default());
+    if input.toread.as_deref() == Some("yes") {
+        tags.push(import::UNREAD_LIST.to_string());
+    }
+
+    if let Some(existing) = existing {
+        let was_public = db::bookmarks::is_public(&mut tx, existing.id).await?;
+        let new_public_lists = set_tags(&mut tx, &api_user, &existing, &tags).await?;
+        // Updating a public bookmark already sends it to remote instances
+        // that don't know it yet
+        let unpublished = VisibilityChange::detect(&mut tx, existing.clone(), was_public)
+            .await?
+            .filter(|change| !change.is_public);
+        let updated = crate::routes::bookmarks::update_bookmark(
+            tx,
+            &state,
+            &federation_data,
+            existing,
+            insert_bookmark,
+        )
+        .await?;
+        if let Some(unpublished) = unpublished {
+            unpublished.send(&federation_data).await?;
+        }
+        announce(&new_public_lists, &updated, &federation_data).await?;
+
+        return Ok(ResultCode::done());
+    }
+
+    let bookmark = db::bookmarks::insert_local_at(
+        &mut tx,
+        api_user.ap_user_id,
+        insert_bookmark,
+        created_at,
+        &state.base_url,
+    )
+    .await?;
+    let new_public_lists = set_tags(&mut tx, &api_user, &bookmark, &tags).await?;
+
+    db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    let ap_user = if db::bookmarks::is_public(&mut tx, bookmark.id).await? {
+        Some(db::ap_users::read_by_id(&mut tx, api_user.ap_user_id).await?)
+    } else {
+        None
+    };
+    tx.commit().await?;
+
+    state.archive_queue.wake_worker();
+    if let Some(ap_user) = ap_user {
+        federation::CreateBookmark::send_to_followers(&ap_user, bookmark.clone(), &federation_data)
+            .await?;
+    }
+    announce(&new_public_lists, &bookmark, &federation_data).await?;
+
+    Ok(ResultCode::done())
+}
+
+/// Link the bookmark from the lists for `tags`, creating private lists for
+/// new tags, and remove it from other lists that were created for tags.
+/// Returns the public lists that now link to the bookmark, to [`announce`] it
+/// once committed.
+async fn set_tags(
+    tx: &mut AppTx,
+    api_user: &ApiUser,
+    bookmark: &db::Bookmark,
+    tags: &[String],
+) -> ResponseResult<Vec<db::List>> {
+    let mut lists = db::lists::list_by_ap_user_id(tx, api_user.ap_user_id).await?;
+    let mut list_ids = Vec::new();
+    for tag_name in tags {
+        let existing = lists
+            .iter()
+            .find(|list| tag(&list.title).to_lowercase() == tag_name.to_lowercase());
+        let list_id = match existing {
+            Some(list) => list.id,
+            None => {
+                let list = db::lists::insert_tag(tx, api_user.ap_user_id, tag_name).await?;
+                let id = list.id;
+                lists.push(list);
+                id
+            }
+        };
+        if !list_ids.contains(&list_id) {
+            list_ids.push(list_id);
+        }
+    }
+
+    let links = db::links::list_by_ap_user_id(tx, api_user.ap_user_id).await?;
+    let mut linked_list_ids = Vec::new();
+    for link in links
+        .into_iter()
+        .filter(|link| link.dest_bookmark_id == Some(bookmark.id))
+    {
+        let Some(src) = link.src_list_id else {
+            continue;
+        };
+        if list_ids.contains(&src) {
+            linked_list_ids.push(src);
+        } else if lists
+            .iter()
+            .any(|list| list.id == src && list.created_as_tag)
+        {
+            // Lists that weren't created for tags are organized in ties, so
+            // the bookmark stays in them
+            db::links::delete_by_id(tx, link.id).await?;
+        }
+    }
+
+    let mut new_public_lists = Vec::new();
+    for list_id in list_ids {
+        if !linked_list_ids.contains(&list_id) {
+            db::links::insert(
+                tx,
+                api_user.user_id,
+                CreateLink {
+                    src: list_id,
+                    dest: bookmark.id,
+                },
+            )
+            .await?;
+            new_public_lists.extend(
+                lists
+                    .iter()
+                    .find(|list| list.id == list_id && !list.private)
+                    .cloned(),
+            );
+        }
+    }
+
+    Ok(new_public_lists)
+}
+
+/// Announce a bookmark to the followers of lists it was added to.
+async fn announce(
+    lists: &[db::List],
+    bookmark: &db::Bookmark,
+    federation_data: &federation::Data,
+) -> ResponseResult<()> {
+    for list in lists {
+        federation::AnnounceBookmark::send_to_followers(list, bookmark.clone(), federation_data)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct DeletePost {
+    url: String,
+}
+
+async fn get_delete(
+    extract::Tx(mut tx): extract::Tx,
+    PinboardUser(api_user): PinboardUser,
+    federation_data: federation::Data,
+    QsQuery(input): QsQuery<DeletePost>,
+) -> ApiResult<Json<ResultCode>> {
+    api_user.require_write()?;
+
+    let Some(bookmark) = db::bookmarks::by_url(&mut tx, api_user.ap_user_id, &input.url).await?
+    else {
+        return Ok(Json(ResultCode {
+            result_code: "item not found",
+        }));
+    };
+
+    crate::routes::bookmarks::delete_owned_bookmark(
+        tx,
+        api_user.ap_user_id,
+        &federation_data,
+        bookmark.id,
+    )
+    .await?;
+
+    Ok(ResultCode::done())
+}
+
+#[derive(Deserialize)]
+struct GetPosts {
+    tag: Option<String>,
+    /// Only bookmarks saved on this day, like `2025-01-31`
+    dt: Option<String>,
+    url: Option<String>,
+}
+
+/// Bookmarks saved on a single day, or the bookmark for a URL. Without
+/// filters, this returns the bookmarks of the most recent day with any.
+async fn get_posts(
+    extract::Tx(mut tx): extract::Tx,
+    PinboardUser(api_user): PinboardUser,
+    QsQuery(input): QsQuery<GetPosts>,
+) -> ApiResult<Json<Posts>> {
+    let tags = filter_tags(input.tag.as_deref());
+    let date = match &input.dt {
+        Some(dt) => Some(parse_date(dt)?),
+        None if input.url.is_none() => {
+            let newest = db::pinboard::Filter {
+                tags: tags.clone(),
+                limit: Some(1),
+                ..Default::default()
+            };
+            entries(&mut tx, api_user.ap_user_id, &newest)
+                .await?
+                .first()
+                .map(|entry| utc(entry).date())
+        }
+        None => None,
+    };
+    let filter = db::pinboard::Filter {
+        tags,
+        url: input.url,
+        date,
+        ..Default::default()
+    };
+    let posts = entries(&mut tx, api_user.ap_user_id, &filter)
+        .await?
+        .iter()
+        .map(Entry::post)
+        .collect();
+
+    Ok(Json(Posts {
+        date: format_time(OffsetDateTime::now_utc()),
+        user: username(&mut tx, api_user.ap_user_id).await?,
+        posts,
+    }))
+}
+
+#[derive(Deserialize)]
+struct RecentPosts {
+    tag: Option<String>,
+    count: Option<u32>,
+}
+
+async fn get_recent(
+    extract::Tx(mut tx): extract::Tx,
+    PinboardUser(api_user): PinboardUser,
+    QsQuery(input): QsQuery<RecentPosts>,
+) -> ApiResult<Json<Posts>> {
+    let count = input
+        .count
+        .unwrap_or(DEFAULT_RECENT_COUNT)
+        .min(MAX_RECENT_COUNT);
+    let filter = db::pinboard::Filter {
+        tags: filter_tags(input.tag.as_deref()),
+        limit: Some(i64::from(count)),
+        ..Default::default()
+    };
+    let posts = entries(&mut tx, api_user.ap_user_id, &filter)
+        .await?
+        .iter()
+        .map(Entry::post)
+        .collect();
+
+    Ok(Json(Posts {
+        date: format_time(OffsetDateTime::now_utc()),
+        user: username(&mut tx, api_user.ap_user_id).await?,
+        posts,
+    }))
+}
+
+#[derive(Deserialize)]
+struct AllPosts {
+    tag: Option<String>,
+    /// Number of bookmarks to skip
+    start: Option<u32>,
+    /// Maximum number of bookmarks to return
+    results: Option<u32>,
+    fromdt: Option<String>,
+    todt: Option<String>,
+}
+
+async fn get_all(
+    extract::Tx(mut tx): extract::Tx,
+    PinboardUser(api_user): PinboardUser,
+    QsQuery(input): QsQuery<AllPosts>,
+) -> ApiResult<Json<Vec<Post>>> {
+    let filter = db::pinboard::Filter {
+        tags: filter_tags(input.tag.as_deref()),
+        from: input.fromdt.as_deref().map(parse_time).transpose()?,
+        to: input.todt.as_deref().map(parse_time).transpose()?,
+        offset: i64::from(input.start.unwrap_or_default()),
+        limit: input.results.map(i64::from),
+        ..Default::default()
+    };
+    let posts = entries(&mut tx, api_user.ap_user_id, &filter)
+        .await?
+        .iter()
+        .map(Entry::post)
+        .collect();
+
+    Ok(Json(posts))
+}
+
+/// All tags with the number of bookmarks for each.
+async fn get_tags(
+    extract::Tx(mut tx): extract::Tx,
+    PinboardUser(api_user): PinboardUser,
+) -> ApiResult<Json<BTreeMap<String, i64>>> {
+    let mut counts = BTreeMap::new();
+    for (title, count) in
+        db::pinboard::list_titles_with_counts(&mut tx, api_user.ap_user_id).await?
+    {
+        if title != import::UNREAD_LIST {
+            *counts.entry(tag(&title)).or_default() += count;
+        }
+    }
+
+    Ok(Json(counts))
+}
+
+/// Pinboard tags can't contain spaces.
+fn tag(list_title: &str) -> String {
+    list_title.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+/// Pinboard clients separate tags with spaces or commas.
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split([' ', ','])
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Tags to filter bookmarks by, see [`db::pinboard::Filter::tags`].
+fn filter_tags(tags: Option<&str>) -> Vec<String> {
+    let mut tags: Vec<_> = split_tags(tags.unwrap_or_default())
+        .iter()
+        .map(|tag| tag.to_lowercase())
+        .collect();
+    tags.sort();
+    tags.dedup();
+
+    tags
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+fn utc(entry: &Entry) -> OffsetDateTime {
+    entry.bookmark.created_at.to_offset(UtcOffset::UTC)
+}
+
+fn format_time(time: OffsetDateTime) -> String {
+    time.to_offset(UtcOffset::UTC)
+        .replace_nanosecond(0)
+        .unwrap_or(time)
+        .format(&Rfc3339)
+        .unwrap_or_default()
+}
+
+fn parse_time(value: &str) -> ApiResult<OffsetDateTime> {
+    OffsetDateTime::parse(value, &Rfc3339)
+        .map_err(|_| ApiError::Invalid(format!("\"{value}\" is not a valid time")))
+}
+
+fn parse_date(value: &str) -> ApiResult<Date> {
+    format_description::parse("[year]-[month]-[day]")
+        .ok()
+        .and_then(|format| Date::parse(value, &format).ok())
+        .ok_or_else(|| ApiError::Invalid(format!("\"{value}\" is not a valid date")))
+}
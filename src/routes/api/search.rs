@@ -0,0 +1,72 @@
+use axum::{Json, Router, routing::get};
+use serde::Serialize;
+use serde_qs::web::QsQuery;
+use uuid::Uuid;
+
+use super::{Item, List};
+use crate::{
+    api_error::{ApiError, ApiResult},
+    authentication::ApiUser,
+    db::{self, search},
+    extract::{self},
+    routes::search::SearchQuery,
+    server::AppState,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/search", get(get_search))
+}
+
+#[derive(Serialize)]
+struct SearchResults {
+    items: Vec<SearchResult>,
+    total_count: i64,
+    /// Pass this as `after_id` to get the next page
+    next_page_after_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+struct SearchResult {
+    #[serde(flatten)]
+    item: Item,
+    /// Matching passage of the notes, archived page or list description
+    snippet: Option<String>,
+    /// The user's lists linking to this item
+    lists: Vec<List>,
+}
+
+/// Search the user's bookmarks and lists, using the same query syntax as the
+/// search page.
+async fn get_search(
+    extract::Tx(mut tx): extract::Tx,
+    api_user: ApiUser,
+    QsQuery(query): QsQuery<SearchQuery>,
+) -> ApiResult<Json<SearchResults>> {
+    let parsed = query
+        .parse()
+        .map_err(|error| ApiError::Invalid(error.to_string()))?;
+    let results = db::search::search(
+        &mut tx,
+        &parsed,
+        api_user.ap_user_id,
+        query.kind,
+        query.after_id,
+    )
+    .await?;
+
+    Ok(Json(SearchResults {
+        items: results
+            .items
+            .into_iter()
+            .map(|result| SearchResult {
+                item: result.item.into(),
+                snippet: result.snippet.map(|snippet| {
+                    snippet.replace([search::HIGHLIGHT_START, search::HIGHLIGHT_END], "")
+                }),
+                lists: result.lists.into_iter().map(List::from).collect(),
+            })
+            .collect(),
+        total_count: results.total_count,
+        next_page_after_id: results.next_page_after_id,
+    }))
+}
@@ -2,7 +2,7 @@ use anyhow::Context;
 use axum::{
     Router,
     extract::{Path, State},
-    http::HeaderMap,
+    http::{HeaderMap, header},
     response::{IntoResponse, Redirect, Response},
     routing::{delete, get, post},
 };
@@ -11,12 +11,17 @@ use serde_qs::web::{QsForm, QsQuery};
 use uuid::Uuid;
 
 use crate::{
+    archive,
     authentication::AuthUser,
-    db::{self, bookmarks::InsertBookmark},
+    db::{self, AppTx, bookmarks::InsertBookmark},
     extract::{self},
     federation,
     form_errors::FormErrors,
-    forms::{bookmarks::CreateBookmark, links::CreateLink, lists::CreateList},
+    forms::{
+        bookmarks::{CreateBookmark, EditBookmark},
+        links::CreateLink,
+        lists::CreateList,
+    },
     htmf_response::HtmfResponse,
     response_error::{ResponseError, ResponseResult},
     server::AppState,
@@ -28,7 +33,12 @@ pub fn router() -> Router<AppState> {
         .route("/bookmarks/create", get(get_create).post(post_create))
         .route("/bookmarks/unsorted", get(get_unsorted))
         .route("/bookmarks/{id}", delete(delete_by_id).get(get_by_id))
+        .route("/bookmarks/{id}/edit", get(get_edit).post(post_edit))
+        .route("/bookmarks/{id}/delete", get(get_delete).post(post_delete))
         .route("/bookmarks/{id}/archive", post(post_archive))
+        .route("/bookmarks/{id}/archives/{archive_id}", get(get_archive))
+        .route("/bookmarks/{id}/diff", get(get_diff))
+        .route("/archive_resources/{hash}", get(get_archive_resource))
 }
 
 async fn post_create(
@@ -72,7 +82,7 @@ async fn post_create(
     )
     .await?;
 
-    let mut first_created_parent = Option::None;
+    let mut created_parents = Vec::new();
     for parent_title in input.create_parents {
         let parent = db::lists::insert(
             &mut tx,
@@ -94,9 +104,7 @@ async fn post_create(
         )
         .await?;
 
-        if first_created_parent.is_none() {
-            first_created_parent.replace(parent);
-        }
+        created_parents.push(parent);
     }
 
     for parent in input.parents {
@@ -119,13 +127,17 @@ async fn post_create(
         )
         .await?;
     }
+    for parent in selected_parents.iter().chain(&created_parents) {
+        federation::AnnounceBookmark::send_to_followers(parent, bookmark.clone(), &federation_data)
+            .await?;
+    }
 
-    let archive = db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    db::archives::insert_pending(&mut tx, bookmark.id).await?;
     tx.commit().await?;
 
-    state.archive_queue.archive_in_background(archive.id);
+    state.archive_queue.wake_worker();
 
-    let redirect_dest = match selected_parents.first().or(first_created_parent.as_ref()) {
+    let redirect_dest = match selected_parents.first().or(created_parents.first()) {
         Some(parent) => parent.path(),
         None => "/bookmarks/unsorted".to_string(),
     };
@@ -169,21 +181,38 @@ async fn get_create(
 }
 
 async fn get_by_id(
-    extract::Tx(mut tx): extract::Tx,
+    extract::Tx(tx): extract::Tx,
     auth_user: AuthUser,
     Path(id): Path<Uuid>,
 ) -> ResponseResult<HtmfResponse> {
-    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+    show(tx, &auth_user, id, None).await
+}
 
-    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+/// Show an older snapshot of the bookmark's archive.
+async fn get_archive(
+    extract::Tx(tx): extract::Tx,
+    auth_user: AuthUser,
+    Path((id, archive_id)): Path<(Uuid, Uuid)>,
+) -> ResponseResult<HtmfResponse> {
+    show(tx, &auth_user, id, Some(archive_id)).await
+}
 
-    if !db::bookmarks::is_public(&mut tx, bookmark.id).await?
-        && bookmark.ap_user_id != auth_user.ap_user_id
-    {
-        return Err(ResponseError::NotFound);
-    }
+/// Show a bookmark along with the given snapshot, or its newest one.
+async fn show(
+    mut tx: AppTx,
+    auth_user: &AuthUser,
+    id: Uuid,
+    archive_id: Option<Uuid>,
+) -> ResponseResult<HtmfResponse> {
+    let layout = layout::Template::from_db(&mut tx, Some(auth_user)).await?;
+
+    let bookmark = readable_bookmark(&mut tx, auth_user, id).await?;
 
-    let archive = db::archives::by_bookmark_id(&mut tx, bookmark.id).await?;
+    let archive = match archive_id {
+        Some(archive_id) => Some(archive_of_bookmark(&mut tx, &bookmark, archive_id).await?),
+        None => db::archives::by_bookmark_id(&mut tx, bookmark.id).await?,
+    };
+    let snapshots = db::archives::list_snapshots(&mut tx, bookmark.id).await?;
     let backlinks = db::lists::pointing_to_bookmark(
         &mut tx,
         id,
@@ -199,12 +228,210 @@ async fn get_by_id(
             layout,
             bookmark,
             archive,
+            snapshots,
             backlinks,
             username,
         },
     )))
 }
 
+#[derive(Deserialize)]
+struct DiffQuery {
+    from: Uuid,
+    to: Uuid,
+}
+
+/// Compare the text of two snapshots of the bookmark's archive.
+async fn get_diff(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    QsQuery(query): QsQuery<DiffQuery>,
+) -> ResponseResult<HtmfResponse> {
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+
+    let bookmark = readable_bookmark(&mut tx, &auth_user, id).await?;
+    let from = archive_of_bookmark(&mut tx, &bookmark, query.from).await?;
+    let to = archive_of_bookmark(&mut tx, &bookmark, query.to).await?;
+
+    let lines = archive::diff(
+        from.extracted_html.as_deref().unwrap_or_default(),
+        to.extracted_html.as_deref().unwrap_or_default(),
+    );
+
+    Ok(HtmfResponse(views::archive_diff::view(
+        views::archive_diff::Data {
+            layout,
+            bookmark,
+            from,
+            to,
+            lines,
+        },
+    )))
+}
+
+/// Serve an image of an archived page that the user can read. Resources are
+/// addressed by the hash of their contents, so they never change and can be
+/// cached forever.
+async fn get_archive_resource(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: Option<AuthUser>,
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> ResponseResult<Response> {
+    let ap_user_id = auth_user.map(|auth_user| auth_user.ap_user_id);
+    if !db::archive_resources::is_readable(&mut tx, &hash, ap_user_id).await? {
+        return Err(ResponseError::NotFound);
+    }
+    let (content_type, data) = state.archive_resource_store.load(&mut tx, &hash).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (
+                header::CACHE_CONTROL,
+                // Private bookmarks can use the resource, too
+                "private, max-age=31536000, immutable".to_string(),
+            ),
+            // Images like SVGs can contain scripts, which must not run on our domain
+            (
+                header::CONTENT_SECURITY_POLICY,
+                "default-src 'none'; style-src 'unsafe-inline'; sandbox".to_string(),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+/// Find a bookmark that's either public or belongs to the user.
+async fn readable_bookmark(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    id: Uuid,
+) -> ResponseResult<db::Bookmark> {
+    let bookmark = db::bookmarks::by_id(tx, id).await?;
+
+    if !db::bookmarks::is_public(tx, bookmark.id).await?
+        && bookmark.ap_user_id != auth_user.ap_user_id
+    {
+        return Err(ResponseError::NotFound);
+    }
+
+    Ok(bookmark)
+}
+
+async fn archive_of_bookmark(
+    tx: &mut AppTx,
+    bookmark: &db::Bookmark,
+    archive_id: Uuid,
+) -> ResponseResult<db::Archive> {
+    let archive = db::archives::by_id(tx, archive_id).await?;
+    if archive.bookmark_id != bookmark.id {
+        return Err(ResponseError::NotFound);
+    }
+
+    Ok(archive)
+}
+
+async fn get_edit(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<HtmfResponse> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+
+    if bookmark.ap_user_id != auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+
+    Ok(HtmfResponse(views::edit_bookmark::view(
+        views::edit_bookmark::Data {
+            layout,
+            errors: FormErrors::default(),
+            form_input: EditBookmark {
+                url: bookmark.url,
+                title: bookmark.title,
+                notes: bookmark.notes,
+            },
+            bookmark_id: id,
+        },
+    )))
+}
+
+async fn post_edit(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+    QsForm(input): QsForm<EditBookmark>,
+) -> ResponseResult<Response> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+
+    if bookmark.ap_user_id != auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
+    let update = match InsertBookmark::try_from(input.clone()) {
+        Err(errors) => {
+            let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+            return Ok(
+                HtmfResponse(views::edit_bookmark::view(views::edit_bookmark::Data {
+                    layout,
+                    errors,
+                    form_input: input,
+                    bookmark_id: id,
+                }))
+                .into_response(),
+            );
+        }
+        Ok(i) => i,
+    };
+
+    let updated = update_bookmark(tx, &state, &federation_data, bookmark, update).await?;
+
+    Ok(Redirect::to(&updated.path()).into_response())
+}
+
+/// Save changes to a bookmark, archive it again if its URL changed, and tell
+/// remote instances about the changes once they're committed.
+pub(super) async fn update_bookmark(
+    mut tx: AppTx,
+    state: &AppState,
+    federation_data: &federation::Data,
+    bookmark: db::Bookmark,
+    update: InsertBookmark,
+) -> ResponseResult<db::Bookmark> {
+    let updated = db::bookmarks::update(&mut tx, bookmark.id, update).await?;
+
+    // Older snapshots show a different page, so take a new one
+    let url_changed = updated.url != bookmark.url;
+    if url_changed {
+        db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    }
+    let ap_user = if db::bookmarks::is_public(&mut tx, updated.id).await? {
+        Some(db::ap_users::read_by_id(&mut tx, updated.ap_user_id).await?)
+    } else {
+        None
+    };
+    tx.commit().await?;
+
+    if url_changed {
+        state.archive_queue.wake_worker();
+    }
+    // Only announce changes that were actually saved
+    if let Some(ap_user) = ap_user {
+        federation::UpdateBookmark::send_to_followers(&ap_user, updated.clone(), federation_data)
+            .await?;
+    }
+
+    Ok(updated)
+}
+
 async fn get_unsorted(
     extract::Tx(mut tx): extract::Tx,
     auth_user: AuthUser,
@@ -217,13 +444,51 @@ async fn get_unsorted(
     )))
 }
 
-async fn delete_by_id(
+async fn get_delete(
     extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
     Path(id): Path<Uuid>,
-) -> ResponseResult<HeaderMap> {
-    db::bookmarks::delete_by_id(&mut tx, id).await?;
+) -> ResponseResult<HtmfResponse> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
 
-    tx.commit().await?;
+    if bookmark.ap_user_id != auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+
+    Ok(HtmfResponse(views::confirm_delete::view(
+        views::confirm_delete::Data {
+            layout,
+            kind: "bookmark",
+            consequences: "The bookmark will be removed from all lists, and its archived page \
+                           will be deleted."
+                .to_string(),
+            action: format!("/bookmarks/{id}/delete"),
+            cancel_path: bookmark.path(),
+            title: bookmark.title,
+        },
+    )))
+}
+
+async fn post_delete(
+    extract::Tx(tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    delete_owned_bookmark(tx, auth_user.ap_user_id, &federation_data, id).await?;
+
+    Ok(Redirect::to("/bookmarks/unsorted"))
+}
+
+async fn delete_by_id(
+    extract::Tx(tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<HeaderMap> {
+    delete_owned_bookmark(tx, auth_user.ap_user_id, &federation_data, id).await?;
 
     let mut headers = HeaderMap::new();
     headers.insert(
@@ -234,6 +499,89 @@ async fn delete_by_id(
     Ok(headers)
 }
 
+/// Delete a bookmark if it belongs to the user, and tell remote instances
+/// about it if we've sent it to them before.
+pub(super) async fn delete_owned_bookmark(
+    mut tx: AppTx,
+    ap_user_id: Uuid,
+    federation_data: &federation::Data,
+    id: Uuid,
+) -> ResponseResult<()> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+
+    if bookmark.ap_user_id != ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
+    let ap_user = if db::bookmarks::is_public(&mut tx, id).await? {
+        Some(db::ap_users::read_by_id(&mut tx, ap_user_id).await?)
+    } else {
+        None
+    };
+    let bookmark = db::bookmarks::delete_by_id(&mut tx, id).await?;
+    tx.commit().await?;
+
+    if let Some(ap_user) = ap_user {
+        federation::DeleteBookmark::send_to_followers(&ap_user, &bookmark, federation_data).await?;
+    }
+
+    Ok(())
+}
+
+/// A local bookmark that became public or stopped being public, because a
+/// link from a public list was added or removed.
+pub(super) struct VisibilityChange {
+    author: db::ApUser,
+    bookmark: db::Bookmark,
+    pub(super) is_public: bool,
+}
+
+impl VisibilityChange {
+    /// Compare whether the bookmark is public now to `was_public`.
+    pub(super) async fn detect(
+        tx: &mut AppTx,
+        bookmark: db::Bookmark,
+        was_public: bool,
+    ) -> ResponseResult<Option<Self>> {
+        let is_public = db::bookmarks::is_public(tx, bookmark.id).await?;
+        // Remote bookmarks are federated by the instance they come from
+        if is_public == was_public
+            || db::users::by_ap_user_id(tx, bookmark.ap_user_id)
+                .await?
+                .is_none()
+        {
+            return Ok(None);
+        }
+        let author = db::ap_users::read_by_id(tx, bookmark.ap_user_id).await?;
+
+        Ok(Some(Self {
+            author,
+            bookmark,
+            is_public,
+        }))
+    }
+
+    /// Send the bookmark to the author's followers, or tell them to delete
+    /// it. Call this after committing the change.
+    pub(super) async fn send(self, federation_data: &federation::Data) -> ResponseResult<()> {
+        if self.is_public {
+            federation::CreateBookmark::send_to_followers(
+                &self.author,
+                self.bookmark,
+                federation_data,
+            )
+            .await
+        } else {
+            federation::DeleteBookmark::send_to_followers(
+                &self.author,
+                &self.bookmark,
+                federation_data,
+            )
+            .await
+        }
+    }
+}
+
 async fn post_archive(
     extract::Tx(mut tx): extract::Tx,
     auth_user: AuthUser,
@@ -245,12 +593,11 @@ async fn post_archive(
         return Err(crate::response_error::ResponseError::NotFound);
     }
 
-    db::archives::delete_by_bookmark_id(&mut tx, id).await?;
-
-    let archive = db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    // Older snapshots are kept, so the page can be compared to them later
+    db::archives::insert_pending(&mut tx, bookmark.id).await?;
     tx.commit().await?;
 
-    state.archive_queue.archive_in_background(archive.id);
+    state.archive_queue.wake_worker();
 
     Ok(Redirect::to(&format!("/bookmarks/{id}")))
 }
@@ -0,0 +1,251 @@
+use anyhow::Context;
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use uuid::Uuid;
+
+use crate::{
+    archive,
+    authentication::AuthUser,
+    db::{self, AppTx},
+    export,
+    extract::{self},
+    htmf_response::HtmfResponse,
+    response_error::{ResponseError, ResponseResult},
+    server::AppState,
+    views::{self, layout},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/export", get(get_export))
+        .route("/export/bookmarks.html", get(get_netscape_html))
+        .route("/export/backup.json", get(get_backup))
+        .route(
+            "/bookmarks/{id}/archives/{archive_id}/archive.warc",
+            get(get_archive_warc),
+        )
+        .route(
+            "/bookmarks/{id}/archives/{archive_id}/archive.html",
+            get(get_archive_html),
+        )
+        .route("/lists/{list_id}/archives.warc", get(get_list_warc))
+        .route("/lists/{list_id}/archives.html", get(get_list_html))
+}
+
+async fn get_export(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+
+    Ok(HtmfResponse(views::export::view(&views::export::Data {
+        layout,
+    })))
+}
+
+async fn get_netscape_html(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<Response> {
+    let backup = backup(&mut tx, &auth_user).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"ties-bookmarks.html\"",
+            ),
+        ],
+        export::netscape_html(&backup),
+    )
+        .into_response())
+}
+
+async fn get_backup(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<Response> {
+    let backup = backup(&mut tx, &auth_user).await?;
+    let json = serde_json::to_string_pretty(&backup).context("Failed to serialize backup")?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"ties-backup.json\"",
+            ),
+        ],
+        json,
+    )
+        .into_response())
+}
+
+async fn backup(tx: &mut AppTx, auth_user: &AuthUser) -> ResponseResult<export::Backup> {
+    let user = db::users::by_ap_user_id(tx, auth_user.ap_user_id)
+        .await?
+        .ok_or(ResponseError::NotFound)?;
+
+    export::backup(tx, &user).await
+}
+
+async fn get_archive_warc(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path((id, archive_id)): Path<(Uuid, Uuid)>,
+) -> ResponseResult<Response> {
+    let export = bookmark_export(&mut tx, &state, &auth_user, id, archive_id).await?;
+
+    Ok(warc_response(
+        &format!("ties-archive-{archive_id}.warc"),
+        archive::warc(&[export])?,
+    ))
+}
+
+async fn get_archive_html(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path((id, archive_id)): Path<(Uuid, Uuid)>,
+) -> ResponseResult<Response> {
+    let export = bookmark_export(&mut tx, &state, &auth_user, id, archive_id).await?;
+
+    Ok(html_response(
+        &format!("ties-archive-{archive_id}.html"),
+        views::single_file_archive::view(views::single_file_archive::Data {
+            list_title: None,
+            exports: vec![export],
+        })
+        .to_html(),
+    ))
+}
+
+async fn get_list_warc(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(list_id): Path<Uuid>,
+) -> ResponseResult<Response> {
+    let (_, exports) = list_exports(&mut tx, &state, &auth_user, list_id).await?;
+
+    Ok(warc_response(
+        &format!("ties-list-{list_id}.warc"),
+        archive::warc(&exports)?,
+    ))
+}
+
+async fn get_list_html(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(list_id): Path<Uuid>,
+) -> ResponseResult<Response> {
+    let (list, exports) = list_exports(&mut tx, &state, &auth_user, list_id).await?;
+
+    Ok(html_response(
+        &format!("ties-list-{list_id}.html"),
+        views::single_file_archive::view(views::single_file_archive::Data {
+            list_title: Some(list.title),
+            exports,
+        })
+        .to_html(),
+    ))
+}
+
+/// A successful snapshot of a bookmark that's either public or belongs to the
+/// user.
+async fn bookmark_export(
+    tx: &mut AppTx,
+    state: &AppState,
+    auth_user: &AuthUser,
+    id: Uuid,
+    archive_id: Uuid,
+) -> ResponseResult<archive::Export> {
+    let bookmark = db::bookmarks::by_id(tx, id).await?;
+    if !db::bookmarks::is_public(tx, bookmark.id).await?
+        && bookmark.ap_user_id != auth_user.ap_user_id
+    {
+        return Err(ResponseError::NotFound);
+    }
+
+    let archive = db::archives::by_id(tx, archive_id).await?;
+    if archive.bookmark_id != bookmark.id {
+        return Err(ResponseError::NotFound);
+    }
+
+    archive::Export::load(
+        tx,
+        &state.archive_resource_store,
+        &state.base_url,
+        bookmark,
+        archive,
+    )
+    .await
+}
+
+/// The newest successful snapshot of each bookmark in one of the user's lists.
+/// Exports are built in memory, so only owners can download them instead of
+/// anyone who can see a public list.
+async fn list_exports(
+    tx: &mut AppTx,
+    state: &AppState,
+    auth_user: &AuthUser,
+    list_id: Uuid,
+) -> ResponseResult<(db::List, Vec<archive::Export>)> {
+    let list = db::lists::by_id(tx, list_id).await?;
+    if list.ap_user_id != auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
+    let mut exports = Vec::new();
+    for archive in db::archives::list_latest_successful_by_list_id(tx, list_id).await? {
+        let bookmark = db::bookmarks::by_id(tx, archive.bookmark_id).await?;
+        exports.push(
+            archive::Export::load(
+                tx,
+                &state.archive_resource_store,
+                &state.base_url,
+                bookmark,
+                archive,
+            )
+            .await?,
+        );
+    }
+
+    Ok((list, exports))
+}
+
+fn warc_response(filename: &str, warc: Vec<u8>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "application/warc".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        warc,
+    )
+        .into_response()
+}
+
+fn html_response(filename: &str, html: String) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        html,
+    )
+        .into_response()
+}
@@ -4,13 +4,18 @@ use activitypub_federation::{
         json::FederationJson,
     },
     config::Data,
-    fetch::webfinger::{Webfinger, build_webfinger_response, extract_webfinger_name},
+    fetch::webfinger::{
+        Webfinger, build_webfinger_response, build_webfinger_response_with_type,
+        extract_webfinger_name,
+    },
     protocol::context::WithContext,
     traits::{ActivityHandler, Object},
 };
 use axum::{
     Json, Router,
     extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
     routing::{get, post},
 };
 use serde::{Deserialize, Serialize};
@@ -19,20 +24,32 @@ use url::Url;
 use uuid::Uuid;
 
 use crate::{
-    db::{self},
-    extract,
-    federation::{self, person::Person},
-    response_error::ResponseResult,
+    db::{self, AppTx},
+    export, extract,
+    federation::{
+        self,
+        collection::{self, ActorCollection},
+        group::{self, Group, GroupOutbox},
+        person::Person,
+    },
+    response_error::{ResponseError, ResponseResult},
     server::AppState,
 };
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/ap/user/{id}", get(get_person))
+        .route("/ap/user/{id}/followers", get(get_followers))
+        .route("/ap/user/{id}/following", get(get_following))
         .route("/ap/inbox/{user_id}", post(post_inbox))
         .route("/ap/outbox/{user_id}", get(get_outbox))
         .route("/ap/bookmark/{id}", get(get_bookmark))
+        .route("/ap/list/{id}", get(get_group))
+        .route("/ap/list/{id}/inbox", post(post_list_inbox))
+        .route("/ap/list/{id}/outbox", get(get_list_outbox))
+        .route("/ap/list/{id}/followers", get(get_list_followers))
         .route("/.well-known/webfinger", get(webfinger))
+        .route("/.well-known/host-meta", get(host_meta))
 }
 
 /// Read a local person by requesting the URL that is it's `ap_id`.
@@ -48,12 +65,61 @@ async fn get_person(
     Ok(FederationJson(WithContext::new_default(json_person)))
 }
 
+/// Read the actors following a local person.
+async fn get_followers(
+    extract::Tx(mut tx): extract::Tx,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<FederationJson<WithContext<ActorCollection>>> {
+    let ap_user = read_local_ap_user(&mut tx, id).await?;
+    let followers = db::ap_users::list_followers(&mut tx, ap_user.id).await?;
+
+    Ok(FederationJson(WithContext::new_default(
+        ActorCollection::new(
+            collection::followers_url(&state.base_url, ap_user.id)?,
+            followers,
+        ),
+    )))
+}
+
+/// Read the actors a local person follows.
+async fn get_following(
+    extract::Tx(mut tx): extract::Tx,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<FederationJson<WithContext<ActorCollection>>> {
+    let ap_user = read_local_ap_user(&mut tx, id).await?;
+    let following = db::ap_users::list_following(&mut tx, ap_user.id).await?;
+
+    Ok(FederationJson(WithContext::new_default(
+        ActorCollection::new(
+            collection::following_url(&state.base_url, ap_user.id)?,
+            following,
+        ),
+    )))
+}
+
+/// Remote users have their collections on their own instance, so we only
+/// serve them for local users.
+async fn read_local_ap_user(tx: &mut AppTx, id: Uuid) -> ResponseResult<db::ApUser> {
+    let ap_user = db::ap_users::read_by_id(tx, id).await?;
+    if db::users::by_ap_user_id(tx, ap_user.id).await?.is_none() {
+        return Err(ResponseError::NotFound);
+    }
+
+    Ok(ap_user)
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(untagged)]
 #[enum_delegate::implement(ActivityHandler)]
 pub enum PersonAcceptedActivities {
     Follow(federation::Follow),
     UndoFollow(federation::UndoFollow),
+    Accept(federation::Accept),
+    CreateBookmark(federation::CreateBookmark),
+    UpdateBookmark(federation::UpdateBookmark),
+    DeleteBookmark(federation::DeleteBookmark),
 }
 
 async fn post_inbox(data: federation::Data, activity_data: ActivityData) -> ResponseResult<()> {
@@ -66,28 +132,118 @@ async fn post_inbox(data: federation::Data, activity_data: ActivityData) -> Resp
     Ok(())
 }
 
-async fn get_outbox() -> ResponseResult<FederationJson<WithContext<serde_json::Value>>> {
-    let empty_outbox = serde_json::json!({
-        "type": "OrderedCollection",
-        "orderedItems": [],
-        "totalItems": 0
-    });
-    Ok(FederationJson(WithContext::new_default(empty_outbox)))
+#[derive(Deserialize)]
+pub struct OutboxQuery {
+    /// Request a page of activities instead of the collection itself
+    #[serde(default)]
+    page: bool,
+    before: Option<Uuid>,
+}
+
+/// Read the public bookmarks of a local user as `Create` activities.
+async fn get_outbox(
+    extract::Tx(mut tx): extract::Tx,
+    data: federation::Data,
+    Path(user_id): Path<Uuid>,
+    QsQuery(query): QsQuery<OutboxQuery>,
+) -> ResponseResult<Response> {
+    let ap_user = read_local_ap_user(&mut tx, user_id).await?;
+
+    Ok(if query.page {
+        let page = federation::outbox::page(&mut tx, &ap_user, query.before, &data).await?;
+        FederationJson(WithContext::new_default(page)).into_response()
+    } else {
+        let outbox = federation::outbox::outbox(&mut tx, &ap_user, &data).await?;
+        FederationJson(WithContext::new_default(outbox)).into_response()
+    })
 }
 
-/// Read a local bookmark by requesting the URL that is it's `ap_id`.
+/// Read a public local bookmark by requesting the URL that is it's `ap_id`.
 async fn get_bookmark(
     extract::Tx(mut tx): extract::Tx,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> ResponseResult<FederationJson<WithContext<federation::bookmark::BookmarkJson>>> {
     let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+    // Private bookmarks are never federated, and copies of remote bookmarks
+    // are served by their own instance
+    if !db::bookmarks::is_public(&mut tx, bookmark.id).await?
+        || db::users::by_ap_user_id(&mut tx, bookmark.ap_user_id)
+            .await?
+            .is_none()
+    {
+        return Err(ResponseError::NotFound);
+    }
     let json_bookmark = bookmark
         .into_json(&state.federation_config.to_request_data())
         .await?;
     Ok(FederationJson(WithContext::new_default(json_bookmark)))
 }
 
+/// Read a public list by requesting the URL that is it's `ap_id`.
+async fn get_group(
+    extract::Tx(mut tx): extract::Tx,
+    data: federation::Data,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<FederationJson<WithContext<Group>>> {
+    let list = group::read_public_list(&mut tx, id, &data.base_url).await?;
+    tx.commit().await?;
+    let json_group = list.into_json(&data).await?;
+    Ok(FederationJson(WithContext::new_default(json_group)))
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(untagged)]
+#[enum_delegate::implement(ActivityHandler)]
+pub enum GroupAcceptedActivities {
+    FollowList(federation::FollowList),
+    UndoFollowList(federation::UndoFollowList),
+}
+
+async fn post_list_inbox(
+    data: federation::Data,
+    activity_data: ActivityData,
+) -> ResponseResult<()> {
+    receive_activity::<WithContext<GroupAcceptedActivities>, db::ApUser, federation::Context>(
+        activity_data,
+        &data,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Read the announcements of the bookmarks recently added to a public list.
+async fn get_list_outbox(
+    extract::Tx(mut tx): extract::Tx,
+    data: federation::Data,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<FederationJson<WithContext<GroupOutbox>>> {
+    let list = group::read_public_list(&mut tx, id, &data.base_url).await?;
+    let outbox = group::outbox(&mut tx, &list, &data).await?;
+    tx.commit().await?;
+
+    Ok(FederationJson(WithContext::new_default(outbox)))
+}
+
+/// Read the actors following a public list.
+async fn get_list_followers(
+    extract::Tx(mut tx): extract::Tx,
+    data: federation::Data,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<FederationJson<WithContext<ActorCollection>>> {
+    let list = group::read_public_list(&mut tx, id, &data.base_url).await?;
+    let followers = db::ap_users::list_list_followers(&mut tx, list.list.id).await?;
+    tx.commit().await?;
+
+    Ok(FederationJson(WithContext::new_default(
+        ActorCollection::new(
+            group::followers_url(&data.base_url, list.list.id)?,
+            followers,
+        ),
+    )))
+}
+
 #[derive(Deserialize)]
 pub struct WebfingerQuery {
     resource: String,
@@ -101,6 +257,22 @@ async fn webfinger(
 ) -> ResponseResult<Json<Webfinger>> {
     // This also verifies that the domain is correct
     let username = extract_webfinger_name(&query.resource, &data)?;
+
+    // Public lists use their hyphenated ID as username, which can't clash with
+    // usernames of users since those can't contain dashes
+    if let Some(list_id) = username
+        .contains('-')
+        .then(|| Uuid::parse_str(username).ok())
+        .flatten()
+    {
+        let list = group::read_public_list(&mut tx, list_id, &state.base_url).await?;
+        tx.commit().await?;
+        return Ok(Json(build_webfinger_response_with_type(
+            query.resource,
+            vec![(list.ap_id.into_inner(), Some("Group"))],
+        )));
+    }
+
     let ap_id = db::ap_users::read_by_username(
         &mut tx,
         federation::webfinger::Resource::from_name_and_url(username.to_string(), &state.base_url)?,
@@ -112,3 +284,26 @@ async fn webfinger(
         ap_id.into_inner(),
     )))
 }
+
+/// Points older clients that don't know about the well-known webfinger URL to
+/// [`webfinger`].
+async fn host_meta(State(state): State<AppState>) -> ResponseResult<Response> {
+    let template = format!(
+        "{}?resource={{uri}}",
+        state.base_url.join("/.well-known/webfinger")?
+    );
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<XRD xmlns="http://docs.oasis-open.org/ns/xri/xrd-1.0">
+  <Link rel="lrdd" template="{}"/>
+</XRD>
+"#,
+        export::escape(&template)
+    );
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/xrd+xml; charset=utf-8")],
+        xml,
+    )
+        .into_response())
+}
@@ -0,0 +1,122 @@
+use axum::{
+    Router,
+    extract::{Path, State},
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use garde::{Report, Validate};
+use serde_qs::web::QsForm;
+use uuid::Uuid;
+
+use crate::{
+    authentication::AuthUser,
+    db::{self, AppTx, List},
+    extract::{self},
+    feed_subscriptions,
+    form_errors::FormErrors,
+    forms::feed_subscriptions::CreateFeedSubscription,
+    htmf_response::HtmfResponse,
+    response_error::{ResponseError, ResponseResult},
+    server::AppState,
+    views::{self, layout},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/lists/{list_id}/feeds",
+            get(get_subscriptions).post(post_subscribe),
+        )
+        .route(
+            "/lists/{list_id}/feeds/{subscription_id}/delete",
+            post(post_unsubscribe),
+        )
+}
+
+/// Only the owner of a list can manage its subscriptions.
+async fn owned_list(tx: &mut AppTx, auth_user: &AuthUser, list_id: Uuid) -> ResponseResult<List> {
+    let list = db::lists::by_id(tx, list_id).await?;
+    if list.ap_user_id != auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
+    Ok(list)
+}
+
+async fn get_subscriptions(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(list_id): Path<Uuid>,
+) -> ResponseResult<HtmfResponse> {
+    let list = owned_list(&mut tx, &auth_user, list_id).await?;
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+    let subscriptions = db::feed_subscriptions::list_by_list_id(&mut tx, list_id).await?;
+
+    Ok(HtmfResponse(views::feed_subscriptions::view(
+        &views::feed_subscriptions::Data {
+            layout,
+            list,
+            subscriptions,
+            input: CreateFeedSubscription::default(),
+            errors: FormErrors::default(),
+        },
+    )))
+}
+
+async fn post_subscribe(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(list_id): Path<Uuid>,
+    QsForm(input): QsForm<CreateFeedSubscription>,
+) -> ResponseResult<Response> {
+    let list = owned_list(&mut tx, &auth_user, list_id).await?;
+
+    let errors = if let Err(errors) = input.validate() {
+        Some(errors)
+    } else if db::feed_subscriptions::exists(&mut tx, list_id, &input.url).await? {
+        let mut errors = Report::new();
+        errors.append(
+            garde::Path::new("url"),
+            garde::Error::new("This list is already subscribed to this feed"),
+        );
+        Some(errors)
+    } else {
+        None
+    };
+    if let Some(errors) = errors {
+        let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+        let subscriptions = db::feed_subscriptions::list_by_list_id(&mut tx, list_id).await?;
+        return Ok(HtmfResponse(views::feed_subscriptions::view(
+            &views::feed_subscriptions::Data {
+                layout,
+                list,
+                subscriptions,
+                input,
+                errors: errors.into(),
+            },
+        ))
+        .into_response());
+    }
+
+    let subscription = db::feed_subscriptions::insert(&mut tx, list_id, input).await?;
+
+    tx.commit().await?;
+
+    feed_subscriptions::poll_once_in_background(state, subscription);
+
+    Ok(Redirect::to(&format!("{}/feeds", list.path())).into_response())
+}
+
+async fn post_unsubscribe(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path((list_id, subscription_id)): Path<(Uuid, Uuid)>,
+) -> ResponseResult<Redirect> {
+    let list = owned_list(&mut tx, &auth_user, list_id).await?;
+    db::feed_subscriptions::delete_by_id(&mut tx, subscription_id, list_id).await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to(&format!("{}/feeds", list.path())))
+}
@@ -0,0 +1,181 @@
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use uuid::Uuid;
+
+use crate::{
+    db::{self, AppTx, LinkDestinationWithChildren},
+    extract::{self},
+    federation::webfinger::Resource,
+    feeds::{self, Feed},
+    response_error::{ResponseError, ResponseResult},
+    server::AppState,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/lists/{list_id}/feed.rss", get(get_list_rss))
+        .route("/lists/{list_id}/feed.atom", get(get_list_atom))
+        .route("/user/{username}/feed.rss", get(get_user_rss))
+        .route("/user/{username}/feed.atom", get(get_user_atom))
+}
+
+async fn get_list_rss(
+    extract::Tx(mut tx): extract::Tx,
+    State(state): State<AppState>,
+    Path(list_id): Path<Uuid>,
+) -> ResponseResult<Response> {
+    let feed = list_feed(&mut tx, &state, list_id, "rss").await?;
+
+    Ok(rss_response(&feed))
+}
+
+async fn get_list_atom(
+    extract::Tx(mut tx): extract::Tx,
+    State(state): State<AppState>,
+    Path(list_id): Path<Uuid>,
+) -> ResponseResult<Response> {
+    let feed = list_feed(&mut tx, &state, list_id, "atom").await?;
+
+    Ok(atom_response(&feed))
+}
+
+async fn get_user_rss(
+    extract::Tx(mut tx): extract::Tx,
+    State(state): State<AppState>,
+    Path(handle): Path<String>,
+) -> ResponseResult<Response> {
+    let feed = user_feed(&mut tx, &state, &handle, "rss").await?;
+
+    Ok(rss_response(&feed))
+}
+
+async fn get_user_atom(
+    extract::Tx(mut tx): extract::Tx,
+    State(state): State<AppState>,
+    Path(handle): Path<String>,
+) -> ResponseResult<Response> {
+    let feed = user_feed(&mut tx, &state, &handle, "atom").await?;
+
+    Ok(atom_response(&feed))
+}
+
+/// The bookmarks in a public list, and the public lists it links to, in the
+/// order they were added.
+async fn list_feed(
+    tx: &mut AppTx,
+    state: &AppState,
+    list_id: Uuid,
+    extension: &str,
+) -> ResponseResult<Feed> {
+    let list = db::lists::by_id(tx, list_id).await?;
+    if list.private {
+        return Err(ResponseError::NotFound);
+    }
+
+    // Without a user id, this leaves out private lists
+    let links = db::links::list_by_list(tx, list_id, None).await?;
+    let entries = links
+        .into_iter()
+        .take(feeds::MAX_ENTRIES)
+        .map(|link| {
+            Ok(match link.dest {
+                LinkDestinationWithChildren::Bookmark(bookmark) => feeds::Entry {
+                    id: state.base_url.join(&bookmark.path())?,
+                    title: bookmark.title,
+                    url: bookmark.url,
+                    notes: bookmark.notes,
+                    date: link.created_at,
+                },
+                LinkDestinationWithChildren::List(linked) => feeds::Entry {
+                    id: state.base_url.join(&linked.list.path())?,
+                    title: linked.list.title,
+                    url: state.base_url.join(&linked.list.path())?.to_string(),
+                    notes: linked.list.content,
+                    date: link.created_at,
+                },
+            })
+        })
+        .collect::<ResponseResult<Vec<_>>>()?;
+
+    Ok(Feed {
+        description: format!("Bookmarks in the list \"{}\" on ties", list.title),
+        title: list.title,
+        url: state.base_url.join(&list.path())?,
+        feed_url: state
+            .base_url
+            .join(&format!("{}/feed.{extension}", list.path()))?,
+        entries,
+    })
+}
+
+/// The newest bookmarks of a local user that are in at least one public list.
+async fn user_feed(
+    tx: &mut AppTx,
+    state: &AppState,
+    handle: &str,
+    extension: &str,
+) -> ResponseResult<Feed> {
+    let ap_user =
+        db::ap_users::read_by_username(tx, Resource::parse_handle(handle, &state.base_url)?)
+            .await?;
+    // Remote users publish their bookmarks on their own instance
+    let user = db::users::by_ap_user_id(tx, ap_user.id)
+        .await?
+        .ok_or(ResponseError::NotFound)?;
+
+    let bookmarks = db::bookmarks::list_public_by_ap_user_id(
+        tx,
+        user.ap_user_id,
+        None,
+        i64::try_from(feeds::MAX_ENTRIES).unwrap_or(i64::MAX),
+    )
+    .await?;
+    let entries = bookmarks
+        .into_iter()
+        .map(|bookmark| {
+            Ok(feeds::Entry {
+                id: state.base_url.join(&bookmark.path())?,
+                title: bookmark.title,
+                url: bookmark.url,
+                notes: bookmark.notes,
+                date: bookmark.created_at,
+            })
+        })
+        .collect::<ResponseResult<Vec<_>>>()?;
+
+    let name = ap_user
+        .display_name
+        .unwrap_or_else(|| ap_user.username.clone());
+    let profile_path = format!("/user/{}", ap_user.username);
+
+    Ok(Feed {
+        title: format!("{name}'s bookmarks"),
+        description: format!("Public bookmarks of {name} on ties"),
+        url: state.base_url.join(&profile_path)?,
+        feed_url: state
+            .base_url
+            .join(&format!("{profile_path}/feed.{extension}"))?,
+        entries,
+    })
+}
+
+fn rss_response(feed: &Feed) -> Response {
+    (
+        [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+        feeds::rss(feed),
+    )
+        .into_response()
+}
+
+fn atom_response(feed: &Feed) -> Response {
+    (
+        [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+        feeds::atom(feed),
+    )
+        .into_response()
+}
@@ -0,0 +1,165 @@
+use anyhow::Context;
+use axum::{
+    Router,
+    extract::{DefaultBodyLimit, Multipart, State},
+    routing::get,
+};
+use clap::ValueEnum;
+
+use crate::{
+    authentication::AuthUser,
+    db,
+    extract::{self},
+    federation,
+    htmf_response::HtmfResponse,
+    import::{self, Duplicates, Format},
+    response_error::ResponseResult,
+    server::AppState,
+    views::{self, layout},
+};
+
+/// Browser exports of large collections can be several megabytes.
+const MAX_UPLOAD_SIZE: usize = 50 * 1024 * 1024;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route(
+        "/import",
+        get(get_import)
+            .post(post_import)
+            .layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE)),
+    )
+}
+
+async fn get_import(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+
+    Ok(HtmfResponse(views::import::view(&views::import::Data {
+        layout,
+        format: Format::default(),
+        duplicates: Duplicates::default(),
+        error: None,
+    })))
+}
+
+async fn post_import(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    federation_data: federation::Data,
+    mut multipart: Multipart,
+) -> ResponseResult<HtmfResponse> {
+    let mut file = None;
+    let mut format = Format::default();
+    let mut duplicates = Duplicates::default();
+    let mut dry_run = false;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .context("Failed to read import form")?
+    {
+        match field.name() {
+            Some("file") => {
+                file = Some(field.bytes().await.context("Failed to read import file")?);
+            }
+            Some("format") => {
+                let value = field.text().await.context("Failed to read import form")?;
+                format = Format::from_str(&value, true).unwrap_or_default();
+            }
+            Some("duplicates") => {
+                let value = field.text().await.context("Failed to read import form")?;
+                duplicates = Duplicates::from_str(&value, true).unwrap_or_default();
+            }
+            Some("dry_run") => {
+                dry_run = field.text().await.context("Failed to read import form")? == "true";
+            }
+            _ => {}
+        }
+    }
+
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+
+    let file = file
+        .filter(|file| !file.is_empty())
+        .map(|file| String::from_utf8_lossy(&file).into_owned());
+    let parsed = match &file {
+        Some(file) => import::parse(format, file),
+        None => Err(import::Error::InvalidFormat(
+            "Please select a file to import.".to_string(),
+        )),
+    };
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            return Ok(HtmfResponse(views::import::view(&views::import::Data {
+                layout,
+                format,
+                duplicates,
+                error: Some(error.to_string()),
+            })));
+        }
+    };
+
+    let summary = import::save(
+        &mut tx,
+        auth_user.user_id,
+        auth_user.ap_user_id,
+        &state.base_url,
+        parsed,
+        duplicates,
+    )
+    .await?;
+
+    if dry_run {
+        // Dropping the transaction rolls back everything the import saved
+        drop(tx);
+        return Ok(HtmfResponse(views::import::summary(
+            &views::import::SummaryData {
+                layout,
+                summary,
+                preview: Some(views::import::Preview {
+                    format,
+                    duplicates,
+                    file: file.unwrap_or_default(),
+                }),
+            },
+        )));
+    }
+
+    tx.commit().await?;
+
+    state
+        .archive_queue
+        .archive_all_in_background(summary.archive_ids.clone());
+
+    let mut tx = federation_data.db_pool.begin().await?;
+    let mut published = Vec::with_capacity(summary.published.len());
+    for bookmark_id in &summary.published {
+        published.push(db::bookmarks::by_id(&mut tx, *bookmark_id).await?);
+    }
+    let mut announcements = Vec::with_capacity(summary.announcements.len());
+    for (list_id, bookmark_id) in &summary.announcements {
+        announcements.push((
+            db::lists::by_id(&mut tx, *list_id).await?,
+            db::bookmarks::by_id(&mut tx, *bookmark_id).await?,
+        ));
+    }
+    let ap_user = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+    drop(tx);
+    for bookmark in published {
+        federation::CreateBookmark::send_to_followers(&ap_user, bookmark, &federation_data).await?;
+    }
+    for (list, bookmark) in announcements {
+        federation::AnnounceBookmark::send_to_followers(&list, bookmark, &federation_data).await?;
+    }
+
+    Ok(HtmfResponse(views::import::summary(
+        &views::import::SummaryData {
+            layout,
+            summary,
+            preview: None,
+        },
+    )))
+}
@@ -13,12 +13,14 @@ use uuid::Uuid;
 
 use crate::{
     authentication::AuthUser,
-    db::{self, LinkDestination},
+    db::{self, AppTx, LinkDestination},
     extract::{self},
+    federation,
     form_errors::FormErrors,
     forms::links::{CreateLink, PartialCreateLink},
     htmf_response::HtmfResponse,
     response_error::ResponseResult,
+    routes::bookmarks::VisibilityChange,
     server::AppState,
     views::{self, layout},
 };
@@ -32,6 +34,7 @@ pub fn router() -> Router<AppState> {
 async fn post_create(
     extract::Tx(mut tx): extract::Tx,
     auth_user: AuthUser,
+    federation_data: federation::Data,
     // TODO handle failed extractors in forms better
     QsForm(input): QsForm<PartialCreateLink>,
 ) -> ResponseResult<Response> {
@@ -74,21 +77,8 @@ async fn post_create(
         None => Vec::new(),
     };
 
-    // TODO if the link points to a public list and we haven't sent an activity for
-    // this src bookmark before, send an activity now
-    // https://github.com/raffomania/ties/issues/175
     if let (Some(src), Some(dest), true) = (&src_from_db, &dest_from_db, input.submitted) {
-        db::links::insert(
-            &mut tx,
-            auth_user.user_id,
-            CreateLink {
-                src: src.id(),
-                dest: dest.id(),
-            },
-        )
-        .await?;
-
-        tx.commit().await?;
+        insert_link(tx, auth_user.user_id, &federation_data, src, dest).await?;
 
         return Ok(Redirect::to(&src.path()).into_response());
     }
@@ -150,13 +140,59 @@ async fn get_create(
     .into())
 }
 
+/// Link `src` to `dest`. Once committed, a linked bookmark is announced to
+/// the followers of the list, and sent to the followers of its author if the
+/// link made it public.
+pub(super) async fn insert_link(
+    mut tx: AppTx,
+    user_id: Uuid,
+    federation_data: &federation::Data,
+    src: &LinkDestination,
+    dest: &LinkDestination,
+) -> ResponseResult<db::Link> {
+    let was_public = match dest {
+        LinkDestination::Bookmark(bookmark) => {
+            db::bookmarks::is_public(&mut tx, bookmark.id).await?
+        }
+        LinkDestination::List(_) => false,
+    };
+
+    let link = db::links::insert(
+        &mut tx,
+        user_id,
+        CreateLink {
+            src: src.id(),
+            dest: dest.id(),
+        },
+    )
+    .await?;
+
+    let visibility_change = match dest {
+        LinkDestination::Bookmark(bookmark) => {
+            VisibilityChange::detect(&mut tx, bookmark.clone(), was_public).await?
+        }
+        LinkDestination::List(_) => None,
+    };
+    tx.commit().await?;
+
+    if let Some(visibility_change) = visibility_change {
+        visibility_change.send(federation_data).await?;
+    }
+    // Let people following the list know about the new bookmark
+    if let (LinkDestination::List(list), LinkDestination::Bookmark(bookmark)) = (src, dest) {
+        federation::AnnounceBookmark::send_to_followers(list, bookmark.clone(), federation_data)
+            .await?;
+    }
+
+    Ok(link)
+}
+
 async fn delete_by_id(
-    extract::Tx(mut tx): extract::Tx,
+    extract::Tx(tx): extract::Tx,
+    federation_data: federation::Data,
     Path(id): Path<Uuid>,
 ) -> ResponseResult<HeaderMap> {
-    db::links::delete_by_id(&mut tx, id).await?;
-
-    tx.commit().await?;
+    delete_link(tx, &federation_data, id).await?;
 
     let mut headers = HeaderMap::new();
     headers.insert(
@@ -166,3 +202,39 @@ async fn delete_by_id(
 
     Ok(headers)
 }
+
+/// Delete a link, and tell the followers of the author of a linked bookmark
+/// to delete it if the bookmark isn't public anymore.
+pub(super) async fn delete_link(
+    mut tx: AppTx,
+    federation_data: &federation::Data,
+    id: Uuid,
+) -> ResponseResult<()> {
+    let link = db::links::by_id(&mut tx, id).await?;
+    let dest_bookmark = match link.dest_bookmark_id {
+        Some(bookmark_id) => {
+            let was_public = db::bookmarks::is_public(&mut tx, bookmark_id).await?;
+            Some((
+                db::bookmarks::by_id(&mut tx, bookmark_id).await?,
+                was_public,
+            ))
+        }
+        None => None,
+    };
+
+    db::links::delete_by_id(&mut tx, id).await?;
+
+    let visibility_change = match dest_bookmark {
+        Some((bookmark, was_public)) => {
+            VisibilityChange::detect(&mut tx, bookmark, was_public).await?
+        }
+        None => None,
+    };
+    tx.commit().await?;
+
+    if let Some(visibility_change) = visibility_change {
+        visibility_change.send(federation_data).await?;
+    }
+
+    Ok(())
+}
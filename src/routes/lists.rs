@@ -10,8 +10,9 @@ use uuid::Uuid;
 
 use crate::{
     authentication::AuthUser,
-    db::{self},
+    db::{self, AppTx},
     extract::{self},
+    federation,
     form_errors::FormErrors,
     forms,
     forms::lists::{CreateList, EditListPinned, EditListPrivate},
@@ -19,7 +20,7 @@ use crate::{
     response_error::{ResponseError, ResponseResult},
     server::AppState,
     views,
-    views::layout,
+    views::{base_document::Feed, layout},
 };
 
 pub fn router() -> Router<AppState> {
@@ -31,12 +32,14 @@ pub fn router() -> Router<AppState> {
         .route("/lists/{list_id}/edit_title", post(post_edit_title))
         .route("/lists/{list_id}/edit_title", get(get_edit_title))
         .route("/lists/{list_id}/edit_pinned", post(edit_pinned))
+        .route("/lists/{list_id}/delete", get(get_delete).post(post_delete))
         .route("/lists/unpinned", get(list_unpinned))
 }
 
 async fn get_show(
     auth_user: Option<AuthUser>,
     extract::Tx(mut tx): extract::Tx,
+    federation_data: federation::Data,
     Path(list_id): Path<Uuid>,
 ) -> ResponseResult<HtmfResponse> {
     let maybe_ap_user_id = auth_user.as_ref().map(|u| u.ap_user_id);
@@ -57,10 +60,22 @@ async fn get_show(
         }
     }
 
+    let mut layout = layout::Template::from_db(&mut tx, auth_user.as_ref()).await?;
+    if !list.private {
+        layout.feeds.push(Feed {
+            title: list.title.clone(),
+            path: format!("{}/feed", list.path()),
+        });
+    }
+
+    let fediverse_handle =
+        (!list.private).then(|| format!("{}@{}", list.id, federation_data.domain()));
+
     Ok(HtmfResponse(views::list::view(&views::list::Data {
-        layout: layout::Template::from_db(&mut tx, auth_user.as_ref()).await?,
+        layout,
         links,
         list,
+        fediverse_handle,
         metadata: db::lists::metadata_by_id(&mut tx, list_id).await?,
         backlinks,
     })))
@@ -149,6 +164,7 @@ async fn post_edit_title(
 async fn edit_private(
     auth_user: AuthUser,
     extract::Tx(mut tx): extract::Tx,
+    federation_data: federation::Data,
     Path(list_id): Path<Uuid>,
     QsForm(input): QsForm<EditListPrivate>,
 ) -> ResponseResult<Response> {
@@ -158,11 +174,39 @@ async fn edit_private(
         return Err(ResponseError::NotFound);
     }
 
-    db::lists::set_private(&mut tx, list_id, input.private).await?;
+    set_private(tx, &federation_data, &list, input.private).await?;
+
+    Ok(Redirect::to(&list.path()).into_response())
+}
+
+/// Change whether a list is private. Making a public list private removes it
+/// from the fediverse, along with the bookmarks that were only public because
+/// of it.
+pub(super) async fn set_private(
+    mut tx: AppTx,
+    federation_data: &federation::Data,
+    list: &db::List,
+    private: bool,
+) -> ResponseResult<db::List> {
+    let federated = if private {
+        FederatedList::read(&mut tx, list, federation_data).await?
+    } else {
+        None
+    };
+
+    let updated = db::lists::set_private(&mut tx, list.id, private).await?;
+    if private {
+        // Private lists aren't federated, so nobody can follow them
+        db::list_actors::remove_all_followers(&mut tx, list.id).await?;
+    }
 
     tx.commit().await?;
 
-    Ok(Redirect::to(&list.path()).into_response())
+    if let Some(federated) = federated {
+        federated.send_deletes(federation_data).await?;
+    }
+
+    Ok(updated)
 }
 
 async fn edit_pinned(
@@ -184,6 +228,121 @@ async fn edit_pinned(
     Ok(Redirect::to(&list.path()).into_response())
 }
 
+async fn get_delete(
+    auth_user: AuthUser,
+    extract::Tx(mut tx): extract::Tx,
+    Path(list_id): Path<Uuid>,
+) -> ResponseResult<HtmfResponse> {
+    let list = db::lists::by_id(&mut tx, list_id).await?;
+
+    if list.ap_user_id != auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+
+    Ok(HtmfResponse(views::confirm_delete::view(
+        views::confirm_delete::Data {
+            layout,
+            kind: "list",
+            consequences: "The bookmarks and lists in this list will not be deleted, but all \
+                           links from and to this list will be removed."
+                .to_string(),
+            action: format!("/lists/{list_id}/delete"),
+            cancel_path: list.path(),
+            title: list.title,
+        },
+    )))
+}
+
+async fn post_delete(
+    auth_user: AuthUser,
+    extract::Tx(mut tx): extract::Tx,
+    federation_data: federation::Data,
+    Path(list_id): Path<Uuid>,
+) -> ResponseResult<Response> {
+    let list = db::lists::by_id(&mut tx, list_id).await?;
+
+    if list.ap_user_id != auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
+    delete_list(tx, &federation_data, &list).await?;
+
+    Ok(Redirect::to("/").into_response())
+}
+
+/// Delete a list along with its links, and remove it from the fediverse if it
+/// was public.
+pub(super) async fn delete_list(
+    mut tx: AppTx,
+    federation_data: &federation::Data,
+    list: &db::List,
+) -> ResponseResult<()> {
+    let federated = FederatedList::read(&mut tx, list, federation_data).await?;
+    db::lists::delete_by_id(&mut tx, list.id).await?;
+
+    tx.commit().await?;
+
+    if let Some(federated) = federated {
+        federated.send_deletes(federation_data).await?;
+    }
+
+    Ok(())
+}
+
+/// What remote instances know about a public list. Read this before the list
+/// is deleted or made private, since its followers and links are removed along
+/// with it.
+struct FederatedList {
+    actor: db::ListActor,
+    followers: Vec<db::ApUser>,
+    bookmarks: Vec<db::Bookmark>,
+}
+
+impl FederatedList {
+    /// Returns `None` for private lists, which were never federated.
+    async fn read(
+        tx: &mut AppTx,
+        list: &db::List,
+        federation_data: &federation::Data,
+    ) -> ResponseResult<Option<Self>> {
+        if list.private {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            actor: db::list_actors::by_list(tx, list.clone(), &federation_data.base_url).await?,
+            followers: db::ap_users::list_list_followers(tx, list.id).await?,
+            bookmarks: db::bookmarks::list_local_by_public_list_id(tx, list.id).await?,
+        }))
+    }
+
+    /// Tell the list's followers that it's gone, and tell remote instances to
+    /// delete their copies of bookmarks that aren't public anymore without
+    /// the list. Call this after committing.
+    async fn send_deletes(self, federation_data: &federation::Data) -> ResponseResult<()> {
+        federation::DeleteList::send(&self.actor, &self.followers, federation_data).await?;
+
+        let mut tx = federation_data.db_pool.begin().await?;
+        let mut unpublished = Vec::new();
+        for bookmark in self.bookmarks {
+            if !db::bookmarks::is_public(&mut tx, bookmark.id).await? {
+                let author = db::ap_users::read_by_id(&mut tx, bookmark.ap_user_id).await?;
+                unpublished.push((author, bookmark));
+            }
+        }
+        tx.commit().await?;
+
+        for (author, bookmark) in unpublished {
+            federation::DeleteBookmark::send_to_followers(&author, &bookmark, federation_data)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
 // TODO colocate this with view and db code
 async fn list_unpinned(
     auth_user: AuthUser,
@@ -1,8 +1,16 @@
+pub mod api;
 pub mod assets;
 pub mod bookmarks;
+pub mod export;
 pub mod federation;
+pub mod feed_subscriptions;
+pub mod feeds;
+pub mod import;
 pub mod index;
 pub mod links;
 pub mod lists;
+pub mod nodeinfo;
 pub mod search;
+pub mod settings;
+pub mod timeline;
 pub mod users;
@@ -0,0 +1,120 @@
+//! [NodeInfo](https://nodeinfo.diaspora.software/) lets other fediverse
+//! software and crawlers find out which software a server runs and how many
+//! people use it.
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Serialize;
+use url::Url;
+
+use crate::{db, extract, oidc, response_error::ResponseResult, server::AppState};
+
+const SCHEMA_2_1: &str = "http://nodeinfo.diaspora.software/ns/schema/2.1";
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/.well-known/nodeinfo", get(get_discovery))
+        .route("/nodeinfo/2.1", get(get_nodeinfo))
+}
+
+#[derive(Serialize)]
+struct Discovery {
+    links: Vec<DiscoveryLink>,
+}
+
+#[derive(Serialize)]
+struct DiscoveryLink {
+    rel: &'static str,
+    href: Url,
+}
+
+async fn get_discovery(State(state): State<AppState>) -> ResponseResult<Json<Discovery>> {
+    Ok(Json(Discovery {
+        links: vec![DiscoveryLink {
+            rel: SCHEMA_2_1,
+            href: state.base_url.join("/nodeinfo/2.1")?,
+        }],
+    }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeInfo {
+    version: &'static str,
+    software: Software,
+    protocols: Vec<&'static str>,
+    services: Services,
+    open_registrations: bool,
+    usage: Usage,
+    metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct Software {
+    name: &'static str,
+    version: &'static str,
+    repository: &'static str,
+}
+
+#[derive(Serialize)]
+struct Services {
+    inbound: Vec<&'static str>,
+    outbound: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Usage {
+    users: UserUsage,
+    /// Public bookmarks of local users
+    local_posts: i64,
+}
+
+#[derive(Serialize)]
+struct UserUsage {
+    total: i64,
+}
+
+async fn get_nodeinfo(
+    extract::Tx(mut tx): extract::Tx,
+    State(state): State<AppState>,
+) -> ResponseResult<Response> {
+    let nodeinfo = NodeInfo {
+        version: "2.1",
+        software: Software {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            repository: env!("CARGO_PKG_REPOSITORY"),
+        },
+        protocols: vec!["activitypub"],
+        services: Services {
+            inbound: Vec::new(),
+            outbound: vec!["atom1.0", "rss2.0"],
+        },
+        // New users can sign up in demo mode, or by logging in via OIDC
+        open_registrations: state.demo_mode
+            || matches!(state.oidc_state, oidc::State::Configured(_)),
+        usage: Usage {
+            users: UserUsage {
+                total: db::users::count(&mut tx).await?,
+            },
+            local_posts: db::bookmarks::count_local_public(&mut tx).await?,
+        },
+        metadata: serde_json::Map::new(),
+    };
+    tx.commit().await?;
+
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            format!("application/json; profile=\"{SCHEMA_2_1}#\""),
+        )],
+        Json(nodeinfo),
+    )
+        .into_response())
+}
@@ -1,11 +1,15 @@
 use axum::{Router, routing::get};
 use serde::{Deserialize, Serialize};
 use serde_qs::web::QsQuery;
+use time::{Date, Month};
 use uuid::Uuid;
 
 use crate::{
     authentication::AuthUser,
-    db::{self},
+    db::{
+        self,
+        search::{Query, QueryError},
+    },
     extract,
     htmf_response::HtmfResponse,
     response_error::ResponseResult,
@@ -20,9 +24,199 @@ pub fn router() -> Router<AppState> {
 
 #[derive(Deserialize, Serialize)]
 pub struct SearchQuery {
-    /// The words to search for
+    /// The words to search for, and filters like `list:"Rust"`. See
+    /// [`SearchQuery::parse`] for the full syntax.
     pub q: String,
-    pub after_bookmark_id: Option<Uuid>,
+    /// Only show bookmarks or lists
+    pub kind: Option<db::search::Kind>,
+    pub after_id: Option<Uuid>,
+}
+
+impl SearchQuery {
+    /// Parse the search input into words to search for and filters.
+    ///
+    /// Supported syntax:
+    /// - `word` and `"a phrase"` to search for
+    /// - `-word` and `-"a phrase"` to exclude
+    /// - `list:Rust` or `list:"Rust Books"` for items linked from a list
+    /// - `domain:github.com` for bookmarks on a domain and its subdomains
+    /// - `before:2025-01-01` for items created before a date
+    /// - `archived:success`, `archived:failed` or `archived:pending` for
+    ///   bookmarks by archive state
+    /// - `unsorted` for bookmarks that aren't in any list
+    /// - `private` for private lists and bookmarks not in any public list
+    pub fn parse(&self) -> Result<Query, QueryError> {
+        let mut query = Query::default();
+        let mut terms = Vec::new();
+
+        for token in tokenize(&self.q)? {
+            let Token {
+                negated,
+                filter,
+                value,
+                quoted,
+            } = token;
+
+            if let Some(filter) = filter {
+                if negated {
+                    return Err(QueryError::NegatedFilter(filter.to_string()));
+                }
+                if value.is_empty() {
+                    return Err(QueryError::MissingValue(filter.to_string()));
+                }
+                match filter {
+                    Filter::List => query.lists.push(value.to_lowercase()),
+                    Filter::Domain => query.domains.push(value.to_lowercase()),
+                    Filter::Before => {
+                        query.before =
+                            Some(parse_date(&value).ok_or(QueryError::InvalidDate(value))?);
+                    }
+                    Filter::Archived => {
+                        query.archived = Some(match value.to_lowercase().as_str() {
+                            "success" => db::archives::Status::Success,
+                            "failed" => db::archives::Status::Error,
+                            "pending" => db::archives::Status::Pending,
+                            _ => return Err(QueryError::InvalidArchiveStatus(value)),
+                        });
+                    }
+                }
+                continue;
+            }
+
+            let flag = if quoted {
+                None
+            } else if value.eq_ignore_ascii_case("unsorted") {
+                Some(&mut query.unsorted)
+            } else if value.eq_ignore_ascii_case("private") {
+                Some(&mut query.private)
+            } else {
+                None
+            };
+            if let Some(flag) = flag {
+                if negated {
+                    return Err(QueryError::NegatedFilter(value));
+                }
+                *flag = true;
+                continue;
+            }
+
+            if value.is_empty() {
+                continue;
+            }
+            if negated {
+                query.excluded_terms.push(value);
+                continue;
+            }
+            if quoted {
+                terms.push(format!("\"{value}\""));
+            } else {
+                terms.push(value.clone());
+            }
+            query.words.push(value);
+        }
+
+        query.terms = terms.join(" ");
+        Ok(query)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Filter {
+    List,
+    Domain,
+    Before,
+    Archived,
+}
+
+impl Filter {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "list" => Some(Self::List),
+            "domain" => Some(Self::Domain),
+            "before" => Some(Self::Before),
+            "archived" => Some(Self::Archived),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Filter::List => "list",
+            Filter::Domain => "domain",
+            Filter::Before => "before",
+            Filter::Archived => "archived",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Token {
+    negated: bool,
+    filter: Option<Filter>,
+    value: String,
+    quoted: bool,
+}
+
+/// Split the search input at whitespace outside of quotes.
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return Ok(tokens);
+        }
+
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut token = Token {
+            negated,
+            filter: None,
+            value: String::new(),
+            quoted: false,
+        };
+
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+            match c {
+                '"' => {
+                    token.quoted = true;
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some(c) => token.value.push(c),
+                            None => return Err(QueryError::UnterminatedQuote),
+                        }
+                    }
+                }
+                // Only the first colon in an unquoted word can start a filter, so
+                // words like "https://example.com" are still searchable.
+                ':' if token.filter.is_none() && !token.quoted => {
+                    if let Some(filter) = Filter::from_name(&token.value) {
+                        token.filter = Some(filter);
+                        token.value.clear();
+                    } else {
+                        token.value.push(c);
+                    }
+                }
+                c => token.value.push(c),
+            }
+        }
+
+        tokens.push(token);
+    }
+}
+
+/// Parse dates like `2025-01-31`.
+fn parse_date(value: &str) -> Option<Date> {
+    let mut parts = value.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month: u8 = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+
+    Date::from_calendar_date(year, Month::try_from(month).ok()?, day).ok()
 }
 
 async fn get_search(
@@ -31,16 +225,103 @@ async fn get_search(
 
     QsQuery(query): QsQuery<SearchQuery>,
 ) -> ResponseResult<HtmfResponse> {
-    let results = db::search::search(
-        &mut tx,
-        &query.q,
-        auth_user.ap_user_id,
-        query.after_bookmark_id,
-    )
-    .await?;
+    let results = match query.parse() {
+        Ok(parsed) => Ok(db::search::search(
+            &mut tx,
+            &parsed,
+            auth_user.ap_user_id,
+            query.kind,
+            query.after_id,
+        )
+        .await?),
+        Err(e) => Err(e),
+    };
     let mut layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
     layout.previous_search_input = Some(query.q);
     Ok(HtmfResponse(views::search_results::view(
-        &views::search_results::Data { layout, results },
+        &views::search_results::Data {
+            layout,
+            results,
+            kind: query.kind,
+        },
     )))
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn parse(q: &str) -> Result<Query, QueryError> {
+        SearchQuery {
+            q: q.to_string(),
+            kind: None,
+            after_id: None,
+        }
+        .parse()
+    }
+
+    #[test]
+    fn parses_terms_and_filters() {
+        let query = parse(
+            r#"rust "async book" -tokio -"old stuff" list:"Rust Books" domain:GitHub.com before:2025-01-31 archived:failed unsorted private"#,
+        );
+        assert_eq!(
+            query,
+            Ok(Query {
+                terms: r#"rust "async book""#.to_string(),
+                words: vec!["rust".to_string(), "async book".to_string()],
+                excluded_terms: vec!["tokio".to_string(), "old stuff".to_string()],
+                lists: vec!["rust books".to_string()],
+                domains: vec!["github.com".to_string()],
+                before: Date::from_calendar_date(2025, Month::January, 31).ok(),
+                archived: Some(db::archives::Status::Error),
+                unsorted: true,
+                private: true,
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_filters_and_quoted_keywords_as_terms() {
+        let query = parse(r#"https://example.com "private" title:foo"#);
+        assert_eq!(
+            query,
+            Ok(Query {
+                terms: r#"https://example.com "private" title:foo"#.to_string(),
+                words: vec![
+                    "https://example.com".to_string(),
+                    "private".to_string(),
+                    "title:foo".to_string(),
+                ],
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn reports_malformed_queries() {
+        assert_eq!(parse(r#"list:"Rust"#), Err(QueryError::UnterminatedQuote));
+        assert_eq!(
+            parse("rust domain:"),
+            Err(QueryError::MissingValue("domain".to_string()))
+        );
+        assert_eq!(
+            parse("-list:Rust"),
+            Err(QueryError::NegatedFilter("list".to_string()))
+        );
+        assert_eq!(
+            parse("-unsorted"),
+            Err(QueryError::NegatedFilter("unsorted".to_string()))
+        );
+        assert_eq!(
+            parse("before:2025-02-30"),
+            Err(QueryError::InvalidDate("2025-02-30".to_string()))
+        );
+        assert_eq!(
+            parse("archived:maybe"),
+            Err(QueryError::InvalidArchiveStatus("maybe".to_string()))
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use axum::{
+    Router,
+    extract::Path,
+    response::Redirect,
+    routing::{get, post},
+};
+use garde::Validate;
+use serde_qs::web::QsForm;
+use uuid::Uuid;
+
+use crate::{
+    authentication::{self, AuthUser},
+    db::{self},
+    extract::{self},
+    form_errors::FormErrors,
+    forms::api_tokens::CreateApiToken,
+    htmf_response::HtmfResponse,
+    response_error::ResponseResult,
+    server::AppState,
+    views::{self, layout},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/settings", get(get_settings))
+        .route("/settings/tokens", post(post_create_token))
+        .route(
+            "/settings/tokens/{token_id}/delete",
+            post(post_delete_token),
+        )
+}
+
+async fn get_settings(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+    let tokens = db::api_tokens::list_by_user_id(&mut tx, auth_user.user_id).await?;
+
+    Ok(HtmfResponse(views::settings::view(
+        &views::settings::Data {
+            layout,
+            tokens,
+            input: CreateApiToken::default(),
+            errors: FormErrors::default(),
+            created_token: None,
+        },
+    )))
+}
+
+async fn post_create_token(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    QsForm(input): QsForm<CreateApiToken>,
+) -> ResponseResult<HtmfResponse> {
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+
+    if let Err(errors) = input.validate() {
+        let tokens = db::api_tokens::list_by_user_id(&mut tx, auth_user.user_id).await?;
+        return Ok(HtmfResponse(views::settings::view(
+            &views::settings::Data {
+                layout,
+                tokens,
+                input,
+                errors: errors.into(),
+                created_token: None,
+            },
+        )));
+    }
+
+    let token = authentication::generate_api_token();
+    db::api_tokens::insert(
+        &mut tx,
+        auth_user.user_id,
+        input,
+        &authentication::hash_api_token(&token),
+    )
+    .await?;
+    let tokens = db::api_tokens::list_by_user_id(&mut tx, auth_user.user_id).await?;
+
+    tx.commit().await?;
+
+    Ok(HtmfResponse(views::settings::view(
+        &views::settings::Data {
+            layout,
+            tokens,
+            input: CreateApiToken::default(),
+            errors: FormErrors::default(),
+            created_token: Some(token),
+        },
+    )))
+}
+
+async fn post_delete_token(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(token_id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    db::api_tokens::delete_by_id(&mut tx, token_id, auth_user.user_id).await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/settings"))
+}
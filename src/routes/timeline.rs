@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use axum::{
+    Router,
+    extract::{Path, State},
+    response::Redirect,
+    routing::{get, post},
+};
+use serde::Deserialize;
+use serde_qs::web::QsQuery;
+use uuid::Uuid;
+
+use crate::{
+    authentication::AuthUser,
+    db::{self, bookmarks::InsertBookmark},
+    extract::{self},
+    htmf_response::HtmfResponse,
+    response_error::{ResponseError, ResponseResult},
+    server::AppState,
+    views::{self, layout},
+};
+
+/// Number of bookmarks on each page of the timeline.
+const PAGE_SIZE: usize = 30;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/timeline", get(get_timeline))
+        .route("/timeline/bookmarks/{id}/save", post(post_save))
+        .route("/timeline/authors/{id}/mute", post(post_mute))
+        .route("/timeline/authors/{id}/unmute", post(post_unmute))
+}
+
+#[derive(Deserialize)]
+struct TimelineQuery {
+    before: Option<Uuid>,
+}
+
+async fn get_timeline(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    QsQuery(query): QsQuery<TimelineQuery>,
+) -> ResponseResult<HtmfResponse> {
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+
+    // Fetch one more bookmark than needed to find out if there's a next page
+    let mut bookmarks = db::bookmarks::list_timeline(
+        &mut tx,
+        auth_user.ap_user_id,
+        query.before,
+        i64::try_from(PAGE_SIZE + 1).unwrap_or(i64::MAX),
+    )
+    .await?;
+    let next_page = if bookmarks.len() > PAGE_SIZE {
+        bookmarks.truncate(PAGE_SIZE);
+        bookmarks
+            .last()
+            .map(|last| format!("/timeline?before={}", last.id))
+    } else {
+        None
+    };
+
+    let author_ids = bookmarks
+        .iter()
+        .map(|bookmark| bookmark.ap_user_id)
+        .collect::<Vec<_>>();
+    let authors = db::ap_users::list_by_ids(&mut tx, &author_ids)
+        .await?
+        .into_iter()
+        .map(|author| (author.id, author))
+        .collect::<HashMap<_, _>>();
+    let muted = db::ap_users::list_muted(&mut tx, auth_user.ap_user_id).await?;
+
+    Ok(HtmfResponse(views::timeline::view(
+        &views::timeline::Data {
+            layout,
+            bookmarks,
+            authors,
+            muted,
+            next_page,
+        },
+    )))
+}
+
+/// Copy a bookmark from the timeline into a new bookmark of the current user,
+/// then ask them which lists to add it to. Reuses the user's existing
+/// bookmark if they already saved the URL.
+async fn post_save(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+    let is_local = db::users::by_ap_user_id(&mut tx, bookmark.ap_user_id)
+        .await?
+        .is_some();
+    // Don't reveal private bookmarks of other users
+    if is_local && !db::bookmarks::is_public(&mut tx, bookmark.id).await? {
+        return Err(ResponseError::NotFound);
+    }
+
+    let existing = db::bookmarks::by_url(&mut tx, auth_user.ap_user_id, &bookmark.url).await?;
+    let (saved, is_new) = match existing {
+        Some(existing) => (existing, false),
+        None => {
+            let saved = db::bookmarks::insert_local(
+                &mut tx,
+                auth_user.ap_user_id,
+                InsertBookmark {
+                    url: bookmark.url,
+                    title: bookmark.title,
+                    notes: None,
+                },
+                &state.base_url,
+            )
+            .await?;
+            db::archives::insert_pending(&mut tx, saved.id).await?;
+            (saved, true)
+        }
+    };
+    tx.commit().await?;
+
+    if is_new {
+        state.archive_queue.wake_worker();
+    }
+
+    Ok(Redirect::to(&format!("/links/create?dest_id={}", saved.id)))
+}
+
+async fn post_mute(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    let author = db::ap_users::read_by_id(&mut tx, id).await?;
+    db::mutes::insert(&mut tx, auth_user.ap_user_id, author.id).await?;
+    tx.commit().await?;
+
+    Ok(Redirect::to("/timeline"))
+}
+
+async fn post_unmute(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    db::mutes::remove(&mut tx, auth_user.ap_user_id, id).await?;
+    tx.commit().await?;
+
+    Ok(Redirect::to("/timeline"))
+}
@@ -1,3 +1,4 @@
+use activitypub_federation::fetch::webfinger::webfinger_resolve_actor;
 use anyhow::{Context, anyhow};
 use axum::{
     Router,
@@ -12,14 +13,18 @@ use tower_sessions::Session;
 
 use crate::{
     authentication::{self, AuthUser},
-    db,
+    db::{self, AppTx},
     extract::{self},
-    forms::users::{CreateOidcUser, Login, OidcLoginQuery, OidcSelectUsername},
+    federation,
+    form_errors::FormErrors,
+    forms::users::{CreateOidcUser, Login, LookupUser, OidcLoginQuery, OidcSelectUsername},
     htmf_response::HtmfResponse,
     oidc::{self},
     response_error::{ResponseError, ResponseResult},
     server::AppState,
-    views::{self, layout, login, oidc_select_username},
+    views::{
+        self, base_document::Feed, layout, login, oidc_select_username, profile::FollowStatus,
+    },
 };
 
 pub fn router() -> Router<AppState> {
@@ -31,6 +36,9 @@ pub fn router() -> Router<AppState> {
         .route("/login_demo", post(post_login_demo))
         .route("/logout", post(logout))
         .route("/user/{username}", get(get_profile))
+        .route("/user/{username}/follow", post(post_follow))
+        .route("/user/{username}/unfollow", post(post_unfollow))
+        .route("/users/lookup", post(post_lookup))
 }
 
 async fn post_login(
@@ -219,18 +227,63 @@ async fn get_profile(
     State(state): State<AppState>,
     auth_user: Option<AuthUser>,
 ) -> ResponseResult<HtmfResponse> {
-    let layout = layout::Template::from_db(&mut tx, auth_user.as_ref()).await?;
+    let ap_user = read_by_handle(&mut tx, &handle, &state).await?;
 
-    let ap_user = db::ap_users::read_by_username(
-        &mut tx,
-        crate::federation::webfinger::Resource::parse_handle(&handle, &state.base_url)?,
+    profile(
+        tx,
+        ap_user,
+        auth_user.as_ref(),
+        LookupUser::default(),
+        FormErrors::default(),
     )
-    .await?;
+    .await
+}
+
+async fn profile(
+    mut tx: AppTx,
+    ap_user: db::ApUser,
+    auth_user: Option<&AuthUser>,
+    lookup_input: LookupUser,
+    lookup_errors: FormErrors,
+) -> ResponseResult<HtmfResponse> {
+    let mut layout = layout::Template::from_db(&mut tx, auth_user).await?;
+
     let maybe_user = db::users::by_ap_user_id(&mut tx, ap_user.id).await?;
-    let public_lists = if let Some(user) = maybe_user {
-        db::lists::list_public_by_user(&mut tx, user.ap_user_id).await?
+    let (public_lists, follows) = if let Some(user) = maybe_user {
+        layout.feeds.push(Feed {
+            title: format!("{}'s bookmarks", ap_user.username),
+            path: format!("/user/{}/feed", ap_user.username),
+        });
+        let follows = views::profile::Follows {
+            followers: db::ap_users::list_followers(&mut tx, user.ap_user_id).await?,
+            following: db::ap_users::list_following(&mut tx, user.ap_user_id).await?,
+        };
+        (
+            db::lists::list_public_by_user(&mut tx, user.ap_user_id).await?,
+            Some(follows),
+        )
     } else {
-        Vec::new()
+        (Vec::new(), None)
+    };
+
+    let (follow_status, lookup) = match auth_user {
+        Some(auth_user) if auth_user.ap_user_id == ap_user.id => (
+            None,
+            Some(views::profile::Lookup {
+                input: lookup_input,
+                errors: lookup_errors,
+            }),
+        ),
+        Some(auth_user) => {
+            let follow = db::follows::by_ids(&mut tx, auth_user.ap_user_id, ap_user.id).await?;
+            let status = match follow {
+                None => FollowStatus::NotFollowing,
+                Some(follow) if follow.accepted => FollowStatus::Following,
+                Some(_) => FollowStatus::Requested,
+            };
+            (Some(status), None)
+        }
+        None => (None, None),
     };
 
     let elem = views::profile::view(
@@ -239,6 +292,9 @@ async fn get_profile(
             layout,
             ap_user,
             public_lists,
+            follows,
+            follow_status,
+            lookup,
         },
     )
     .await?;
@@ -246,6 +302,127 @@ async fn get_profile(
     Ok(HtmfResponse(elem))
 }
 
+/// Find a user by their handle, which can be a remote handle like
+/// `rafael@ties.pub` or the username of a local user.
+async fn read_by_handle(
+    tx: &mut AppTx,
+    handle: &str,
+    state: &AppState,
+) -> ResponseResult<db::ApUser> {
+    db::ap_users::read_by_username(
+        tx,
+        federation::webfinger::Resource::parse_handle(handle, &state.base_url)?,
+    )
+    .await
+}
+
+/// Look up a user on another server using webfinger, and show their profile.
+async fn post_lookup(
+    extract::Tx(mut tx): extract::Tx,
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    QsForm(input): QsForm<LookupUser>,
+) -> ResponseResult<Response> {
+    let ap_user = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+    if let Err(errors) = input.validate() {
+        return Ok(profile(tx, ap_user, Some(&auth_user), input, errors.into())
+            .await?
+            .into_response());
+    }
+
+    let handle = input.handle.trim().trim_start_matches('@');
+    let resource = federation::webfinger::Resource::parse_handle(handle, &state.base_url)?;
+    let found = webfinger_resolve_actor::<federation::Context, db::ApUser>(
+        &format!("{}@{}", resource.name, resource.domain),
+        &state.federation_config.to_request_data(),
+    )
+    .await;
+
+    match found {
+        Ok(found) => Ok(Redirect::to(&format!("/user/{}", found.handle())).into_response()),
+        Err(error) => {
+            tracing::debug!(?error, "Could not look up user");
+            let mut errors = Report::new();
+            errors.append(
+                garde::Path::new("handle"),
+                garde::Error::new("Could not find this user"),
+            );
+            Ok(profile(tx, ap_user, Some(&auth_user), input, errors.into())
+                .await?
+                .into_response())
+        }
+    }
+}
+
+/// Follow local users right away, and send a follow request to users on other
+/// servers.
+async fn post_follow(
+    extract::Tx(mut tx): extract::Tx,
+    Path(handle): Path<String>,
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> ResponseResult<Redirect> {
+    let followed = read_by_handle(&mut tx, &handle, &state).await?;
+    if followed.id == auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+    let follower = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+    let insert = db::follows::Insert {
+        follower_id: follower.id,
+        following_id: followed.id,
+    };
+
+    if db::users::by_ap_user_id(&mut tx, followed.id)
+        .await?
+        .is_some()
+    {
+        db::follows::upsert(&mut tx, insert).await?;
+        tx.commit().await?;
+    } else {
+        db::follows::insert_pending(&mut tx, insert).await?;
+        // Commit first, since the other server might accept the request before
+        // the follow activity is sent completely
+        tx.commit().await?;
+
+        let federation_data = state.federation_config.to_request_data();
+        federation::Follow::new(&follower, &followed, &federation_data)?
+            .send(&follower, &followed, &federation_data)
+            .await?;
+    }
+
+    Ok(Redirect::to(&format!("/user/{}", followed.handle())))
+}
+
+async fn post_unfollow(
+    extract::Tx(mut tx): extract::Tx,
+    Path(handle): Path<String>,
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> ResponseResult<Redirect> {
+    let followed = read_by_handle(&mut tx, &handle, &state).await?;
+    let follower = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+    db::follows::remove(
+        &mut tx,
+        db::follows::Insert {
+            follower_id: follower.id,
+            following_id: followed.id,
+        },
+    )
+    .await?;
+    let is_local = db::users::by_ap_user_id(&mut tx, followed.id)
+        .await?
+        .is_some();
+    tx.commit().await?;
+
+    if !is_local {
+        let federation_data = state.federation_config.to_request_data();
+        let follow = federation::Follow::new(&follower, &followed, &federation_data)?;
+        federation::UndoFollow::send(&follower, follow, &federation_data).await?;
+    }
+
+    Ok(Redirect::to(&format!("/user/{}", followed.handle())))
+}
+
 async fn logout(auth_user: AuthUser) -> ResponseResult<Redirect> {
     auth_user.logout().await?;
     Ok(Redirect::to("/login"))
@@ -25,6 +25,8 @@ pub struct AppState {
     pub demo_mode: bool,
     pub oidc_state: oidc::State,
     pub federation_config: FederationConfig<federation::Context>,
+    pub archive_client: archive::Client,
+    pub archive_resource_store: archive::ResourceStore,
     pub archive_queue: archive::QueueHandle,
 }
 
@@ -37,6 +39,13 @@ pub async fn app(state: AppState) -> anyhow::Result<Router> {
             .continuously_delete_expired(tokio::time::Duration::from_secs(6 * 60 * 60)),
     );
 
+    tokio::task::spawn(
+        state
+            .archive_resource_store
+            .clone()
+            .continuously_delete_unused(state.pool.clone()),
+    );
+
     if state.demo_mode {
         tokio::task::spawn(periodically_wipe_all_data(state.pool.clone()));
     }
@@ -62,10 +71,16 @@ pub async fn app(state: AppState) -> anyhow::Result<Router> {
         .merge(routes::bookmarks::router())
         .merge(routes::links::router())
         .merge(routes::federation::router())
+        .merge(routes::nodeinfo::router())
+        .merge(routes::feeds::router())
+        .merge(routes::feed_subscriptions::router())
         .merge(routes::search::router())
+        .merge(routes::import::router())
+        .merge(routes::export::router())
+        .merge(routes::settings::router())
+        .merge(routes::timeline::router())
+        .merge(routes::api::router())
         .merge(routes::assets::router().with_state(()))
-        // TODO add layer to use the same URL for AP and HTML
-        // this should simplify things and be more error tolerant for other services
         .layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
@@ -87,7 +102,15 @@ pub async fn app(state: AppState) -> anyhow::Result<Router> {
             .layer(LiveReloadLayer::new().request_predicate(not_htmx_request_livereload_predicate));
     }
 
-    Ok(router.with_state(state))
+    // Layers added to a router run after routing, so wrap the whole router to
+    // rewrite URLs of ActivityPub requests before they're routed
+    let negotiation = axum::middleware::from_fn_with_state(
+        state.clone(),
+        federation::content_negotiation::negotiate,
+    );
+    Ok(Router::new()
+        .fallback_service(router.with_state(state))
+        .layer(negotiation))
 }
 
 pub async fn start(
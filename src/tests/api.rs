@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+
+use axum::http::{Method, StatusCode, header};
+use serde_json::{Value, json};
+
+use crate::{
+    db::{self, api_tokens::Scope},
+    forms::api_tokens::CreateApiToken,
+    tests::util::test_app::TestApp,
+};
+
+/// Create a token on the settings page and return it.
+async fn create_token(app: &mut TestApp, scope: Scope) -> String {
+    let settings = app.req().get("/").await.test_page().await;
+    let settings = settings.visit_link("Settings").await;
+    let created = settings
+        .fill_form(
+            "#create_token",
+            &CreateApiToken {
+                name: "Script".to_string(),
+                scope,
+            },
+        )
+        .await
+        .test_page()
+        .await;
+
+    created.dom.find("#created_token code").text()
+}
+
+#[test_log::test(tokio::test)]
+async fn manage_bookmarks_and_lists_with_token() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    app.login_test_user().await;
+    let token = create_token(&mut app, Scope::Write).await;
+    let bearer = format!("Bearer {token}");
+
+    let list: Value = app
+        .req()
+        .header(header::AUTHORIZATION, &bearer)
+        .expect_status(StatusCode::CREATED)
+        .json(Method::POST, "/api/v1/lists", &json!({"title": "Rust"}))
+        .await
+        .json()
+        .await;
+    let list_id = list["id"].as_str().unwrap();
+
+    let bookmark: Value = app
+        .req()
+        .header(header::AUTHORIZATION, &bearer)
+        .expect_status(StatusCode::CREATED)
+        .json(
+            Method::POST,
+            "/api/v1/bookmarks",
+            &json!({
+                "url": "https://tokio.rs/",
+                "title": "Tokio",
+                "lists": [list_id],
+            }),
+        )
+        .await
+        .json()
+        .await;
+    let bookmark_id = bookmark["id"].as_str().unwrap();
+
+    let list: Value = app
+        .req()
+        .header(header::AUTHORIZATION, &bearer)
+        .get(&format!("/api/v1/lists/{list_id}"))
+        .await
+        .json()
+        .await;
+    assert_eq!(list["items"][0]["type"], "bookmark");
+    assert_eq!(list["items"][0]["id"], bookmark_id);
+
+    let bookmark: Value = app
+        .req()
+        .header(header::AUTHORIZATION, &bearer)
+        .json(
+            Method::PUT,
+            &format!("/api/v1/bookmarks/{bookmark_id}"),
+            &json!({
+                "url": "https://tokio.rs/",
+                "title": "Tokio runtime",
+                "notes": "Async Rust",
+            }),
+        )
+        .await
+        .json()
+        .await;
+    assert_eq!(bookmark["title"], "Tokio runtime");
+
+    let results: Value = app
+        .req()
+        .header(header::AUTHORIZATION, &bearer)
+        .get("/api/v1/search?q=runtime")
+        .await
+        .json()
+        .await;
+    assert_eq!(results["total_count"], 1);
+    assert_eq!(results["items"][0]["id"], bookmark_id);
+
+    app.req()
+        .header(header::AUTHORIZATION, &bearer)
+        .expect_status(StatusCode::NO_CONTENT)
+        .delete(&format!("/api/v1/bookmarks/{bookmark_id}"))
+        .await;
+    let bookmarks: Value = app
+        .req()
+        .header(header::AUTHORIZATION, &bearer)
+        .get("/api/v1/bookmarks")
+        .await
+        .json()
+        .await;
+    assert_eq!(bookmarks, json!([]));
+
+    let settings = app.req().get("/settings").await.test_page().await;
+    assert!(settings.dom.find("#tokens").text().contains("last used on"));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn read_token_cannot_write() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let token = create_token(&mut app, Scope::Read).await;
+    let bearer = format!("Bearer {token}");
+
+    app.req()
+        .header(header::AUTHORIZATION, &bearer)
+        .get("/api/v1/lists")
+        .await;
+    app.req()
+        .header(header::AUTHORIZATION, &bearer)
+        .expect_status(StatusCode::FORBIDDEN)
+        .json(Method::POST, "/api/v1/lists", &json!({"title": "Rust"}))
+        .await;
+
+    let mut tx = app.tx().await;
+    assert!(
+        db::lists::list_by_ap_user_id(&mut tx, user.ap_user_id)
+            .await?
+            .is_empty()
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn revoked_and_unknown_tokens_are_rejected() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    app.login_test_user().await;
+    let token = create_token(&mut app, Scope::Read).await;
+
+    app.req()
+        .header(header::AUTHORIZATION, "Bearer ties_unknown")
+        .expect_status(StatusCode::UNAUTHORIZED)
+        .get("/api/v1/bookmarks")
+        .await;
+    app.req()
+        .expect_status(StatusCode::UNAUTHORIZED)
+        .get("/api/v1/bookmarks")
+        .await;
+
+    let settings = app.req().get("/settings").await.test_page().await;
+    assert!(settings.dom.find("#tokens").text().contains("never used"));
+    settings
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form("#tokens form", &HashMap::<String, String>::new())
+        .await;
+
+    app.req()
+        .header(header::AUTHORIZATION, format!("Bearer {token}"))
+        .expect_status(StatusCode::UNAUTHORIZED)
+        .get("/api/v1/bookmarks")
+        .await;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn other_users_items_are_not_found() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    app.login_test_user().await;
+    let other_user = app.create_user("other", "testpassword").await;
+    let bookmark = app.create_bookmark(&other_user, "https://tokio.rs/").await;
+    let list = app.create_list(&other_user, "Rust").await;
+    let token = create_token(&mut app, Scope::Write).await;
+    let bearer = format!("Bearer {token}");
+
+    app.req()
+        .header(header::AUTHORIZATION, &bearer)
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&format!("/api/v1/bookmarks/{}", bookmark.id))
+        .await;
+    app.req()
+        .header(header::AUTHORIZATION, &bearer)
+        .expect_status(StatusCode::NOT_FOUND)
+        .delete(&format!("/api/v1/bookmarks/{}", bookmark.id))
+        .await;
+    app.req()
+        .header(header::AUTHORIZATION, &bearer)
+        .expect_status(StatusCode::NOT_FOUND)
+        .json(
+            Method::PATCH,
+            &format!("/api/v1/lists/{}", list.id),
+            &json!({"title": "Mine now"}),
+        )
+        .await;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn pinboard_clients() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let token = create_token(&mut app, Scope::Write).await;
+    let api = |path: &str| format!("/api/pinboard/v1/{path}&auth_token=test:{token}&format=json");
+
+    app.req()
+        .expect_status(StatusCode::UNAUTHORIZED)
+        .get("/api/pinboard/v1/posts/all?format=json")
+        .await;
+    // Pinboard defaults to XML, which isn't supported
+    app.req()
+        .expect_status(StatusCode::UNPROCESSABLE_ENTITY)
+        .get(&format!(
+            "/api/pinboard/v1/posts/all?auth_token=test:{token}"
+        ))
+        .await;
+
+    let result: Value = app
+        .req()
+        .get(&api(
+            "posts/add?url=https://tokio.rs/&description=Tokio&tags=rust%20async&toread=yes",
+        ))
+        .await
+        .json()
+        .await;
+    assert_eq!(result, json!({"result_code": "done"}));
+
+    let posts: Value = app.req().get(&api("posts/all?tag=rust")).await.json().await;
+    assert_eq!(posts[0]["href"], "https://tokio.rs/");
+    assert_eq!(posts[0]["description"], "Tokio");
+    assert_eq!(posts[0]["tags"], "rust async");
+    assert_eq!(posts[0]["toread"], "yes");
+    assert_eq!(posts[0]["shared"], "no");
+
+    let tags: Value = app.req().get(&api("tags/get?")).await.json().await;
+    assert_eq!(tags, json!({"async": 1, "rust": 1}));
+
+    let result: Value = app
+        .req()
+        .get(&api(
+            "posts/add?url=https://tokio.rs/&description=Tokio&replace=no",
+        ))
+        .await
+        .json()
+        .await;
+    assert_eq!(result, json!({"result_code": "item already exists"}));
+
+    let update: Value = app.req().get(&api("posts/update?")).await.json().await;
+    assert_ne!(update["update_time"], "1970-01-01T00:00:00Z");
+
+    // Lists organized in ties aren't removed when a client replaces the tags
+    let list = app.create_list(&user, "Reading list").await;
+    let bookmark = db::bookmarks::by_url(&mut app.tx().await, user.ap_user_id, "https://tokio.rs/")
+        .await?
+        .expect("bookmark should exist");
+    let mut tx = app.tx().await;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    app.req()
+        .get(&api(
+            "posts/add?url=https://tokio.rs/&description=Tokio%20runtime&tags=rust",
+        ))
+        .await;
+    let posts: Value = app
+        .req()
+        .get(&api("posts/get?url=https://tokio.rs/"))
+        .await
+        .json()
+        .await;
+    assert_eq!(posts["posts"][0]["description"], "Tokio runtime");
+    assert_eq!(posts["posts"][0]["tags"], "Reading_list rust");
+    assert_eq!(posts["posts"][0]["toread"], "no");
+
+    let result: Value = app
+        .req()
+        .get(&api("posts/delete?url=https://tokio.rs/"))
+        .await
+        .json()
+        .await;
+    assert_eq!(result, json!({"result_code": "done"}));
+    let posts: Value = app.req().get(&api("posts/recent?")).await.json().await;
+    assert_eq!(posts["posts"], json!([]));
+
+    Ok(())
+}
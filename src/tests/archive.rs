@@ -1,22 +1,33 @@
-use crate::{archive, db, tests::util::test_app::TestApp};
+use std::time::Duration;
+
+use axum::http::{StatusCode, header};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{archive, db, forms::links::CreateLink, tests::util::test_app::TestApp};
 
 #[test_log::test(tokio::test)]
 #[ignore = "Test depends on an external resource and should only be run manually."]
 async fn flaky_test_get_website() -> anyhow::Result<()> {
-    let text = archive::fetch_url_as_text("https://rafa.ee").await?;
+    let client = archive::Client::new(archive::Config::default())?;
+    let text = client.fetch_url_as_text("https://rafa.ee").await?;
     assert!(!text.is_empty());
 
-    let image_err = archive::fetch_url_as_text("https://www.rafa.ee/portrait.jpg").await;
+    let image_err = client
+        .fetch_url_as_text("https://www.rafa.ee/portrait.jpg")
+        .await;
     assert!(image_err.is_err());
 
-    let blocked_ip_err = archive::fetch_url_as_text("https://localhost:8080").await;
+    let blocked_ip_err = client.fetch_url_as_text("https://localhost:8080").await;
     assert!(blocked_ip_err.is_err());
 
-    let text = archive::fetch_url_as_text("https://google.com").await?;
+    let text = client.fetch_url_as_text("https://google.com").await?;
     dbg!(&text);
     assert!(!text.is_empty());
 
-    let text = archive::fetch_url_as_text("https://github.com/ArthurTent/ShaderAmp").await?;
+    let text = client
+        .fetch_url_as_text("https://github.com/ArthurTent/ShaderAmp")
+        .await?;
     assert!(!text.is_empty());
 
     Ok(())
@@ -25,18 +36,19 @@ async fn flaky_test_get_website() -> anyhow::Result<()> {
 #[test_log::test(tokio::test)]
 #[ignore = "Test depends on an external resource and should only be run manually."]
 async fn flaky_test_readability() -> anyhow::Result<()> {
+    let client = archive::Client::new(archive::Config::default())?;
     let url = "https://rafa.ee";
-    let text = archive::fetch_url_as_text(url).await?;
+    let text = client.fetch_url_as_text(url).await?;
     let readable = archive::make_readable(url.parse()?, &text)?;
     dbg!(readable);
 
     let url = "https://google.com";
-    let text = archive::fetch_url_as_text(url).await?;
+    let text = client.fetch_url_as_text(url).await?;
     let readable = archive::make_readable(url.parse()?, &text)?;
     dbg!(readable);
 
     let url = "https://github.com/ArthurTent/ShaderAmp";
-    let text = archive::fetch_url_as_text(url).await?;
+    let text = client.fetch_url_as_text(url).await?;
     let readable = archive::make_readable(url.parse()?, &text)?;
     dbg!(readable);
 
@@ -56,10 +68,16 @@ async fn flaky_test_archive_queue() -> anyhow::Result<()> {
     let archive = db::archives::insert_pending(&mut tx, bookmark.id).await?;
     tx.commit().await?;
 
-    let queue = archive::QueueHandle::new(app.pool.clone());
+    let queue = archive::QueueHandle::new(
+        app.pool.clone(),
+        app.state.archive_client.clone(),
+        app.state.archive_resource_store.clone(),
+        false,
+        1,
+    );
 
     let processed = queue.wait_until_archive_processed(archive.id);
-    queue.archive_in_background(archive.id);
+    queue.wake_worker();
     assert!(processed.await);
 
     let mut tx = app.tx().await;
@@ -83,7 +101,13 @@ async fn flaky_test_archive_queue_dangling_pending() -> anyhow::Result<()> {
     let archive = db::archives::insert_pending(&mut tx, bookmark.id).await?;
     tx.commit().await?;
 
-    let queue = archive::QueueHandle::new(app.pool.clone());
+    let queue = archive::QueueHandle::new(
+        app.pool.clone(),
+        app.state.archive_client.clone(),
+        app.state.archive_resource_store.clone(),
+        false,
+        1,
+    );
 
     let processed = queue.wait_until_archive_processed(archive.id).await;
     assert!(processed);
@@ -96,3 +120,387 @@ async fn flaky_test_archive_queue_dangling_pending() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test_log::test(tokio::test)]
+async fn archive_jobs_are_claimed_once_and_retried() -> anyhow::Result<()> {
+    let app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let bookmark = app.create_bookmark(&user, "https://example.com").await;
+    let lease = Duration::from_secs(60);
+
+    let mut tx = app.tx().await;
+    let archive = db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    tx.commit().await?;
+
+    let mut tx = app.tx().await;
+    let job = db::archive_jobs::claim_due(&mut tx, lease)
+        .await?
+        .expect("Job should be due");
+    assert_eq!(job.archive_id, archive.id);
+    assert_eq!(job.attempts, 1);
+
+    // Other workers skip jobs that are being claimed right now
+    let mut other_tx = app.tx().await;
+    assert!(
+        db::archive_jobs::claim_due(&mut other_tx, lease)
+            .await?
+            .is_none()
+    );
+    drop(other_tx);
+    tx.commit().await?;
+
+    // Claimed jobs aren't due again until their lease runs out
+    let mut tx = app.tx().await;
+    assert!(db::archive_jobs::claim_due(&mut tx, lease).await?.is_none());
+
+    db::archive_jobs::retry_later(
+        &mut tx,
+        archive.id,
+        &archive::Error::Timeout,
+        Duration::ZERO,
+    )
+    .await?;
+    let job = db::archive_jobs::claim_due(&mut tx, lease)
+        .await?
+        .expect("Job should be due again");
+    assert_eq!(job.attempts, 2);
+    assert!(matches!(
+        job.last_error.map(|error| error.0),
+        Some(archive::Error::Timeout)
+    ));
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn permanent_archive_errors_are_not_retried() -> anyhow::Result<()> {
+    let app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let bookmark = app.create_bookmark(&user, "ftp://example.com/file").await;
+    let queue = archive::QueueHandle::new(
+        app.pool.clone(),
+        app.state.archive_client.clone(),
+        app.state.archive_resource_store.clone(),
+        false,
+        1,
+    );
+
+    let mut tx = app.tx().await;
+    let archive = db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    tx.commit().await?;
+
+    let (processed, ()) = tokio::join!(queue.wait_until_archive_processed(archive.id), async {
+        queue.wake_worker();
+    });
+    assert!(processed);
+
+    let mut tx = app.tx().await;
+    let archive = db::archives::by_id(&mut tx, archive.id).await?;
+    assert_eq!(archive.status, db::archives::Status::Error);
+    assert!(matches!(
+        archive.error.map(|error| error.0),
+        Some(archive::Error::UnsupportedScheme { .. })
+    ));
+    assert!(
+        db::archive_jobs::by_archive_id(&mut tx, archive.id)
+            .await?
+            .is_none()
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn archive_jobs_give_up_after_too_many_attempts() -> anyhow::Result<()> {
+    let app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let bookmark = app.create_bookmark(&user, "https://example.com").await;
+    let queue = archive::QueueHandle::new(
+        app.pool.clone(),
+        app.state.archive_client.clone(),
+        app.state.archive_resource_store.clone(),
+        false,
+        1,
+    );
+
+    let mut tx = app.tx().await;
+    let archive = db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    // Earlier attempts stopped without storing a result
+    sqlx::query("update archive_jobs set attempts = 5 where archive_id = $1")
+        .bind(archive.id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    let (processed, ()) = tokio::join!(queue.wait_until_archive_processed(archive.id), async {
+        queue.wake_worker();
+    });
+    assert!(processed);
+
+    let mut tx = app.tx().await;
+    let archive = db::archives::by_id(&mut tx, archive.id).await?;
+    assert_eq!(archive.status, db::archives::Status::Error);
+    assert!(
+        db::archive_jobs::by_archive_id(&mut tx, archive.id)
+            .await?
+            .is_none()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn transient_archive_errors() {
+    assert!(archive::Error::Timeout.is_transient());
+    assert!(
+        archive::Error::Status {
+            status: Some(503),
+            retry_after_secs: None
+        }
+        .is_transient()
+    );
+    assert!(
+        archive::Error::Status {
+            status: Some(429),
+            retry_after_secs: None
+        }
+        .is_transient()
+    );
+    assert!(
+        !archive::Error::Status {
+            status: Some(404),
+            retry_after_secs: None
+        }
+        .is_transient()
+    );
+    assert!(
+        !archive::Error::UnsupportedScheme {
+            scheme: "ftp".to_string()
+        }
+        .is_transient()
+    );
+    assert!(!archive::Error::DisallowedByRobotsTxt.is_transient());
+    assert!(!archive::Error::TooManyRedirects.is_transient());
+}
+
+#[test_log::test(tokio::test)]
+#[ignore = "Test depends on an external resource and should only be run manually."]
+async fn flaky_test_robots_txt() -> anyhow::Result<()> {
+    let client = archive::Client::new(archive::Config {
+        respect_robots_txt: true,
+        ..Default::default()
+    })?;
+
+    // GitHub doesn't allow unknown crawlers to visit commit histories
+    let disallowed = client
+        .fetch_url_as_text("https://github.com/raffomania/ties/commits/main")
+        .await;
+    assert!(matches!(
+        disallowed,
+        Err(archive::Error::DisallowedByRobotsTxt)
+    ));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn archived_images() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let bookmark = app.create_bookmark(&user, "https://example.com").await;
+    let image = archive::Resource {
+        url: "https://example.com/image.png".to_string(),
+        hash: "0123abcd".to_string(),
+        content_type: "image/png".to_string(),
+        data: b"not really a png".to_vec(),
+    };
+
+    let mut tx = app.tx().await;
+    let archive = db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    app.state
+        .archive_resource_store
+        .save(&mut tx, archive.id, &image)
+        .await?;
+    tx.commit().await?;
+
+    app.login_test_user().await;
+    let response = app.req().get("/archive_resources/0123abcd").await;
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+    assert_eq!(response.text().await, "not really a png");
+
+    // Other users can only see images of public bookmarks
+    app.create_user("otheruser", "otherpassword").await;
+    app.login_user("otheruser", "otherpassword").await;
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get("/archive_resources/0123abcd")
+        .await;
+    let list = app.create_list(&user, "Public list").await;
+    let mut tx = app.tx().await;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+    let response = app.req().get("/archive_resources/0123abcd").await;
+    assert_eq!(response.text().await, "not really a png");
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get("/archive_resources/unknown")
+        .await;
+
+    // Images can also be stored in a directory
+    let directory = std::env::temp_dir().join(format!("ties-test-{}", Uuid::new_v4()));
+    let store = archive::ResourceStore::Directory(directory.clone());
+    let other_image = archive::Resource {
+        url: "https://example.com/image.gif".to_string(),
+        hash: "4567ef".to_string(),
+        content_type: "image/gif".to_string(),
+        data: b"not really a gif".to_vec(),
+    };
+    let mut tx = app.tx().await;
+    store.save(&mut tx, archive.id, &other_image).await?;
+    tx.commit().await?;
+    assert!(directory.join("4567ef").exists());
+    let mut tx = app.tx().await;
+    let (content_type, data) = store.load(&mut tx, "4567ef").await?;
+    assert_eq!(content_type, "image/gif");
+    assert_eq!(data, other_image.data);
+    tx.commit().await?;
+
+    // Images are deleted once no archive uses them anymore
+    let mut tx = app.tx().await;
+    db::bookmarks::delete_by_id(&mut tx, bookmark.id).await?;
+    tx.commit().await?;
+    store.delete_unused(&app.pool).await?;
+    assert!(!directory.join("4567ef").exists());
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get("/archive_resources/0123abcd")
+        .await;
+
+    std::fs::remove_dir_all(directory)?;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn archive_exports() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let bookmark = app.create_bookmark(&user, "https://example.com").await;
+    let list = app.create_list(&user, "Reading list").await;
+
+    let mut tx = app.tx().await;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    let archive = db::archives::insert_restored(
+        &mut tx,
+        bookmark.id,
+        OffsetDateTime::now_utc(),
+        db::archives::Status::Success,
+        None,
+        Some(r#"<p>Hello archive</p><img src="/archive_resources/0123abcd">"#),
+    )
+    .await?;
+    app.state
+        .archive_resource_store
+        .save(
+            &mut tx,
+            archive.id,
+            &archive::Resource {
+                url: "https://example.com/image.png".to_string(),
+                hash: "0123abcd".to_string(),
+                content_type: "image/png".to_string(),
+                data: b"not really a png".to_vec(),
+            },
+        )
+        .await?;
+    db::archive_responses::insert(
+        &mut tx,
+        archive.id,
+        &archive::RawResponse {
+            url: "https://example.com/".to_string(),
+            status: 200,
+            headers: vec![("content-type".to_string(), "text/html".to_string())],
+            body: b"<html><body><p>Hello archive</p></body></html>".to_vec(),
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    let bookmark_page = app.req().get(&bookmark.path()).await.test_page().await;
+    assert!(!bookmark_page.dom.find("#archive_downloads").is_empty());
+
+    let path = format!("/bookmarks/{}/archives/{}", bookmark.id, archive.id);
+    let response = app.req().get(&format!("{path}/archive.warc")).await;
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/warc");
+    let warc = response.text().await;
+    assert!(warc.starts_with("WARC/1.1\r\nWARC-Record-ID: <urn:uuid:"));
+    assert!(warc.contains("WARC-Type: warcinfo"));
+    assert!(warc.contains("WARC-Type: response"));
+    assert!(warc.contains("HTTP/1.1 200 OK\r\ncontent-type: text/html\r\n\r\n<html>"));
+    assert!(warc.contains("WARC-Type: conversion"));
+    assert!(warc.contains("WARC-Refers-To: <urn:uuid:"));
+    // Images point to the website again, where their resource record is found
+    assert!(warc.contains(r#"<img src="https://example.com/image.png">"#));
+    assert!(warc.contains("WARC-Type: resource"));
+    assert!(warc.contains("WARC-Target-URI: https://example.com/image.png"));
+
+    // Images are inlined into the single HTML file
+    let html = app
+        .req()
+        .get(&format!("{path}/archive.html"))
+        .await
+        .text()
+        .await;
+    assert!(html.contains("Hello archive"));
+    assert!(html.contains(r#"<img src="data:image/png;base64,bm90IHJlYWxseSBhIHBuZw==">"#));
+
+    // All archives of a list can be exported at once
+    let list_page = app.req().get(&list.path()).await.test_page().await;
+    assert!(!list_page.dom.find("#archive_exports").is_empty());
+    let list_html = app
+        .req()
+        .get(&format!("{}/archives.html", list.path()))
+        .await
+        .text()
+        .await;
+    assert!(list_html.contains("Reading list"));
+    assert!(list_html.contains("Hello archive"));
+    let list_warc = app
+        .req()
+        .get(&format!("{}/archives.warc", list.path()))
+        .await
+        .text()
+        .await;
+    assert!(list_warc.contains("WARC-Target-URI: https://example.com"));
+
+    // Exporting a whole list is only available to its owner, even if the
+    // list is public
+    app.create_user("otheruser", "otherpassword").await;
+    app.login_user("otheruser", "otherpassword").await;
+    let list_page = app.req().get(&list.path()).await.test_page().await;
+    assert!(list_page.dom.find("#archive_exports").is_empty());
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&format!("{}/archives.warc", list.path()))
+        .await;
+
+    Ok(())
+}
@@ -1,6 +1,12 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use time::OffsetDateTime;
+
 use crate::{
     db::{self, bookmarks::InsertBookmark},
-    forms::{links::CreateLink, lists::CreateList},
+    forms::{bookmarks::EditBookmark, links::CreateLink, lists::CreateList},
+    response_error::ResponseError,
     tests::util::test_app::TestApp,
 };
 
@@ -46,6 +52,7 @@ async fn is_bookmark_public() -> anyhow::Result<()> {
         InsertBookmark {
             url: String::new(),
             title: String::new(),
+            notes: None,
         },
         &app.base_url,
     )
@@ -99,3 +106,281 @@ async fn is_bookmark_public() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test_log::test(tokio::test)]
+async fn edit_bookmark() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let bookmark = app.create_bookmark(&user, "https://example.com/old").await;
+
+    let mut tx = app.tx().await;
+    let old_archive = db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    tx.commit().await?;
+
+    let bookmark_page = app.req().get(&bookmark.path()).await.test_page().await;
+    let edit_page = bookmark_page.visit_link("/edit").await;
+
+    let title_input = edit_page.dom.find("form#edit_bookmark input[name='title']");
+    assert_eq!(
+        title_input.attr("value").map(|v| v.to_string()),
+        Some("https://example.com/old".to_string())
+    );
+
+    edit_page
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form(
+            "form#edit_bookmark",
+            &EditBookmark {
+                url: "https://example.com/new".to_string(),
+                title: "New title".to_string(),
+                notes: Some("Read *this* later".to_string()),
+            },
+        )
+        .await;
+
+    let mut tx = app.tx().await;
+    let edited = db::bookmarks::by_id(&mut tx, bookmark.id).await?;
+    assert_eq!(edited.url, "https://example.com/new");
+    assert_eq!(edited.title, "New title");
+    assert_eq!(edited.notes.as_deref(), Some("Read *this* later"));
+
+    // Changing the URL archives the new page, and keeps the old snapshot
+    let snapshots = db::archives::list_snapshots(&mut tx, bookmark.id).await?;
+    assert_eq!(snapshots.len(), 2);
+    assert_ne!(snapshots[0].id, old_archive.id);
+    assert_eq!(snapshots[1].id, old_archive.id);
+    tx.commit().await?;
+
+    let bookmark_page = app.req().get(&bookmark.path()).await.test_page().await;
+    assert!(
+        bookmark_page
+            .dom
+            .find("header .prose")
+            .html()
+            .contains("Read <em>this</em> later")
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn edit_bookmark_validates_input() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let other_user = app.create_user("otheruser", "otherpassword").await;
+    app.login_test_user().await;
+    let bookmark = app.create_bookmark(&user, "https://example.com").await;
+    let other_bookmark = app
+        .create_bookmark(&other_user, "https://example.org")
+        .await;
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&format!("/bookmarks/{}/edit", other_bookmark.id))
+        .await;
+
+    let edit_page = app
+        .req()
+        .get(&format!("/bookmarks/{}/edit", bookmark.id))
+        .await
+        .test_page()
+        .await;
+    let error_page = edit_page
+        .fill_form(
+            "form#edit_bookmark",
+            &EditBookmark {
+                url: "not a url".to_string(),
+                title: String::new(),
+                notes: Some("a".repeat(10001)),
+            },
+        )
+        .await
+        .test_page()
+        .await;
+
+    let errors = error_page.dom.find("form#edit_bookmark p.text-red-700");
+    assert_eq!(errors.length(), 3);
+    assert!(errors.text().contains("cannot be empty"));
+
+    let mut tx = app.tx().await;
+    let unchanged = db::bookmarks::by_id(&mut tx, bookmark.id).await?;
+    assert_eq!(unchanged.url, "https://example.com");
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn delete_bookmark() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let other_user = app.create_user("otheruser", "otherpassword").await;
+    app.login_test_user().await;
+    let list = app.create_list(&user, "Reading").await;
+    let bookmark = app.create_bookmark(&user, "https://example.com").await;
+    let other_bookmark = app
+        .create_bookmark(&other_user, "https://example.org")
+        .await;
+
+    let mut tx = app.tx().await;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    db::archives::insert_pending(&mut tx, bookmark.id).await?;
+    tx.commit().await?;
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&format!("/bookmarks/{}/delete", other_bookmark.id))
+        .await;
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .post(
+            &format!("/bookmarks/{}/delete", other_bookmark.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    let bookmark_page = app.req().get(&bookmark.path()).await.test_page().await;
+    let confirm_page = bookmark_page.visit_link("/delete").await;
+    assert!(
+        confirm_page
+            .dom
+            .find("main")
+            .text()
+            .contains("Do you really want to delete")
+    );
+
+    confirm_page
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form("form#confirm_delete", &HashMap::<String, String>::new())
+        .await;
+
+    let mut tx = app.tx().await;
+    assert!(matches!(
+        db::bookmarks::by_id(&mut tx, bookmark.id).await,
+        Err(ResponseError::NotFound)
+    ));
+    assert!(
+        db::archives::by_bookmark_id(&mut tx, bookmark.id)
+            .await?
+            .is_none()
+    );
+    assert_eq!(
+        db::lists::metadata_by_id(&mut tx, list.id)
+            .await?
+            .linked_bookmark_count,
+        0
+    );
+    db::bookmarks::by_id(&mut tx, other_bookmark.id).await?;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn archive_snapshots() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let bookmark = app.create_bookmark(&user, "https://example.com").await;
+    let other_bookmark = app.create_bookmark(&user, "https://example.org").await;
+
+    let mut tx = app.tx().await;
+    let old = db::archives::insert_restored(
+        &mut tx,
+        bookmark.id,
+        OffsetDateTime::now_utc() - time::Duration::days(30),
+        db::archives::Status::Success,
+        None,
+        Some("<h1>Prices</h1><p>Coffee costs 2 euros.</p><p>Tea is free.</p>"),
+    )
+    .await?;
+    let new = db::archives::insert_restored(
+        &mut tx,
+        bookmark.id,
+        OffsetDateTime::now_utc(),
+        db::archives::Status::Success,
+        None,
+        Some("<h1>Prices</h1><p>Coffee costs 3 euros.</p><p>Tea is free.</p>"),
+    )
+    .await?;
+    let other_archive = db::archives::insert_pending(&mut tx, other_bookmark.id).await?;
+    tx.commit().await?;
+
+    // The newest snapshot is shown by default, along with the history
+    let bookmark_page = app.req().get(&bookmark.path()).await.test_page().await;
+    assert!(
+        bookmark_page
+            .dom
+            .find("#archive-contents")
+            .text()
+            .contains("3 euros")
+    );
+    assert_eq!(bookmark_page.dom.find("#snapshots li").length(), 2);
+    assert!(bookmark_page.dom.find("#older_snapshot").is_empty());
+
+    let old_page = app
+        .req()
+        .get(&format!("/bookmarks/{}/archives/{}", bookmark.id, old.id))
+        .await
+        .test_page()
+        .await;
+    assert!(
+        old_page
+            .dom
+            .find("#archive-contents")
+            .text()
+            .contains("2 euros")
+    );
+    assert!(!old_page.dom.find("#older_snapshot").is_empty());
+
+    let diff_page = bookmark_page.visit_link("changes").await;
+    assert_eq!(
+        diff_page.dom.find("#diff .removed").text(),
+        "Coffee costs 2 euros."
+    );
+    assert_eq!(
+        diff_page.dom.find("#diff .added").text(),
+        "Coffee costs 3 euros."
+    );
+    assert!(diff_page.dom.find("#diff").text().contains("Tea is free."));
+
+    // Snapshots of other bookmarks can't be shown or compared
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&format!(
+            "/bookmarks/{}/archives/{}",
+            bookmark.id, other_archive.id
+        ))
+        .await;
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&format!(
+            "/bookmarks/{}/diff?from={}&to={}",
+            bookmark.id, other_archive.id, new.id
+        ))
+        .await;
+
+    // Archiving again keeps the older snapshots
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/bookmarks/{}/archive", bookmark.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+    let mut tx = app.tx().await;
+    let snapshots = db::archives::list_snapshots(&mut tx, bookmark.id).await?;
+    assert_eq!(snapshots.len(), 3);
+    assert_eq!(snapshots[0].status, db::archives::Status::Pending);
+    assert_eq!(snapshots[1].id, new.id);
+    assert_eq!(snapshots[2].id, old.id);
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use crate::{db, forms::links::CreateLink, tests::util::test_app::TestApp};
+
+#[test_log::test(tokio::test)]
+async fn export_netscape_html() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let list = app.create_list(&user, "Rust & friends").await;
+    let bookmark = app.create_bookmark(&user, "https://tokio.rs/").await;
+    let mut tx = app.tx().await;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    let index = app.req().get("/").await.test_page().await;
+    let export_page = index.visit_link("Export my data").await;
+    assert_eq!(export_page.dom.find("#export a").length(), 2);
+
+    let html = app.req().get("/export/bookmarks.html").await.text().await;
+
+    assert!(html.starts_with("<!DOCTYPE NETSCAPE-Bookmark-file-1>"));
+    assert!(html.contains(">Rust &amp; friends</H3>"));
+    assert!(html.contains("<A HREF=\"https://tokio.rs/\""));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn backup_can_be_restored() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+
+    let parent = app.create_list(&user, "Parent").await;
+    let child = app.create_list(&user, "Child").await;
+    let bookmark = app.create_bookmark(&user, "https://tokio.rs/").await;
+    let mut tx = app.tx().await;
+    db::lists::set_private(&mut tx, child.id, true).await?;
+    db::lists::set_pinned(&mut tx, parent.id, true).await?;
+    db::bookmarks::update(
+        &mut tx,
+        bookmark.id,
+        db::bookmarks::InsertBookmark {
+            url: bookmark.url.clone(),
+            title: "Tokio".to_string(),
+            notes: Some("An async runtime".to_string()),
+        },
+    )
+    .await?;
+    db::archives::insert_restored(
+        &mut tx,
+        bookmark.id,
+        bookmark.created_at,
+        db::archives::Status::Success,
+        None,
+        Some("<p>Archived tokio page</p>"),
+    )
+    .await?;
+    for (src, dest) in [(parent.id, child.id), (child.id, bookmark.id)] {
+        db::links::insert(&mut tx, user.id, CreateLink { src, dest }).await?;
+    }
+    tx.commit().await?;
+
+    let backup = app.req().get("/export/backup.json").await.text().await;
+
+    let other_user = app.create_user("otheruser", "otherpassword").await;
+    app.login_user("otheruser", "otherpassword").await;
+    let summary = app
+        .req()
+        .get("/import")
+        .await
+        .test_page()
+        .await
+        .fill_multipart_form(
+            "form#import",
+            &[("format", "backup"), ("file", backup.as_str())],
+        )
+        .await
+        .test_page()
+        .await;
+    let summary_text = summary.dom.find("#import_summary").text();
+    assert!(summary_text.contains("1 bookmark created"));
+    assert!(summary_text.contains("2 lists created"));
+
+    let mut tx = app.tx().await;
+    let restored_parent = db::lists::by_title(&mut tx, other_user.ap_user_id, "Parent")
+        .await?
+        .unwrap();
+    assert!(restored_parent.pinned);
+    assert!(!restored_parent.private);
+    assert_eq!(restored_parent.created_at, parent.created_at);
+    let restored_child = db::lists::by_title(&mut tx, other_user.ap_user_id, "Child")
+        .await?
+        .unwrap();
+    assert!(restored_child.private);
+    assert!(db::links::exists(&mut tx, restored_parent.id, restored_child.id).await?);
+
+    let restored = db::bookmarks::by_url(&mut tx, other_user.ap_user_id, "https://tokio.rs/")
+        .await?
+        .unwrap();
+    assert_ne!(restored.id, bookmark.id);
+    assert_eq!(restored.title, "Tokio");
+    assert_eq!(restored.notes.as_deref(), Some("An async runtime"));
+    assert_eq!(restored.created_at, bookmark.created_at);
+    assert!(db::links::exists(&mut tx, restored_child.id, restored.id).await?);
+
+    let archive = db::archives::by_bookmark_id(&mut tx, restored.id)
+        .await?
+        .unwrap();
+    assert_eq!(archive.status, db::archives::Status::Success);
+    assert_eq!(
+        archive.extracted_html.as_deref(),
+        Some("<p>Archived tokio page</p>")
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn import_rejects_newer_backups() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    app.login_test_user().await;
+
+    let page = app
+        .req()
+        .get("/import")
+        .await
+        .test_page()
+        .await
+        .fill_multipart_form(
+            "form#import",
+            &[("format", "backup"), ("file", r#"{"version": 1000}"#)],
+        )
+        .await
+        .test_page()
+        .await;
+
+    assert!(
+        page.dom
+            .find("form#import")
+            .text()
+            .contains("created by a newer version of ties")
+    );
+
+    Ok(())
+}
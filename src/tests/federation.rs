@@ -1,11 +1,22 @@
-use activitypub_federation::fetch::webfinger::webfinger_resolve_actor;
+use std::collections::HashMap;
+
+use activitypub_federation::{
+    activity_queue::queue_activity,
+    fetch::webfinger::webfinger_resolve_actor,
+    kinds::activity::{FollowType, UndoType},
+    protocol::context::WithContext,
+};
 use anyhow::Result;
-use axum::http::StatusCode;
+use axum::http::{StatusCode, header};
 
 use crate::{
-    db::{self},
+    db::{self, bookmarks::InsertBookmark},
     federation::{self, webfinger},
-    forms::users::{Credentials, Login},
+    forms::{
+        links::CreateLink,
+        users::{Credentials, Login, LookupUser},
+    },
+    response_error::ResponseError,
     tests::util::test_app::TestApp,
 };
 
@@ -81,6 +92,19 @@ async fn can_resolve_bookmark() -> Result<()> {
 
     let user = app_a.create_test_user().await;
     let bookmark = app_a.create_bookmark(&user, "https://rafa.ee").await;
+    // Only public bookmarks can be resolved
+    let list = app_a.create_list(&user, "Public list").await;
+    let mut tx = app_a.tx().await;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
 
     app_a.serve().await;
     let ap_cx_b = app_b.state.federation_config.to_request_data();
@@ -159,3 +183,729 @@ async fn can_follow_undo_follow() -> Result<()> {
 
     Ok(())
 }
+
+#[test_log::test(tokio::test)]
+async fn deleting_bookmark_removes_remote_copies() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let list = app_a.create_list(&user_a, "Public list").await;
+    let bookmark = app_a.create_bookmark(&user_a, "https://rafa.ee").await;
+    let mut tx_a = app_a.tx().await;
+    db::links::insert(
+        &mut tx_a,
+        user_a.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    tx_a.commit().await?;
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    // User B follows user A and has a copy of A's bookmark
+    federation::Follow::new(&ap_user_b, &ap_user_a, &ap_cx_b)?
+        .send(&ap_user_b, &ap_user_a, &ap_cx_b)
+        .await?;
+    let remote_bookmark = bookmark.ap_id.dereference(&ap_cx_b).await?;
+
+    let mut tx_a = app_a.tx().await;
+    let deleted = db::bookmarks::delete_by_id(&mut tx_a, bookmark.id).await?;
+    tx_a.commit().await?;
+    federation::DeleteBookmark::send_to_followers(&ap_user_a, &deleted, &ap_cx_a).await?;
+
+    let mut tx_b = app_b.tx().await;
+    assert!(matches!(
+        db::bookmarks::by_id(&mut tx_b, remote_bookmark.id).await,
+        Err(ResponseError::NotFound)
+    ));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn deleting_list_removes_remote_copies_of_its_bookmarks() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    app_a.login_test_user().await;
+    let list = app_a.create_list(&user_a, "Public list").await;
+    let bookmark = app_a.create_bookmark(&user_a, "https://rafa.ee").await;
+    let mut tx_a = app_a.tx().await;
+    db::links::insert(
+        &mut tx_a,
+        user_a.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    tx_a.commit().await?;
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    federation::Follow::new(&ap_user_b, &ap_user_a, &ap_cx_b)?
+        .send(&ap_user_b, &ap_user_a, &ap_cx_b)
+        .await?;
+    let remote_bookmark = bookmark.ap_id.dereference(&ap_cx_b).await?;
+
+    // The bookmark isn't public without the list
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/lists/{}/delete", list.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    let mut tx_b = app_b.tx().await;
+    assert!(matches!(
+        db::bookmarks::by_id(&mut tx_b, remote_bookmark.id).await,
+        Err(ResponseError::NotFound)
+    ));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn unlinking_bookmark_from_public_list_removes_remote_copies() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    app_a.login_test_user().await;
+    let list = app_a.create_list(&user_a, "Public list").await;
+    let bookmark = app_a.create_bookmark(&user_a, "https://rafa.ee").await;
+    let mut tx_a = app_a.tx().await;
+    let link = db::links::insert(
+        &mut tx_a,
+        user_a.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    tx_a.commit().await?;
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    federation::Follow::new(&ap_user_b, &ap_user_a, &ap_cx_b)?
+        .send(&ap_user_b, &ap_user_a, &ap_cx_b)
+        .await?;
+    let remote_bookmark = bookmark.ap_id.dereference(&ap_cx_b).await?;
+
+    // The bookmark isn't in any public list anymore
+    app_a.req().delete(&format!("/links/{}", link.id)).await;
+
+    let mut tx_b = app_b.tx().await;
+    assert!(matches!(
+        db::bookmarks::by_id(&mut tx_b, remote_bookmark.id).await,
+        Err(ResponseError::NotFound)
+    ));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn linking_bookmark_from_public_list_sends_it_to_followers() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    app_a.login_test_user().await;
+    let list = app_a.create_list(&user_a, "Public list").await;
+    let bookmark = app_a.create_bookmark(&user_a, "https://rafa.ee").await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    federation::Follow::new(&ap_user_b, &ap_user_a, &ap_cx_b)?
+        .send(&ap_user_b, &ap_user_a, &ap_cx_b)
+        .await?;
+
+    // The bookmark was private, so instance B doesn't know it yet
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/links/create",
+            &HashMap::from([
+                ("src", list.id.to_string()),
+                ("dest", bookmark.id.to_string()),
+                ("submitted", "true".to_string()),
+            ]),
+        )
+        .await;
+
+    let mut tx_b = app_b.tx().await;
+    let copy = db::bookmarks::by_ap_id(&mut tx_b, bookmark.ap_id.clone()).await?;
+    assert_eq!(copy.url, "https://rafa.ee");
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn outbox_contains_public_bookmarks() -> Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let public_list = app.create_list(&user, "Rust").await;
+    let private_list = app.create_list(&user, "Secret").await;
+    let mut tx = app.tx().await;
+    db::lists::set_private(&mut tx, private_list.id, true).await?;
+    tx.commit().await?;
+
+    for index in 0..=21 {
+        let bookmark = app
+            .create_bookmark(&user, &format!("https://example.com/{index}"))
+            .await;
+        let mut tx = app.tx().await;
+        let src = if index == 0 {
+            private_list.id
+        } else {
+            public_list.id
+        };
+        db::links::insert(
+            &mut tx,
+            user.id,
+            CreateLink {
+                src,
+                dest: bookmark.id,
+            },
+        )
+        .await?;
+        tx.commit().await?;
+    }
+    let path = |url: &serde_json::Value| {
+        let url = url::Url::parse(url.as_str().unwrap()).unwrap();
+        format!("{}?{}", url.path(), url.query().unwrap_or_default())
+    };
+
+    let outbox: serde_json::Value = app
+        .req()
+        .get(&format!("/ap/outbox/{}", user.ap_user_id))
+        .await
+        .json()
+        .await;
+    assert_eq!(outbox["type"], "OrderedCollection");
+    assert_eq!(outbox["totalItems"], 21);
+
+    let first_page: serde_json::Value = app.req().get(&path(&outbox["first"])).await.json().await;
+    let items = first_page["orderedItems"].as_array().unwrap();
+    assert_eq!(first_page["partOf"], outbox["id"]);
+    assert_eq!(items.len(), 20);
+    assert_eq!(items[0]["type"], "Create");
+    assert_eq!(items[0]["object"]["name"], "https://example.com/21");
+
+    let last_page: serde_json::Value = app.req().get(&path(&first_page["next"])).await.json().await;
+    let items = last_page["orderedItems"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    // The bookmark in the private list is left out
+    assert_eq!(items[0]["object"]["name"], "https://example.com/1");
+    assert!(last_page.get("next").is_none());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn followers_and_following_collections() -> Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let follower = app.create_user("follower", "testpassword").await;
+    let mut tx = app.tx().await;
+    db::follows::upsert(
+        &mut tx,
+        db::follows::Insert {
+            follower_id: follower.ap_user_id,
+            following_id: user.ap_user_id,
+        },
+    )
+    .await?;
+    let follower_ap_user = db::ap_users::read_by_id(&mut tx, follower.ap_user_id).await?;
+    tx.commit().await?;
+
+    let person: serde_json::Value = app
+        .req()
+        .get(&format!("/ap/user/{}", user.ap_user_id))
+        .await
+        .json()
+        .await;
+    let followers_url = format!("{}ap/user/{}/followers", app.base_url, user.ap_user_id);
+    assert_eq!(person["followers"], followers_url);
+
+    let followers: serde_json::Value = app
+        .req()
+        .get(&format!("/ap/user/{}/followers", user.ap_user_id))
+        .await
+        .json()
+        .await;
+    assert_eq!(followers["id"], followers_url);
+    assert_eq!(followers["totalItems"], 1);
+    assert_eq!(
+        followers["orderedItems"],
+        serde_json::json!([follower_ap_user.ap_id.inner()])
+    );
+
+    let following: serde_json::Value = app
+        .req()
+        .get(&format!("/ap/user/{}/following", user.ap_user_id))
+        .await
+        .json()
+        .await;
+    assert_eq!(following["totalItems"], 0);
+
+    let profile = app.req().get("/user/testuser").await.test_page().await;
+    assert_eq!(
+        profile.dom.find("#follow_counts").text(),
+        "1 follower ∙ 0 following"
+    );
+    assert_eq!(profile.dom.find("#followers a").text(), "follower");
+    assert!(profile.dom.find("#following").is_empty());
+
+    let follower_profile = profile.visit_link("follower").await;
+    assert_eq!(
+        follower_profile.dom.find("#follow_counts").text(),
+        "0 followers ∙ 1 following"
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn follow_remote_user_and_receive_bookmarks() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    app_a.login_test_user().await;
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+
+    let own_profile = app_a.req().get("/user/testuser").await.test_page().await;
+    let remote_handle = format!("testuser@{}", app_b.state.federation_config.domain());
+    own_profile
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form(
+            "#lookup_user",
+            &LookupUser {
+                handle: format!("@{remote_handle}"),
+            },
+        )
+        .await;
+
+    let remote_profile = app_a
+        .req()
+        .get(&format!("/user/{remote_handle}"))
+        .await
+        .test_page()
+        .await;
+    assert_eq!(remote_profile.dom.find("#follow button").text(), "Follow");
+    remote_profile
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form("#follow", &HashMap::<String, String>::new())
+        .await;
+
+    // Instance B accepted the follow request right away
+    let mut tx_b = app_b.tx().await;
+    let followers = db::ap_users::list_followers(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+    assert_eq!(followers.len(), 1);
+    let mut tx_a = app_a.tx().await;
+    let following = db::ap_users::list_following(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+    assert_eq!(following.len(), 1);
+    let remote_profile = app_a
+        .req()
+        .get(&format!("/user/{remote_handle}"))
+        .await
+        .test_page()
+        .await;
+    assert_eq!(remote_profile.dom.find("#follow button").text(), "Unfollow");
+
+    // New public bookmarks of user B are stored on instance A
+    let bookmark = app_b.create_bookmark(&user_b, "https://rafa.ee").await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+    federation::CreateBookmark::send_to_followers(&ap_user_b, bookmark.clone(), &ap_cx_b).await?;
+    let mut tx_a = app_a.tx().await;
+    let copy = db::bookmarks::by_ap_id(&mut tx_a, bookmark.ap_id.clone()).await?;
+    drop(tx_a);
+    assert_eq!(copy.url, "https://rafa.ee");
+
+    // Edits update the copy
+    let mut tx_b = app_b.tx().await;
+    let edited = db::bookmarks::update(
+        &mut tx_b,
+        bookmark.id,
+        InsertBookmark {
+            url: "https://rafa.ee".to_string(),
+            title: "Rafael's website".to_string(),
+            notes: None,
+        },
+    )
+    .await?;
+    tx_b.commit().await?;
+    federation::UpdateBookmark::send_to_followers(&ap_user_b, edited, &ap_cx_b).await?;
+    let mut tx_a = app_a.tx().await;
+    let copy = db::bookmarks::by_ap_id(&mut tx_a, bookmark.ap_id.clone()).await?;
+    drop(tx_a);
+    assert_eq!(copy.title, "Rafael's website");
+
+    remote_profile
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form("#follow", &HashMap::<String, String>::new())
+        .await;
+    let mut tx_b = app_b.tx().await;
+    let followers = db::ap_users::list_followers(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+    assert!(followers.is_empty());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn follow_public_list() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let list = app_a.create_list(&user_a, "Rust").await;
+    app_a.login_test_user().await;
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    let group: serde_json::Value = app_a
+        .req()
+        .get(&format!("/ap/list/{}", list.id))
+        .await
+        .json()
+        .await;
+    let list_ap_id = db::list_actors::ap_id(&app_a.base_url, list.id)?;
+    assert_eq!(group["type"], "Group");
+    assert_eq!(group["id"], list_ap_id.as_str());
+    assert_eq!(group["name"], "Rust");
+
+    let webfinger: serde_json::Value = app_a
+        .req()
+        .get(&format!(
+            "/.well-known/webfinger?resource=acct:{}@{}",
+            list.id,
+            app_a.state.federation_config.domain()
+        ))
+        .await
+        .json()
+        .await;
+    assert_eq!(webfinger["links"][0]["href"], list_ap_id.as_str());
+    let list_page = app_a.req().get(&list.path()).await.test_page().await;
+    assert_eq!(
+        list_page.dom.find("#fediverse_handle").text(),
+        format!("@{}@{}", list.id, app_a.state.federation_config.domain())
+    );
+
+    // Follow the list from the other instance, like Mastodon or Lemmy would
+    let inbox = url::Url::parse(group["inbox"].as_str().unwrap())?;
+    let follow = federation::FollowList {
+        actor: ap_user_b.ap_id.clone(),
+        object: list_ap_id.clone().into(),
+        kind: FollowType::Follow,
+        id: federation::activity::generate_id(&ap_cx_b)?,
+    };
+    queue_activity(
+        &WithContext::new_default(follow.clone()),
+        &ap_user_b,
+        vec![inbox.clone()],
+        &ap_cx_b,
+    )
+    .await?;
+
+    let mut tx_a = app_a.tx().await;
+    let followers = db::ap_users::list_list_followers(&mut tx_a, list.id).await?;
+    drop(tx_a);
+    assert_eq!(followers.len(), 1);
+    assert_eq!(followers[0].ap_id, ap_user_b.ap_id);
+    let followers: serde_json::Value = app_a
+        .req()
+        .get(&format!("/ap/list/{}/followers", list.id))
+        .await
+        .json()
+        .await;
+    assert_eq!(followers["totalItems"], 1);
+
+    // Newly linked bookmarks are announced
+    let bookmark = app_a
+        .create_bookmark(&user_a, "https://www.rust-lang.org")
+        .await;
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/links/create",
+            &HashMap::from([
+                ("src", list.id.to_string()),
+                ("dest", bookmark.id.to_string()),
+                ("submitted", "true".to_string()),
+            ]),
+        )
+        .await;
+    let outbox: serde_json::Value = app_a
+        .req()
+        .get(&format!("/ap/list/{}/outbox", list.id))
+        .await
+        .json()
+        .await;
+    assert_eq!(outbox["totalItems"], 1);
+    let announce = &outbox["orderedItems"][0];
+    assert_eq!(announce["type"], "Announce");
+    assert_eq!(announce["actor"], list_ap_id.as_str());
+    assert_eq!(
+        announce["object"]["object"]["url"],
+        "https://www.rust-lang.org"
+    );
+
+    let undo = federation::UndoFollowList {
+        actor: ap_user_b.ap_id.clone(),
+        object: follow.clone(),
+        kind: UndoType::Undo,
+        id: federation::activity::generate_id(&ap_cx_b)?,
+    };
+    queue_activity(
+        &WithContext::new_default(undo),
+        &ap_user_b,
+        vec![inbox.clone()],
+        &ap_cx_b,
+    )
+    .await?;
+    let mut tx_a = app_a.tx().await;
+    let followers = db::ap_users::list_list_followers(&mut tx_a, list.id).await?;
+    drop(tx_a);
+    assert!(followers.is_empty());
+
+    // Making the list private removes it from the fediverse, including its
+    // followers
+    queue_activity(
+        &WithContext::new_default(follow),
+        &ap_user_b,
+        vec![inbox],
+        &ap_cx_b,
+    )
+    .await?;
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/lists/{}/edit_private", list.id),
+            &HashMap::from([("private", "true")]),
+        )
+        .await;
+    let mut tx_a = app_a.tx().await;
+    let followers = db::ap_users::list_list_followers(&mut tx_a, list.id).await?;
+    drop(tx_a);
+    assert!(followers.is_empty());
+    app_a
+        .req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&format!("/ap/list/{}", list.id))
+        .await;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn nodeinfo_and_host_meta() -> Result<()> {
+    let app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let list = app.create_list(&user, "Rust").await;
+    let public = app
+        .create_bookmark(&user, "https://www.rust-lang.org")
+        .await;
+    app.create_bookmark(&user, "https://private.example").await;
+    let mut tx = app.tx().await;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: public.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    let discovery: serde_json::Value = app.req().get("/.well-known/nodeinfo").await.json().await;
+    assert_eq!(
+        discovery["links"][0]["rel"],
+        "http://nodeinfo.diaspora.software/ns/schema/2.1"
+    );
+    assert_eq!(
+        discovery["links"][0]["href"],
+        format!("{}nodeinfo/2.1", app.base_url)
+    );
+
+    let nodeinfo: serde_json::Value = app.req().get("/nodeinfo/2.1").await.json().await;
+    assert_eq!(nodeinfo["version"], "2.1");
+    assert_eq!(nodeinfo["software"]["name"], "ties");
+    assert_eq!(nodeinfo["software"]["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(nodeinfo["protocols"], serde_json::json!(["activitypub"]));
+    assert_eq!(nodeinfo["openRegistrations"], false);
+    assert_eq!(nodeinfo["usage"]["users"]["total"], 1);
+    assert_eq!(nodeinfo["usage"]["localPosts"], 1);
+
+    let host_meta = app.req().get("/.well-known/host-meta").await.text().await;
+    assert!(host_meta.contains(&format!(
+        "template=\"{}.well-known/webfinger?resource={{uri}}\"",
+        app.base_url
+    )));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn content_negotiation() -> Result<()> {
+    let app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let list = app.create_list(&user, "Rust").await;
+    let bookmark = app
+        .create_bookmark(&user, "https://www.rust-lang.org")
+        .await;
+    let private_bookmark = app.create_bookmark(&user, "https://example.com").await;
+    let mut tx = app.tx().await;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+    let activity_json = "application/activity+json";
+
+    let person_response = app
+        .req()
+        .header(header::ACCEPT, activity_json)
+        .get("/user/testuser")
+        .await;
+    assert_eq!(person_response.headers()[header::VARY], "accept");
+    let person: serde_json::Value = person_response.json().await;
+    assert_eq!(person["type"], "Person");
+    assert_eq!(
+        person["id"],
+        format!("{}ap/user/{}", app.base_url, user.ap_user_id)
+    );
+    assert_eq!(person["url"], format!("{}user/testuser", app.base_url));
+
+    let person: serde_json::Value = app
+        .req()
+        .header(
+            header::ACCEPT,
+            "application/ld+json; profile=\"https://www.w3.org/ns/activitystreams\"",
+        )
+        .get("/user/testuser")
+        .await
+        .json()
+        .await;
+    assert_eq!(person["type"], "Person");
+
+    let note: serde_json::Value = app
+        .req()
+        .header(header::ACCEPT, activity_json)
+        .get(&format!("/bookmarks/{}", bookmark.id))
+        .await
+        .json()
+        .await;
+    assert_eq!(note["type"], "Note");
+
+    // Bookmarks that aren't in any public list stay private
+    app.req()
+        .header(header::ACCEPT, activity_json)
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&private_bookmark.path())
+        .await;
+
+    let group: serde_json::Value = app
+        .req()
+        .header(header::ACCEPT, activity_json)
+        .get(&list.path())
+        .await
+        .json()
+        .await;
+    assert_eq!(group["type"], "Group");
+
+    // Browsers opening ActivityPub URLs are sent to the HTML page
+    let browser_accept = "text/html,application/xhtml+xml,*/*;q=0.8";
+    let response = app
+        .req()
+        .header(header::ACCEPT, browser_accept)
+        .expect_status(StatusCode::SEE_OTHER)
+        .get(&format!("/ap/user/{}", user.ap_user_id))
+        .await;
+    assert_eq!(response.headers()[header::LOCATION], "/user/testuser");
+    let response = app
+        .req()
+        .header(header::ACCEPT, browser_accept)
+        .expect_status(StatusCode::SEE_OTHER)
+        .get(&format!("/ap/bookmark/{}", bookmark.id))
+        .await;
+    assert_eq!(
+        response.headers()[header::LOCATION],
+        format!("/bookmarks/{}", bookmark.id).as_str()
+    );
+
+    let profile = app
+        .req()
+        .header(header::ACCEPT, browser_accept)
+        .get("/user/testuser")
+        .await
+        .test_page()
+        .await;
+    assert!(!profile.dom.find("#follow_counts").is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+
+use crate::{
+    db::{self, LinkDestinationWithChildren},
+    feed_subscriptions,
+    forms::feed_subscriptions::CreateFeedSubscription,
+    tests::util::test_app::TestApp,
+};
+
+const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+<channel>
+<title>Rust Blog</title>
+<link>https://blog.rust.example/</link>
+<item>
+<title>Announcing Rust 2.0</title>
+<link>https://blog.rust.example/2.0</link>
+<guid isPermaLink="false">rust-2.0</guid>
+</item>
+<item>
+<title>Tokio</title>
+<link>https://tokio.rs/</link>
+</item>
+<item>
+<title>Not a website</title>
+<link>mailto:someone@rust.example</link>
+</item>
+</channel>
+</rss>
+"#;
+
+#[test_log::test(tokio::test)]
+async fn subscribe_and_unsubscribe() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let list = app.create_list(&user, "Rust").await;
+
+    let list_page = app.req().get(&list.path()).await.test_page().await;
+    let feeds_page = list_page.visit_link("Feeds").await;
+    assert!(
+        feeds_page
+            .dom
+            .text()
+            .contains("This list isn't subscribed to any feeds yet.")
+    );
+
+    let subscription = CreateFeedSubscription {
+        url: "https://blog.rust.example/feed.xml".to_string(),
+        archive: true,
+    };
+    feeds_page
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form("#subscribe", &subscription)
+        .await;
+
+    let feeds_page = app
+        .req()
+        .get(&format!("{}/feeds", list.path()))
+        .await
+        .test_page()
+        .await;
+    assert!(
+        feeds_page
+            .dom
+            .find("#subscriptions")
+            .text()
+            .contains("https://blog.rust.example/feed.xml")
+    );
+
+    let duplicate = feeds_page
+        .fill_form("#subscribe", &subscription)
+        .await
+        .test_page()
+        .await;
+    assert!(
+        duplicate
+            .dom
+            .text()
+            .contains("This list is already subscribed to this feed")
+    );
+
+    duplicate
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form("#subscriptions form", &HashMap::<String, String>::new())
+        .await;
+    let mut tx = app.tx().await;
+    assert!(
+        db::feed_subscriptions::list_by_list_id(&mut tx, list.id)
+            .await?
+            .is_empty()
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn other_users_cannot_manage_subscriptions() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    app.login_test_user().await;
+    let other_user = app.create_user("other", "testpassword").await;
+    let list = app.create_list(&other_user, "Rust").await;
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&format!("{}/feeds", list.path()))
+        .await;
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .post(
+            &format!("{}/feeds", list.path()),
+            &CreateFeedSubscription {
+                url: "https://blog.rust.example/feed.xml".to_string(),
+                archive: false,
+            },
+        )
+        .await;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn feed_entries_are_added_once() -> anyhow::Result<()> {
+    let app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let list = app.create_list(&user, "Rust").await;
+    let existing = app.create_bookmark(&user, "https://tokio.rs/").await;
+
+    let mut tx = app.tx().await;
+    let subscription = db::feed_subscriptions::insert(
+        &mut tx,
+        list.id,
+        CreateFeedSubscription {
+            url: "https://blog.rust.example/feed.xml".to_string(),
+            archive: true,
+        },
+    )
+    .await?;
+
+    let feed = feed_subscriptions::parse(FEED.as_bytes())?;
+    let archive_ids =
+        feed_subscriptions::add_entries(&mut tx, &app.state, &subscription, feed).await?;
+    // The existing bookmark for tokio.rs is reused instead of archived again
+    assert_eq!(archive_ids.len(), 1);
+
+    let feed = feed_subscriptions::parse(FEED.as_bytes())?;
+    let archive_ids =
+        feed_subscriptions::add_entries(&mut tx, &app.state, &subscription, feed).await?;
+    assert!(archive_ids.is_empty());
+
+    let links = db::links::list_by_list(&mut tx, list.id, Some(user.ap_user_id)).await?;
+    let mut urls = links
+        .into_iter()
+        .map(|link| match link.dest {
+            LinkDestinationWithChildren::Bookmark(bookmark) => {
+                assert_eq!(
+                    bookmark.id == existing.id,
+                    bookmark.url == "https://tokio.rs/"
+                );
+                bookmark.url
+            }
+            LinkDestinationWithChildren::List(_) => panic!("Unexpected list in {list:?}"),
+        })
+        .collect::<Vec<_>>();
+    urls.sort();
+    assert_eq!(urls, ["https://blog.rust.example/2.0", "https://tokio.rs/"]);
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use axum::http::{StatusCode, header};
+
+use crate::{db, forms::links::CreateLink, tests::util::test_app::TestApp};
+
+#[test_log::test(tokio::test)]
+async fn feeds_only_contain_public_bookmarks() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let public_list = app.create_list(&user, "Rust").await;
+    let private_list = app.create_list(&user, "Secret").await;
+    let public_bookmark = app.create_bookmark(&user, "https://tokio.rs/").await;
+    let private_bookmark = app.create_bookmark(&user, "https://private.example/").await;
+    app.create_bookmark(&user, "https://unsorted.example/")
+        .await;
+    let mut tx = app.tx().await;
+    db::lists::set_private(&mut tx, private_list.id, true).await?;
+    for (src, dest) in [
+        (public_list.id, public_bookmark.id),
+        (private_list.id, public_bookmark.id),
+        (private_list.id, private_bookmark.id),
+    ] {
+        db::links::insert(&mut tx, user.id, CreateLink { src, dest }).await?;
+    }
+    tx.commit().await?;
+
+    let response = app
+        .req()
+        .get(&format!("/lists/{}/feed.rss", public_list.id))
+        .await;
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "application/rss+xml; charset=utf-8"
+    );
+    let rss = response.text().await;
+    assert!(rss.contains("<title>Rust</title>"));
+    assert!(rss.contains("<link>https://tokio.rs/</link>"));
+    assert!(!rss.contains("private.example"));
+
+    let atom = app.req().get("/user/testuser/feed.atom").await.text().await;
+    assert_eq!(atom.matches("<entry>").count(), 1);
+    assert!(atom.contains("<link href=\"https://tokio.rs/\" rel=\"alternate\"/>"));
+    assert!(!atom.contains("private.example"));
+    assert!(!atom.contains("unsorted.example"));
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&format!("/lists/{}/feed.atom", private_list.id))
+        .await;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn pages_link_to_their_feeds() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let public_list = app.create_list(&user, "Rust").await;
+    let private_list = app.create_list(&user, "Secret").await;
+    let mut tx = app.tx().await;
+    db::lists::set_private(&mut tx, private_list.id, true).await?;
+    tx.commit().await?;
+
+    let list_page = app.req().get(&public_list.path()).await.test_page().await;
+    let feed_links = list_page.dom.find("head link[rel='alternate']");
+    assert_eq!(feed_links.length(), 2);
+    assert_eq!(
+        feed_links.attr("href").map(|href| href.to_string()),
+        Some(format!("{}/feed.rss", public_list.path()))
+    );
+
+    let private_page = app.req().get(&private_list.path()).await.test_page().await;
+    assert!(
+        private_page
+            .dom
+            .find("head link[rel='alternate']")
+            .is_empty()
+    );
+
+    let profile = app.req().get("/user/testuser").await.test_page().await;
+    assert_eq!(
+        profile
+            .dom
+            .find("head link[type='application/atom+xml']")
+            .attr("href")
+            .map(|href| href.to_string()),
+        Some("/user/testuser/feed.atom".to_string())
+    );
+
+    Ok(())
+}
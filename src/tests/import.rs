@@ -0,0 +1,226 @@
+use crate::{
+    db,
+    tests::util::{html_decode::html_decode, test_app::TestApp},
+};
+
+const BOOKMARKS_HTML: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3>Rust</H3>
+    <DL><p>
+        <DT><A HREF="https://doc.rust-lang.org/book/" ADD_DATE="1600000000">The Rust Book</A>
+        <DD>Start with chapter 4
+        <DT><H3>Async</H3>
+        <DL><p>
+            <DT><A HREF="https://tokio.rs/">Tokio</A>
+            <DT><A HREF="https://doc.rust-lang.org/book/">The Rust Book, again</A>
+        </DL><p>
+    </DL><p>
+    <DT><A HREF="https://existing.example.com/">Already saved</A>
+    <DD>Imported notes
+    <DT><A HREF="javascript:alert(1)">Bookmarklet</A>
+</DL><p>
+"#;
+
+#[test_log::test(tokio::test)]
+async fn import_bookmarks_html() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let existing_list = app.create_list(&user, "Rust").await;
+    let existing = app
+        .create_bookmark(&user, "https://existing.example.com/")
+        .await;
+
+    let index = app.req().get("/").await.test_page().await;
+    let import_page = index.visit_link("Import bookmarks").await;
+    let summary = import_page
+        .fill_multipart_form("form#import", &[("file", BOOKMARKS_HTML)])
+        .await
+        .test_page()
+        .await;
+
+    let summary_text = summary.dom.find("#import_summary").text();
+    assert!(summary_text.contains("2 bookmarks created"));
+    assert!(summary_text.contains("1 bookmark skipped"));
+    assert!(summary_text.contains("1 list created, 1 existing list reused"));
+    assert!(summary_text.contains("javascript:alert(1)"));
+
+    let mut tx = app.tx().await;
+    let async_list = db::lists::by_title(&mut tx, user.ap_user_id, "Async")
+        .await?
+        .unwrap();
+    assert!(async_list.private);
+    assert!(db::links::exists(&mut tx, existing_list.id, async_list.id).await?);
+
+    let book = db::bookmarks::by_url(&mut tx, user.ap_user_id, "https://doc.rust-lang.org/book/")
+        .await?
+        .unwrap();
+    assert_eq!(book.title, "The Rust Book");
+    assert_eq!(book.notes.as_deref(), Some("Start with chapter 4"));
+    assert_eq!(book.created_at.unix_timestamp(), 1_600_000_000);
+    // The same URL in two folders becomes one bookmark in both lists
+    assert!(db::links::exists(&mut tx, existing_list.id, book.id).await?);
+    assert!(db::links::exists(&mut tx, async_list.id, book.id).await?);
+    assert!(
+        db::archives::by_bookmark_id(&mut tx, book.id)
+            .await?
+            .is_some()
+    );
+
+    let existing = db::bookmarks::by_id(&mut tx, existing.id).await?;
+    assert_eq!(existing.notes, None);
+    tx.commit().await?;
+
+    // Importing again with merging only adds the existing bookmark's notes
+    let summary = app
+        .req()
+        .get("/import")
+        .await
+        .test_page()
+        .await
+        .fill_multipart_form(
+            "form#import",
+            &[("file", BOOKMARKS_HTML), ("duplicates", "merge")],
+        )
+        .await
+        .test_page()
+        .await;
+
+    let summary_text = summary.dom.find("#import_summary").text();
+    assert!(summary_text.contains("0 bookmarks created"));
+    assert!(summary_text.contains("3 bookmarks merged"));
+    assert!(summary_text.contains("0 lists created, 2 existing lists reused"));
+
+    let mut tx = app.tx().await;
+    let existing = db::bookmarks::by_id(&mut tx, existing.id).await?;
+    assert_eq!(existing.notes.as_deref(), Some("Imported notes"));
+
+    Ok(())
+}
+
+const PINBOARD_JSON: &str = r#"[
+    {
+        "href": "https://tokio.rs/",
+        "description": "Tokio",
+        "extended": "An async runtime",
+        "time": "2020-09-13T12:26:40Z",
+        "shared": "no",
+        "toread": "yes",
+        "tags": "rust"
+    }
+]"#;
+
+#[test_log::test(tokio::test)]
+async fn import_preview() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+
+    let preview = app
+        .req()
+        .get("/import")
+        .await
+        .test_page()
+        .await
+        .fill_multipart_form(
+            "form#import",
+            &[
+                ("format", "pinboard"),
+                ("file", PINBOARD_JSON),
+                ("dry_run", "true"),
+            ],
+        )
+        .await
+        .test_page()
+        .await;
+
+    let summary_text = preview.dom.find("#import_summary").text();
+    assert!(summary_text.contains("Import preview"));
+    assert!(summary_text.contains("1 bookmark created"));
+    assert!(summary_text.contains("2 lists created"));
+
+    // Nothing is saved until the preview is confirmed
+    let mut tx = app.tx().await;
+    assert!(
+        db::bookmarks::by_url(&mut tx, user.ap_user_id, "https://tokio.rs/")
+            .await?
+            .is_none()
+    );
+    assert!(
+        db::lists::by_title(&mut tx, user.ap_user_id, "rust")
+            .await?
+            .is_none()
+    );
+    tx.commit().await?;
+
+    let confirm_form = preview.dom.find("form#import_confirm");
+    let format = confirm_form
+        .find("input[name='format']")
+        .attr("value")
+        .unwrap()
+        .to_string();
+    let file = html_decode(&confirm_form.find("textarea[name='file']").text());
+    let summary = preview
+        .fill_multipart_form(
+            "form#import_confirm",
+            &[
+                ("format", format.as_str()),
+                ("duplicates", "skip"),
+                ("file", file.as_str()),
+            ],
+        )
+        .await
+        .test_page()
+        .await;
+
+    let summary_text = summary.dom.find("#import_summary").text();
+    assert!(summary_text.contains("Import finished"));
+    assert!(summary_text.contains("1 bookmark created"));
+
+    let mut tx = app.tx().await;
+    let tokio = db::bookmarks::by_url(&mut tx, user.ap_user_id, "https://tokio.rs/")
+        .await?
+        .unwrap();
+    assert_eq!(tokio.title, "Tokio");
+    assert_eq!(tokio.notes.as_deref(), Some("An async runtime"));
+    assert_eq!(tokio.created_at.unix_timestamp(), 1_600_000_000);
+    for title in ["rust", "Unread"] {
+        let list = db::lists::by_title(&mut tx, user.ap_user_id, title)
+            .await?
+            .unwrap();
+        assert!(list.private);
+        assert!(db::links::exists(&mut tx, list.id, tokio.id).await?);
+    }
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn import_rejects_other_files() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    app.login_test_user().await;
+
+    let page = app
+        .req()
+        .get("/import")
+        .await
+        .test_page()
+        .await
+        .fill_multipart_form("form#import", &[("file", "just some text")])
+        .await
+        .test_page()
+        .await;
+
+    assert!(
+        page.dom
+            .find("form#import")
+            .text()
+            .contains("doesn't look like a bookmarks HTML file")
+    );
+
+    Ok(())
+}
@@ -1,4 +1,10 @@
-use crate::tests::util::test_app::TestApp;
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+
+use crate::{
+    db, forms::links::CreateLink, response_error::ResponseError, tests::util::test_app::TestApp,
+};
 
 #[test_log::test(tokio::test)]
 async fn get_create_list() -> anyhow::Result<()> {
@@ -12,3 +18,60 @@ async fn get_create_list() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test_log::test(tokio::test)]
+async fn delete_list() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let parent = app.create_list(&user, "Parent").await;
+    let list = app.create_list(&user, "To be deleted").await;
+    let bookmark = app.create_bookmark(&user, "https://example.com").await;
+
+    let mut tx = app.tx().await;
+    for (src, dest) in [(parent.id, list.id), (list.id, bookmark.id)] {
+        db::links::insert(&mut tx, user.id, CreateLink { src, dest }).await?;
+    }
+    tx.commit().await?;
+
+    let list_page = app.req().get(&list.path()).await.test_page().await;
+    let confirm_page = list_page.visit_link("/delete").await;
+    confirm_page
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form("form#confirm_delete", &HashMap::<String, String>::new())
+        .await;
+
+    let mut tx = app.tx().await;
+    assert!(matches!(
+        db::lists::by_id(&mut tx, list.id).await,
+        Err(ResponseError::NotFound)
+    ));
+    // Linked items are kept, only the links are removed
+    db::bookmarks::by_id(&mut tx, bookmark.id).await?;
+    let parent_metadata = db::lists::metadata_by_id(&mut tx, parent.id).await?;
+    assert_eq!(parent_metadata.linked_list_count, 0);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn cannot_delete_other_users_list() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    let other_user = app.create_user("otheruser", "otherpassword").await;
+    app.login_test_user().await;
+    let list = app.create_list(&other_user, "Not yours").await;
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .post(
+            &format!("/lists/{}/delete", list.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    let mut tx = app.tx().await;
+    db::lists::by_id(&mut tx, list.id).await?;
+
+    Ok(())
+}
@@ -2,13 +2,19 @@
 //! for information on why our tests are inside the `src` folder.
 #![expect(clippy::unwrap_used)]
 #![expect(clippy::expect_used)]
+mod api;
 mod archive;
 mod bookmarks;
+mod export;
 mod federation;
+mod feed_subscriptions;
+mod feeds;
+mod import;
 mod index;
 mod lists;
 mod migrations;
 mod response_error;
 mod search;
+mod timeline;
 mod users;
 mod util;
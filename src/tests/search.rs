@@ -1,7 +1,9 @@
 use pretty_assertions::assert_eq;
 
 use crate::{
+    archive,
     db::{self, bookmarks::InsertBookmark},
+    forms::{links::CreateLink, lists::CreateList},
     routes::search::SearchQuery,
     tests::util::{request_builder::TestPage, test_app::TestApp},
 };
@@ -37,6 +39,7 @@ async fn search_finds_bookmarks_with_various_queries() -> anyhow::Result<()> {
                     title.to_lowercase().replace(' ', "-")
                 ),
                 title: (*title).to_string(),
+                notes: None,
             },
             &app.base_url,
         )
@@ -51,7 +54,8 @@ async fn search_finds_bookmarks_with_various_queries() -> anyhow::Result<()> {
             "form[action='/search']",
             &SearchQuery {
                 q: "Rust".to_string(),
-                after_bookmark_id: None,
+                kind: None,
+                after_id: None,
             },
         )
         .await
@@ -95,6 +99,317 @@ async fn search_finds_bookmarks_with_various_queries() -> anyhow::Result<()> {
     assert!(html.contains("C++ Programming Guide"));
     assert!(!html.contains("Python Tutorial"));
 
+    // Wildcards are matched literally
+    let search_results = app.req().get("/search?q=%25").await.test_page().await;
+    let html = search_results.dom.htmls();
+    assert!(!html.contains("Python Tutorial"));
+    assert!(!html.contains("C++ Programming Guide"));
+
+    // Phrases match titles without their quotes
+    let search_results = app
+        .req()
+        .get("/search?q=%22ming+Gui%22")
+        .await
+        .test_page()
+        .await;
+    let html = search_results.dom.htmls();
+    assert!(html.contains("C++ Programming Guide"));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn search_finds_archive_content_and_lists() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+
+    let archived = app.create_bookmark(&user, "https://example.com/pets").await;
+    let unrelated = app.create_bookmark(&user, "https://example.com/cats").await;
+
+    let paragraph = "Axolotls are amphibians that can regenerate their limbs, their heart and \
+                     even parts of their brain. They stay in the water for their whole life and \
+                     keep their larval features as adults. ";
+    let html = format!(
+        "<html><body><article><h1>Pets</h1><p>{}</p><p>{}</p><p>{}</p></article></body></html>",
+        paragraph.repeat(2),
+        paragraph.repeat(2),
+        paragraph.repeat(2)
+    );
+    let article = archive::make_readable("https://example.com/pets".parse()?, &html);
+
+    let mut tx = app.tx().await;
+    let archive = db::archives::insert_pending(&mut tx, archived.id).await?;
+    db::archives::update(&mut tx, archive.id, &article).await?;
+    let titled = db::bookmarks::insert_local(
+        &mut tx,
+        user.ap_user_id,
+        InsertBookmark {
+            url: "https://example.com/axolotls".to_string(),
+            title: "Axolotl care".to_string(),
+            notes: None,
+        },
+        &app.base_url,
+    )
+    .await?;
+    let list = db::lists::insert(
+        &mut tx,
+        user.ap_user_id,
+        CreateList {
+            title: "Pets".to_string(),
+            content: Some("Everything about keeping an axolotl".to_string()),
+            private: false,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    // Stemming matches "regenerate" in the archived page.
+    let search_results = app
+        .req()
+        .get("/search?q=regenerating")
+        .await
+        .test_page()
+        .await;
+    let html = search_results.dom.find("main").htmls();
+    assert!(html.contains(&archived.id.to_string()));
+    assert!(!html.contains(&titled.id.to_string()));
+    assert!(!html.contains(&unrelated.id.to_string()));
+    assert_eq!(
+        search_results
+            .dom
+            .find("main span.bg-neutral-700")
+            .first()
+            .text()
+            .trim(),
+        "regenerate"
+    );
+
+    // Title matches rank above matches in the archived page.
+    let search_results = app.req().get("/search?q=axolotl").await.test_page().await;
+    let html = search_results.dom.find("main").htmls();
+    let titled_position = html.find(&titled.id.to_string());
+    let archived_position = html.find(&archived.id.to_string());
+    assert!(titled_position.is_some());
+    assert!(archived_position.is_some());
+    assert!(titled_position < archived_position);
+    assert!(!html.contains(&unrelated.id.to_string()));
+
+    // Lists match on their description.
+    assert!(html.contains(&list.id.to_string()));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn search_finds_bookmark_notes() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+
+    let mut tx = app.tx().await;
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx,
+        user.ap_user_id,
+        InsertBookmark {
+            url: "https://example.com/soil".to_string(),
+            title: "Soil health".to_string(),
+            notes: Some("Recommended by a friend who grows *mushrooms*.".to_string()),
+        },
+        &app.base_url,
+    )
+    .await?;
+    tx.commit().await?;
+
+    let search_results = app.req().get("/search?q=mushroom").await.test_page().await;
+    let main = search_results.dom.find("main");
+    assert!(main.htmls().contains(&bookmark.path()));
+    assert_eq!(
+        main.find("p span.bg-neutral-700").text().trim(),
+        "mushrooms"
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn search_mixes_lists_and_bookmarks() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+
+    let list = app.create_list(&user, "Gardening").await;
+    let other_list = app.create_list(&user, "Tomatoes").await;
+    let mut tx = app.tx().await;
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx,
+        user.ap_user_id,
+        InsertBookmark {
+            url: "https://example.com/gardening".to_string(),
+            title: "Gardening for beginners".to_string(),
+            notes: None,
+        },
+        &app.base_url,
+    )
+    .await?;
+    for dest in [bookmark.id, other_list.id] {
+        db::links::insert(&mut tx, user.id, CreateLink { src: list.id, dest }).await?;
+    }
+    tx.commit().await?;
+
+    let search_results = app.req().get("/search?q=gardening").await.test_page().await;
+    let main = search_results.dom.find("main");
+    let html = main.htmls();
+    assert!(html.contains("2 results found"));
+    assert!(html.contains(&bookmark.path()));
+    assert!(html.contains(&list.path()));
+    assert!(!html.contains(&other_list.path()));
+
+    // The list shows how many items it contains, and the bookmark shows the list
+    // it's in.
+    let list_result = main
+        .find("section")
+        .filter_by(|_, section| section.text().contains("2 items"));
+    assert!(list_result.text().contains("Gardening"));
+    let bookmark_result = main
+        .find("section")
+        .filter_by(|_, section| section.text().contains("Gardening for beginners"));
+    assert!(bookmark_result.html().contains(&list.path()));
+
+    let search_results = app
+        .req()
+        .get("/search?q=gardening&kind=lists")
+        .await
+        .test_page()
+        .await;
+    let html = search_results.dom.find("main").htmls();
+    assert!(html.contains("1 result found"));
+    assert!(html.contains(&list.path()));
+    assert!(!html.contains(&bookmark.path()));
+
+    let search_results = search_results.visit_link("Bookmarks").await;
+    let html = search_results.dom.find("main").htmls();
+    assert!(html.contains("1 result found"));
+    assert!(html.contains(&bookmark.path()));
+    assert!(!html.contains(&list.path()));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn search_filters_results() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+
+    let list = app.create_list(&user, "Rust Books").await;
+    let in_list = app
+        .create_bookmark(&user, "https://github.com/rust-lang/book")
+        .await;
+    let unsorted = app
+        .create_bookmark(&user, "https://doc.rust-lang.org/nomicon")
+        .await;
+    let mut tx = app.tx().await;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: in_list.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    let search = |q: &str| format!("/search?q={}", q.replace(' ', "+").replace('"', "%22"));
+
+    let html = app
+        .req()
+        .get(&search(r#"list:"rust books""#))
+        .await
+        .test_page()
+        .await
+        .dom
+        .find("main")
+        .htmls();
+    assert!(html.contains(&in_list.path()));
+    assert!(!html.contains(&unsorted.path()));
+
+    let html = app
+        .req()
+        .get(&search("unsorted"))
+        .await
+        .test_page()
+        .await
+        .dom
+        .find("main")
+        .htmls();
+    assert!(!html.contains(&in_list.path()));
+    assert!(html.contains(&unsorted.path()));
+    // Lists can't be unsorted
+    assert!(!html.contains(&list.path()));
+
+    let html = app
+        .req()
+        .get(&search("domain:github.com"))
+        .await
+        .test_page()
+        .await
+        .dom
+        .find("main")
+        .htmls();
+    assert!(html.contains(&in_list.path()));
+    assert!(!html.contains(&unsorted.path()));
+
+    // "_" is matched literally, not as any character
+    let html = app
+        .req()
+        .get(&search("domain:rust_lang.org"))
+        .await
+        .test_page()
+        .await
+        .dom
+        .find("main")
+        .htmls();
+    assert!(!html.contains(&unsorted.path()));
+
+    let html = app
+        .req()
+        .get(&search("rust -nomicon"))
+        .await
+        .test_page()
+        .await
+        .dom
+        .find("main")
+        .htmls();
+    assert!(html.contains(&in_list.path()));
+    assert!(!html.contains(&unsorted.path()));
+
+    let html = app
+        .req()
+        .get(&search("before:2000-01-01"))
+        .await
+        .test_page()
+        .await
+        .dom
+        .find("main")
+        .htmls();
+    assert!(html.contains("0 results found"));
+
+    // Malformed queries show an error instead of results
+    let html = app
+        .req()
+        .get(&search("before:yesterday"))
+        .await
+        .test_page()
+        .await
+        .dom
+        .find("main")
+        .htmls();
+    assert!(html.contains("Invalid search"));
+    assert!(html.contains("yesterday"));
+    assert!(!html.contains("results found"));
+
     Ok(())
 }
 
@@ -112,6 +427,7 @@ async fn search_only_returns_users_own_bookmarks() -> anyhow::Result<()> {
         InsertBookmark {
             url: "https://example.com/user1".to_string(),
             title: "My Rust Tutorial".to_string(),
+            notes: None,
         },
         &app.base_url,
     )
@@ -122,6 +438,7 @@ async fn search_only_returns_users_own_bookmarks() -> anyhow::Result<()> {
         InsertBookmark {
             url: "https://example.com/user2".to_string(),
             title: "Other User's Rust Guide".to_string(),
+            notes: None,
         },
         &app.base_url,
     )
@@ -167,6 +484,7 @@ async fn search_pagination_navigation() -> anyhow::Result<()> {
             InsertBookmark {
                 url: format!("https://example.com/test{i}"),
                 title: format!("Test Bookmark {i:03}"),
+                notes: None,
             },
             &app.base_url,
         )
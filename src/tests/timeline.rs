@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+
+use crate::{db, forms::links::CreateLink, tests::util::test_app::TestApp};
+
+/// Create a bookmark and add it to the user's public list.
+async fn create_public_bookmark(
+    app: &TestApp,
+    user: &db::User,
+    list: &db::List,
+    url: &str,
+) -> anyhow::Result<db::Bookmark> {
+    let bookmark = app.create_bookmark(user, url).await;
+    let mut tx = app.tx().await;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(bookmark)
+}
+
+async fn follow(app: &TestApp, follower: &db::User, followed: &db::User) -> anyhow::Result<()> {
+    let mut tx = app.tx().await;
+    db::follows::upsert(
+        &mut tx,
+        db::follows::Insert {
+            follower_id: follower.ap_user_id,
+            following_id: followed.ap_user_id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn timeline_shows_public_bookmarks_of_followed_users() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+
+    let empty = app.req().get("/timeline").await.test_page().await;
+    assert!(empty.dom.text().contains("Nothing here yet."));
+
+    let followed = app.create_user("followed", "testpassword").await;
+    let list = app.create_list(&followed, "Rust").await;
+    create_public_bookmark(&app, &followed, &list, "https://www.rust-lang.org").await?;
+    app.create_bookmark(&followed, "https://private.example")
+        .await;
+    follow(&app, &user, &followed).await?;
+
+    let stranger = app.create_user("stranger", "testpassword").await;
+    let stranger_list = app.create_list(&stranger, "Stranger things").await;
+    create_public_bookmark(&app, &stranger, &stranger_list, "https://stranger.example").await?;
+
+    let timeline = app.req().get("/timeline").await.test_page().await;
+    let entries = timeline.dom.find("#timeline").text();
+    assert!(entries.contains("https://www.rust-lang.org"));
+    assert!(!entries.contains("https://private.example"));
+    assert!(!entries.contains("https://stranger.example"));
+    assert!(timeline.dom.find("#next_page").is_empty());
+
+    // Muted authors are hidden until they're unmuted
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/timeline/authors/{}/mute", followed.ap_user_id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+    let timeline = app.req().get("/timeline").await.test_page().await;
+    assert!(timeline.dom.find("#timeline").is_empty());
+    assert_eq!(timeline.dom.find("#muted a").text(), "followed");
+
+    timeline
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form("#muted form", &HashMap::<String, String>::new())
+        .await;
+    let timeline = app.req().get("/timeline").await.test_page().await;
+    assert!(
+        timeline
+            .dom
+            .find("#timeline")
+            .text()
+            .contains("https://www.rust-lang.org")
+    );
+    assert!(timeline.dom.find("#muted").is_empty());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn timeline_is_paged() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let followed = app.create_user("followed", "testpassword").await;
+    let list = app.create_list(&followed, "Rust").await;
+    follow(&app, &user, &followed).await?;
+    for index in 0..31 {
+        create_public_bookmark(&app, &followed, &list, &format!("https://{index}.example")).await?;
+    }
+
+    let timeline = app.req().get("/timeline").await.test_page().await;
+    assert_eq!(timeline.dom.find("#timeline li").length(), 30);
+    // The oldest bookmark is on the next page
+    assert!(
+        !timeline
+            .dom
+            .find("#timeline")
+            .text()
+            .contains("https://0.example")
+    );
+
+    let next_page = timeline.visit_link("Older bookmarks").await;
+    assert_eq!(next_page.dom.find("#timeline li").length(), 1);
+    assert!(
+        next_page
+            .dom
+            .find("#timeline")
+            .text()
+            .contains("https://0.example")
+    );
+    assert!(next_page.dom.find("#next_page").is_empty());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn save_bookmark_from_timeline() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+    let followed = app.create_user("followed", "testpassword").await;
+    let list = app.create_list(&followed, "Rust").await;
+    let bookmark =
+        create_public_bookmark(&app, &followed, &list, "https://www.rust-lang.org").await?;
+    let private = app
+        .create_bookmark(&followed, "https://private.example")
+        .await;
+    follow(&app, &user, &followed).await?;
+
+    let timeline = app.req().get("/timeline").await.test_page().await;
+    let response = timeline
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form("#timeline form", &HashMap::<String, String>::new())
+        .await;
+
+    let mut tx = app.tx().await;
+    let saved = db::bookmarks::by_url(&mut tx, user.ap_user_id, &bookmark.url)
+        .await?
+        .expect("Bookmark should have been saved");
+    drop(tx);
+    assert_ne!(saved.id, bookmark.id);
+    assert_eq!(saved.title, bookmark.title);
+    assert_eq!(
+        response.headers().get("location").unwrap(),
+        &format!("/links/create?dest_id={}", saved.id)
+    );
+
+    // Saving again reuses the saved bookmark
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/timeline/bookmarks/{}/save", bookmark.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+    let mut tx = app.tx().await;
+    let own_bookmarks = db::bookmarks::list_by_ap_user_id(&mut tx, user.ap_user_id).await?;
+    assert_eq!(own_bookmarks.len(), 1);
+    drop(tx);
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .post(
+            &format!("/timeline/bookmarks/{}/save", private.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    Ok(())
+}
@@ -23,7 +23,8 @@ pub fn assert_form_matches<I: Serialize>(form: &visdom::types::Elements, input:
     tracing::debug!("Looking for HTML fields matching this form data: {input_entries:?}",);
     for name in input_entries.keys() {
         assert_eq!(
-            form.find(&format!("input[name='{name}']")).length(),
+            form.find(&format!("input[name='{name}'], textarea[name='{name}']"))
+                .length(),
             1,
             "Expected to find exactly one element with name '{name}'"
         );
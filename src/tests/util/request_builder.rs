@@ -5,7 +5,7 @@ use axum::{
 };
 use http_body_util::BodyExt;
 use mime_guess::mime;
-use serde::Serialize;
+use serde::{Serialize, de::DeserializeOwned};
 use tower::{Service, ServiceExt};
 use visdom::Vis;
 
@@ -81,6 +81,120 @@ impl RequestBuilder {
         }
     }
 
+    /// Submit a `multipart/form-data` form, sending each field's value as
+    /// the contents of a file.
+    pub async fn post_multipart(mut self, url: &str, fields: &[(&str, &str)]) -> TestResponse {
+        if let Some(cookie) = &self.logged_in_cookie {
+            self.request = self.request.header(axum::http::header::COOKIE, cookie);
+        }
+
+        let boundary = "ties-test-boundary";
+        let mut body = String::new();
+        for (name, value) in fields {
+            body.push_str(&format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"; \
+                 filename=\"{name}\"\r\n\r\n{value}\r\n"
+            ));
+        }
+        body.push_str(&format!("--{boundary}--\r\n"));
+
+        let request = self
+            .request
+            .method(http::Method::POST)
+            .uri(url)
+            .header(
+                http::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(body)
+            .unwrap();
+
+        let response = ServiceExt::<Request<Body>>::ready(&mut self.router)
+            .await
+            .unwrap()
+            .call(request)
+            .await
+            .unwrap();
+
+        tracing::debug!("{:?}", response.headers());
+
+        Self::assert_expected_status(self.expected_status, &response, "POST", url);
+
+        TestResponse {
+            response,
+            new_request_builder: RequestBuilder::new(&self.router, self.logged_in_cookie),
+        }
+    }
+
+    /// Send `input` as a JSON body, like API clients do.
+    pub async fn json<Input>(
+        mut self,
+        method: http::Method,
+        url: &str,
+        input: &Input,
+    ) -> TestResponse
+    where
+        Input: Serialize,
+    {
+        if let Some(cookie) = &self.logged_in_cookie {
+            self.request = self.request.header(axum::http::header::COOKIE, cookie);
+        }
+
+        let method_name = method.to_string();
+        let request = self
+            .request
+            .method(method)
+            .uri(url)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(input).unwrap())
+            .unwrap();
+
+        let response = ServiceExt::<Request<Body>>::ready(&mut self.router)
+            .await
+            .unwrap()
+            .call(request)
+            .await
+            .unwrap();
+
+        tracing::debug!("{:?}", response.headers());
+
+        Self::assert_expected_status(self.expected_status, &response, &method_name, url);
+
+        TestResponse {
+            response,
+            new_request_builder: RequestBuilder::new(&self.router, self.logged_in_cookie),
+        }
+    }
+
+    pub async fn delete(mut self, url: &str) -> TestResponse {
+        if let Some(cookie) = &self.logged_in_cookie {
+            self.request = self.request.header(axum::http::header::COOKIE, cookie);
+        }
+
+        let request = self
+            .request
+            .method(http::Method::DELETE)
+            .uri(url)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = ServiceExt::<Request<Body>>::ready(&mut self.router)
+            .await
+            .unwrap()
+            .call(request)
+            .await
+            .unwrap();
+
+        tracing::debug!("{:?}", response.headers());
+
+        Self::assert_expected_status(self.expected_status, &response, "DELETE", url);
+
+        TestResponse {
+            response,
+            new_request_builder: RequestBuilder::new(&self.router, self.logged_in_cookie),
+        }
+    }
+
     pub async fn get(mut self, url: &str) -> TestResponse {
         if let Some(cookie) = &self.logged_in_cookie {
             self.request = self.request.header(axum::http::header::COOKIE, cookie);
@@ -138,6 +252,29 @@ impl TestResponse {
         Vis::load(String::from_utf8(body).unwrap()).unwrap()
     }
 
+    pub async fn text(self) -> String {
+        let body = self
+            .response
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes()
+            .to_vec();
+        String::from_utf8(body).unwrap()
+    }
+
+    pub async fn json<T: DeserializeOwned>(self) -> T {
+        let body = self
+            .response
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
     pub fn headers(&self) -> &HeaderMap {
         self.response.headers()
     }
@@ -194,6 +331,32 @@ impl TestPage {
         }
     }
 
+    pub async fn fill_multipart_form(
+        self,
+        form_selector: &str,
+        fields: &[(&str, &str)],
+    ) -> TestResponse {
+        let form = self.dom.find(form_selector);
+        assert_eq!(
+            form.attr("enctype").map(|val| val.to_string()).as_deref(),
+            Some("multipart/form-data"),
+            "Form {form_selector} is not a multipart form"
+        );
+        for (name, _value) in fields {
+            assert!(
+                !form.find(&format!("[name='{name}']")).is_empty(),
+                "Missing input {name} in form {form_selector}"
+            );
+        }
+
+        let action = form
+            .attr("action")
+            .expect("Missing action attribute for form {form:?}")
+            .to_string();
+
+        self.request_builder.post_multipart(&action, fields).await
+    }
+
     pub fn expect_status(mut self, expected: StatusCode) -> Self {
         self.request_builder = self.request_builder.expect_status(expected);
         self
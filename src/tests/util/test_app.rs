@@ -39,6 +39,7 @@ impl TestApp {
             .await
             .expect("Failed to migrate database");
 
+        let archive_client = archive::Client::new(archive::Config::default()).unwrap();
         let state = AppState {
             pool: pool.clone(),
             base_url: base_url.clone(),
@@ -47,7 +48,16 @@ impl TestApp {
             federation_config: federation::config::new_config(pool.clone(), base_url.clone())
                 .await
                 .unwrap(),
-            archive_queue: archive::QueueHandle::new(pool.clone()),
+            archive_client: archive_client.clone(),
+            archive_resource_store: archive::ResourceStore::Database,
+            // Tests can't reach websites, so don't archive anything in the background
+            archive_queue: archive::QueueHandle::new(
+                pool.clone(),
+                archive_client,
+                archive::ResourceStore::Database,
+                false,
+                0,
+            ),
         };
 
         TestApp {
@@ -102,6 +112,7 @@ impl TestApp {
             db::bookmarks::InsertBookmark {
                 url: url.to_string(),
                 title: url.to_string(),
+                notes: None,
             },
             &self.base_url,
         )
@@ -0,0 +1,110 @@
+use htmf::prelude_inline::*;
+
+use crate::{
+    archive::DiffLine,
+    db,
+    views::{content, layout},
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub bookmark: db::Bookmark,
+    pub from: db::Archive,
+    pub to: db::Archive,
+    pub lines: Vec<DiffLine>,
+}
+
+pub fn view(
+    Data {
+        layout,
+        bookmark,
+        from,
+        to,
+        lines,
+    }: Data,
+) -> Element {
+    let has_changes = lines
+        .iter()
+        .any(|line| matches!(line, DiffLine::Added(_) | DiffLine::Removed(_)));
+
+    layout::layout(
+        fragment([
+            header(
+                class("bg-neutral-900 px-4 pt-3 pb-4"),
+                [
+                    h1(
+                        class("text-2xl tracking-tight font-bold"),
+                        a(
+                            [href(bookmark.path()), class("hover:text-fuchsia-300")],
+                            &bookmark.title,
+                        ),
+                    ),
+                    p(
+                        class("text-sm text-neutral-400"),
+                        [
+                            text("Changes from "),
+                            snapshot_link(&bookmark, &from),
+                            text(" to "),
+                            snapshot_link(&bookmark, &to),
+                        ],
+                    ),
+                ],
+            ),
+            div(class("border-b border-black"), ()),
+            div(class("border-b border-neutral-700"), ()),
+            if has_changes {
+                ol(
+                    [id("diff"), class("p-4 flex flex-col gap-0.5")],
+                    lines.iter().map(line).collect::<Vec<_>>(),
+                )
+            } else {
+                p(
+                    class("p-4 text-neutral-500 italic text-sm"),
+                    "The text of the page didn't change.",
+                )
+            },
+        ]),
+        &layout,
+    )
+}
+
+fn snapshot_link(bookmark: &db::Bookmark, archive: &db::Archive) -> Element {
+    a(
+        [
+            href(format!(
+                "/bookmarks/{}/archives/{}",
+                bookmark.id, archive.id
+            )),
+            class("text-neutral-200 hover:text-fuchsia-300"),
+        ],
+        content::format_date_time(archive.created_at),
+    )
+}
+
+fn line(line: &DiffLine) -> Element {
+    match line {
+        DiffLine::Unchanged(text_line) => li(class("pl-4 text-neutral-400"), text_line),
+        DiffLine::Added(text_line) => li(
+            [
+                class("added pl-4 bg-green-950 text-green-200"),
+                title_attr("Added"),
+            ],
+            text_line,
+        ),
+        DiffLine::Removed(text_line) => li(
+            [
+                class("removed pl-4 bg-red-950 text-red-200 line-through"),
+                title_attr("Removed"),
+            ],
+            text_line,
+        ),
+        DiffLine::Skipped(count) => li(
+            class("pl-4 text-neutral-500 italic text-sm"),
+            content::pluralize(
+                i64::try_from(*count).unwrap_or(i64::MAX),
+                "unchanged line",
+                "unchanged lines",
+            ),
+        ),
+    }
+}
@@ -1,6 +1,15 @@
 use htmf::{declare::*, element::Element, into_elements::IntoElements};
 
-pub fn base_document(children: impl IntoElements) -> Element {
+/// A feed of the page's content, announced with `<link rel="alternate">` so
+/// feed readers can find it.
+pub struct Feed {
+    pub title: String,
+    /// Path of the feed without the file extension. It's available in RSS
+    /// format at `{path}.rss` and in Atom format at `{path}.atom`.
+    pub path: String,
+}
+
+pub fn base_document(children: impl IntoElements, feeds: &[Feed]) -> Element {
     document().with(
         html(class("w-full h-full"))
             .with(head([]).with([
@@ -23,7 +32,25 @@ pub fn base_document(children: impl IntoElements) -> Element {
                     content("width=device-width,initial-scale=1"),
                 ]),
                 title_tag([]).with("ties"),
+                fragment().with(feeds.iter().map(feed_links).collect::<Vec<_>>()),
             ]))
             .with(body(class("w-full h-full text-gray-200 bg-neutral-800")).with(children)),
     )
 }
+
+fn feed_links(feed: &Feed) -> Element {
+    fragment().with([
+        link([
+            rel("alternate"),
+            attr("type", "application/rss+xml"),
+            attr("title", feed.title.as_str()),
+            href(format!("{}.rss", feed.path)),
+        ]),
+        link([
+            rel("alternate"),
+            attr("type", "application/atom+xml"),
+            attr("title", feed.title.as_str()),
+            href(format!("{}.atom", feed.path)),
+        ]),
+    ])
+}
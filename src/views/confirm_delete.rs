@@ -0,0 +1,78 @@
+use htmf::prelude_inline::*;
+
+pub struct Data {
+    pub layout: super::layout::Template,
+    /// What is deleted, like "bookmark" or "list"
+    pub kind: &'static str,
+    pub title: String,
+    /// Explains what else happens when deleting
+    pub consequences: String,
+    /// Where the confirmation form is sent to
+    pub action: String,
+    /// Where to go when not deleting after all
+    pub cancel_path: String,
+}
+
+pub fn view(
+    Data {
+        layout,
+        kind,
+        title,
+        consequences,
+        action: action_path,
+        cancel_path,
+    }: Data,
+) -> Element {
+    super::layout::layout(
+        [
+            div(class("border-t border-black"), ()),
+            div(class("border-t border-neutral-700"), ()),
+            form(
+                [
+                    action(action_path),
+                    class("flex flex-col max-w-xl mx-4 mb-4 grow"),
+                    id("confirm_delete"),
+                    method("POST"),
+                ],
+                [
+                    header(
+                        class("mt-3 mb-4"),
+                        [h1(class("text-xl font-bold"), format!("Delete {kind}"))],
+                    ),
+                    p(
+                        (),
+                        [
+                            text("Do you really want to delete "),
+                            span(class("font-bold"), title),
+                            text("?"),
+                        ],
+                    ),
+                    p(class("mt-2 text-neutral-400"), consequences),
+                    div(
+                        class("flex justify-end gap-x-4 mt-4"),
+                        [
+                            a(
+                                [
+                                    class(
+                                        "py-1.5 px-3 border rounded border-neutral-700 \
+                                         hover:bg-neutral-800",
+                                    ),
+                                    href(cancel_path),
+                                ],
+                                "Cancel",
+                            ),
+                            button(
+                                [
+                                    class("bg-red-700 py-1.5 px-3 text-neutral-100 rounded"),
+                                    type_("submit"),
+                                ],
+                                format!("Delete {kind}"),
+                            ),
+                        ],
+                    ),
+                ],
+            ),
+        ],
+        &layout,
+    )
+}
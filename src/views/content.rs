@@ -2,6 +2,8 @@ use anyhow::Context;
 use htmf::prelude_inline::*;
 use time::{OffsetDateTime, format_description};
 
+use crate::markdown;
+
 pub static BULLET: &str = "∙";
 
 pub fn link_url(url: &str) -> Element {
@@ -14,8 +16,32 @@ pub fn link_url(url: &str) -> Element {
     )
 }
 
+/// Render a bookmark's markdown notes.
+pub fn notes(notes: Option<&str>) -> Element {
+    match notes {
+        Some(notes) => div(
+            class("prose prose-invert max-w-2xl text-sm"),
+            unsafe_raw_html(markdown::to_html(notes)),
+        ),
+        None => nothing(),
+    }
+}
+
 pub fn format_date(date: OffsetDateTime) -> String {
-    let maybe_formatted = format_description::parse("[year]-[month]-[day]")
+    format_with(date, "[year]-[month]-[day]")
+}
+
+/// Format a date along with the time in UTC, for telling apart things that
+/// happen on the same day.
+pub fn format_date_time(date: OffsetDateTime) -> String {
+    format_with(
+        date.to_offset(time::UtcOffset::UTC),
+        "[year]-[month]-[day] [hour]:[minute] UTC",
+    )
+}
+
+fn format_with(date: OffsetDateTime, description: &str) -> String {
+    let maybe_formatted = format_description::parse(description)
         .context("Invalid date format description")
         .and_then(|fmt| date.format(&fmt).context("Failed to format date"));
 
@@ -56,6 +56,16 @@ pub fn view(
                     required(""),
                     type_("text"),
                 ]),
+                label([class("mt-4"), for_("notes")]).with("Notes"),
+                errors.view("notes"),
+                textarea([
+                    class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+                    id("notes"),
+                    name("notes"),
+                    placeholder("Why did you save this? Supports Markdown."),
+                    attr("rows", "4"),
+                ])
+                .with(input_data.notes.as_deref().unwrap_or_default()),
                 label([class("mt-4"), for_("list_search_term")]).with("Add to Lists"),
                 div(id("selected_lists")).with([
                     errors.view("parents"),
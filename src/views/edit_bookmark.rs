@@ -0,0 +1,83 @@
+use htmf::prelude_inline::*;
+use uuid::Uuid;
+
+use crate::{form_errors::FormErrors, forms};
+
+pub struct Data {
+    pub layout: super::layout::Template,
+    pub form_input: forms::bookmarks::EditBookmark,
+    pub errors: FormErrors,
+    pub bookmark_id: Uuid,
+}
+
+pub fn view(
+    Data {
+        layout,
+        form_input,
+        errors,
+        bookmark_id,
+    }: Data,
+) -> Element {
+    super::layout::layout(
+        [
+            div(class("border-t border-black"), ()),
+            div(class("border-t border-neutral-700"), ()),
+            form(
+                [
+                    action(format!("/bookmarks/{bookmark_id}/edit")),
+                    class("flex flex-col max-w-xl mx-4 mb-4 grow"),
+                    id("edit_bookmark"),
+                    method("POST"),
+                ],
+                [
+                    header(
+                        class("mt-3 mb-4"),
+                        [h1(class("text-xl font-bold"), "Edit bookmark")],
+                    ),
+                    label(for_("url"), "URL"),
+                    errors.view("url"),
+                    input([
+                        value(form_input.url),
+                        class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+                        name("url"),
+                        placeholder("https://..."),
+                        required(""),
+                        type_("text"),
+                    ]),
+                    label([class("mt-4"), for_("title")], "Title"),
+                    errors.view("title"),
+                    input([
+                        value(form_input.title),
+                        class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+                        name("title"),
+                        required(""),
+                        type_("text"),
+                    ]),
+                    label([class("mt-4"), for_("notes")], "Notes"),
+                    errors.view("notes"),
+                    textarea(
+                        [
+                            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+                            id("notes"),
+                            name("notes"),
+                            placeholder("Why did you save this? Supports Markdown."),
+                            attr("rows", "4"),
+                        ],
+                        form_input.notes.unwrap_or_default(),
+                    ),
+                    errors.view("root"),
+                    button(
+                        [
+                            class(
+                                "bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end",
+                            ),
+                            type_("submit"),
+                        ],
+                        "Save Changes",
+                    ),
+                ],
+            ),
+        ],
+        &layout,
+    )
+}
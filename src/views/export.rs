@@ -0,0 +1,55 @@
+use htmf::prelude_inline::*;
+
+use super::layout;
+
+pub struct Data {
+    pub layout: layout::Template,
+}
+
+pub fn view(data: &Data) -> Element {
+    let download = |url: &str, title: &str, description: &str| {
+        a(
+            [
+                class("block p-4 border rounded border-neutral-700 hover:bg-neutral-700"),
+                href(url),
+            ],
+            [
+                p(class("font-bold"), title),
+                p(class("mt-1 text-sm text-neutral-400"), description),
+            ],
+        )
+    };
+
+    layout::layout(
+        [
+            div(class("border-t border-black"), ()),
+            div(class("border-t border-neutral-700"), ()),
+            section(
+                [
+                    class("flex flex-col max-w-xl mx-4 mb-4 grow gap-4"),
+                    id("export"),
+                ],
+                [
+                    header(
+                        class("mt-3"),
+                        [h1(class("text-xl font-bold"), "Export my data")],
+                    ),
+                    download(
+                        "/export/bookmarks.html",
+                        "Bookmarks HTML file",
+                        "For importing your bookmarks into a browser or another bookmarking \
+                         service. Lists become folders.",
+                    ),
+                    download(
+                        "/export/backup.json",
+                        "JSON backup",
+                        "Contains all your bookmarks, lists, notes, archived pages and the \
+                         accounts you follow. Use \"Import bookmarks\" on the start page of \
+                         another ties instance to restore it.",
+                    ),
+                ],
+            ),
+        ],
+        &data.layout,
+    )
+}
@@ -0,0 +1,151 @@
+use htmf::{into_attrs::IntoAttrs, prelude_inline::*};
+
+use super::{content, layout};
+use crate::{
+    db::{self, feed_subscriptions::FeedSubscription},
+    form_errors::FormErrors,
+    forms::feed_subscriptions::CreateFeedSubscription,
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub list: db::List,
+    pub subscriptions: Vec<FeedSubscription>,
+    pub input: CreateFeedSubscription,
+    pub errors: FormErrors,
+}
+
+pub fn view(data: &Data) -> Element {
+    let submit = button(
+        [
+            class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end"),
+            type_("submit"),
+        ],
+        "Subscribe",
+    );
+
+    layout::layout(
+        [
+            div(class("border-t border-black"), ()),
+            div(class("border-t border-neutral-700"), ()),
+            section(
+                [
+                    class("flex flex-col max-w-xl mx-4 mb-4 grow gap-4"),
+                    id("feed_subscriptions"),
+                ],
+                [
+                    header(
+                        class("mt-3"),
+                        [
+                            h1(class("text-xl font-bold"), "Feeds"),
+                            p(
+                                class("mt-2 text-sm text-neutral-400"),
+                                [
+                                    text("New entries of these RSS and Atom feeds are added to "),
+                                    a(
+                                        [
+                                            href(data.list.path()),
+                                            class("text-neutral-200 hover:text-fuchsia-300"),
+                                        ],
+                                        data.list.title.as_str(),
+                                    ),
+                                    text(" about once an hour."),
+                                ],
+                            ),
+                        ],
+                    ),
+                    subscriptions(&data.list, &data.subscriptions),
+                    form(
+                        [
+                            action(format!("{}/feeds", data.list.path())),
+                            class("flex flex-col"),
+                            id("subscribe"),
+                            method("POST"),
+                        ],
+                        [
+                            h2(class("font-bold mb-2"), "Subscribe to a feed"),
+                            label(for_("url"), "Feed URL"),
+                            data.errors.view("url"),
+                            input([
+                                class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+                                id("url"),
+                                name("url"),
+                                placeholder("https://example.com/feed.xml"),
+                                required(""),
+                                type_("url"),
+                                value(&data.input.url),
+                            ]),
+                            label(
+                                class("mt-4"),
+                                [
+                                    input([
+                                        type_("checkbox"),
+                                        name("archive"),
+                                        value("true"),
+                                        data.input.archive.then(checked).into_attrs(),
+                                    ]),
+                                    span((), " Archive new bookmarks"),
+                                ],
+                            ),
+                            submit,
+                        ],
+                    ),
+                ],
+            ),
+        ],
+        &data.layout,
+    )
+}
+
+fn subscriptions(list: &db::List, subscriptions: &[FeedSubscription]) -> Element {
+    if subscriptions.is_empty() {
+        return p(
+            class("text-neutral-400"),
+            "This list isn't subscribed to any feeds yet.",
+        );
+    }
+
+    ul(
+        [class("flex flex-col gap-2"), id("subscriptions")],
+        subscriptions
+            .iter()
+            .map(|subscription| {
+                let status = match (&subscription.last_polled_at, &subscription.last_error) {
+                    (None, _) => "not checked yet".to_string(),
+                    (Some(date), None) => {
+                        format!("last checked on {}", content::format_date(*date))
+                    }
+                    (Some(date), Some(error)) => format!(
+                        "checking failed on {}: {error}",
+                        content::format_date(*date)
+                    ),
+                };
+                li(
+                    class("flex items-center gap-4 p-4 border rounded border-neutral-700"),
+                    [
+                        div(
+                            class("grow overflow-hidden"),
+                            [
+                                p(class("font-bold break-all"), subscription.url.as_str()),
+                                p(class("text-sm text-neutral-400"), status),
+                            ],
+                        ),
+                        form(
+                            [
+                                action(format!("{}/feeds/{}/delete", list.path(), subscription.id)),
+                                method("POST"),
+                            ],
+                            button(
+                                [
+                                    class("py-1.5 px-3 rounded border border-neutral-300"),
+                                    type_("submit"),
+                                ],
+                                "Unsubscribe",
+                            ),
+                        ),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+}
@@ -0,0 +1,341 @@
+use htmf::{into_attrs::IntoAttrs, prelude_inline::*};
+
+use super::{content, layout};
+use crate::import::{Duplicates, Format, Summary};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub format: Format,
+    pub duplicates: Duplicates,
+    pub error: Option<String>,
+}
+
+pub fn view(data: &Data) -> Element {
+    let duplicates_option = |option: Duplicates, label_text: &str| {
+        label(
+            class("block"),
+            [
+                input([
+                    type_("radio"),
+                    name("duplicates"),
+                    value(duplicates_value(option)),
+                    (data.duplicates == option).then(checked).into_attrs(),
+                ]),
+                span((), format!(" {label_text}")),
+            ],
+        )
+    };
+
+    let format_option = |option: Format, label_text: &str| {
+        label(
+            class("block"),
+            [
+                input([
+                    type_("radio"),
+                    name("format"),
+                    value(format_value(option)),
+                    (data.format == option).then(checked).into_attrs(),
+                ]),
+                span((), format!(" {label_text}")),
+            ],
+        )
+    };
+
+    layout::layout(
+        [
+            div(class("border-t border-black"), ()),
+            div(class("border-t border-neutral-700"), ()),
+            form(
+                [
+                    action("/import"),
+                    class("flex flex-col max-w-xl mx-4 mb-4 grow"),
+                    id("import"),
+                    method("POST"),
+                    attr("enctype", "multipart/form-data"),
+                ],
+                [
+                    header(
+                        class("mt-3 mb-4"),
+                        [
+                            h1(class("text-xl font-bold"), "Import bookmarks"),
+                            p(
+                                class("mt-2 text-sm text-neutral-400"),
+                                "Upload a bookmarks HTML file exported from your browser, an \
+                                 export from another bookmarking service, or a backup exported \
+                                 from ties. Folders and tags become private lists, and the \
+                                 imported bookmarks are archived in the background.",
+                            ),
+                        ],
+                    ),
+                    div(
+                        class("mb-4"),
+                        [
+                            p(class("mb-2"), "File format"),
+                            format_option(Format::Netscape, "Bookmarks HTML file"),
+                            format_option(Format::Pinboard, "Pinboard JSON export"),
+                            format_option(Format::Pocket, "Pocket CSV or HTML export"),
+                            format_option(Format::Raindrop, "Raindrop.io CSV export"),
+                            format_option(
+                                Format::Linkding,
+                                "Linkding API response from /api/bookmarks/",
+                            ),
+                            format_option(Format::Backup, "JSON backup from ties"),
+                        ],
+                    ),
+                    label(for_("file"), "File"),
+                    data.error
+                        .as_ref()
+                        .map_or_else(nothing, |error| p(class("text-red-700"), error)),
+                    input([
+                        class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+                        id("file"),
+                        name("file"),
+                        required(""),
+                        type_("file"),
+                        attr(
+                            "accept",
+                            ".html,.htm,.json,.csv,text/html,application/json,text/csv",
+                        ),
+                    ]),
+                    div(
+                        class("mt-4"),
+                        [
+                            p(class("mb-2"), "Bookmarks you already saved"),
+                            duplicates_option(Duplicates::Skip, "Keep them unchanged"),
+                            duplicates_option(
+                                Duplicates::Merge,
+                                "Add them to the imported lists and use the imported notes",
+                            ),
+                        ],
+                    ),
+                    div(
+                        class("flex gap-2 mt-4 self-end"),
+                        [
+                            button(
+                                [
+                                    class("py-1.5 px-3 rounded border border-neutral-300"),
+                                    name("dry_run"),
+                                    type_("submit"),
+                                    value("true"),
+                                ],
+                                "Preview",
+                            ),
+                            button(
+                                [
+                                    class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded"),
+                                    type_("submit"),
+                                ],
+                                "Import",
+                            ),
+                        ],
+                    ),
+                ],
+            ),
+        ],
+        &data.layout,
+    )
+}
+
+fn format_value(format: Format) -> &'static str {
+    match format {
+        Format::Netscape => "netscape",
+        Format::Pinboard => "pinboard",
+        Format::Pocket => "pocket",
+        Format::Raindrop => "raindrop",
+        Format::Linkding => "linkding",
+        Format::Backup => "backup",
+    }
+}
+
+fn duplicates_value(duplicates: Duplicates) -> &'static str {
+    match duplicates {
+        Duplicates::Skip => "skip",
+        Duplicates::Merge => "merge",
+    }
+}
+
+pub struct SummaryData {
+    pub layout: layout::Template,
+    pub summary: Summary,
+    /// Set for dry runs, which show what would happen without saving anything.
+    pub preview: Option<Preview>,
+}
+
+/// The submitted import form, so a preview can be confirmed without uploading
+/// the file again.
+pub struct Preview {
+    pub format: Format,
+    pub duplicates: Duplicates,
+    pub file: String,
+}
+
+pub fn summary(
+    SummaryData {
+        layout,
+        summary,
+        preview,
+    }: &SummaryData,
+) -> Element {
+    let count = |count: usize, singular: &str, plural: &str| {
+        content::pluralize(i64::try_from(count).unwrap_or(i64::MAX), singular, plural)
+    };
+    let next_step = preview.as_ref().map_or_else(
+        || {
+            a(
+                [
+                    class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end"),
+                    href("/bookmarks/unsorted"),
+                ],
+                "Show unsorted bookmarks",
+            )
+        },
+        confirm_form,
+    );
+
+    layout::layout(
+        [
+            div(class("border-t border-black"), ()),
+            div(class("border-t border-neutral-700"), ()),
+            section(
+                [
+                    class("flex flex-col max-w-xl mx-4 mb-4 grow"),
+                    id("import_summary"),
+                ],
+                [
+                    header(
+                        class("mt-3 mb-4"),
+                        if preview.is_some() {
+                            [
+                                h1(class("text-xl font-bold"), "Import preview"),
+                                p(
+                                    class("mt-2 text-sm text-neutral-400"),
+                                    "Nothing has been saved yet. This is what importing the file \
+                                     will do:",
+                                ),
+                            ]
+                        } else {
+                            [h1(class("text-xl font-bold"), "Import finished"), nothing()]
+                        },
+                    ),
+                    ul(
+                        class("list-disc list-inside"),
+                        [
+                            li(
+                                (),
+                                format!(
+                                    "{} created",
+                                    count(summary.bookmarks_created, "bookmark", "bookmarks")
+                                ),
+                            ),
+                            li(
+                                (),
+                                format!(
+                                    "{} merged into existing bookmarks",
+                                    count(summary.bookmarks_merged, "bookmark", "bookmarks")
+                                ),
+                            ),
+                            li(
+                                (),
+                                format!(
+                                    "{} skipped because you already saved them",
+                                    count(summary.bookmarks_skipped, "bookmark", "bookmarks")
+                                ),
+                            ),
+                            li(
+                                (),
+                                format!(
+                                    "{} created, {} reused",
+                                    count(summary.lists_created, "list", "lists"),
+                                    count(summary.lists_reused, "existing list", "existing lists")
+                                ),
+                            ),
+                        ],
+                    ),
+                    if summary.invalid_urls.is_empty() {
+                        nothing()
+                    } else {
+                        fragment([
+                            p(
+                                class("mt-4"),
+                                "These bookmarks were not imported because their URL is invalid:",
+                            ),
+                            ul(
+                                class("list-disc list-inside text-sm text-neutral-400 break-all"),
+                                summary
+                                    .invalid_urls
+                                    .iter()
+                                    .map(|url| li((), url))
+                                    .collect::<Vec<_>>(),
+                            ),
+                        ])
+                    },
+                    if summary.accounts_to_follow.is_empty() {
+                        nothing()
+                    } else {
+                        fragment([
+                            p(
+                                class("mt-4"),
+                                "Follows can't be restored from a backup. You followed these \
+                                 accounts:",
+                            ),
+                            ul(
+                                class("list-disc list-inside text-sm text-neutral-400 break-all"),
+                                summary
+                                    .accounts_to_follow
+                                    .iter()
+                                    .map(|account| li((), account.as_str()))
+                                    .collect::<Vec<_>>(),
+                            ),
+                        ])
+                    },
+                    p(
+                        class("mt-4 text-sm text-neutral-400"),
+                        "New bookmarks will be archived in the background.",
+                    ),
+                    next_step,
+                ],
+            ),
+        ],
+        layout,
+    )
+}
+
+/// Submits the previewed file again, this time without a dry run.
+fn confirm_form(preview: &Preview) -> Element {
+    form(
+        [
+            action("/import"),
+            class("flex gap-2 mt-4 self-end"),
+            id("import_confirm"),
+            method("POST"),
+            attr("enctype", "multipart/form-data"),
+        ],
+        [
+            input([
+                name("format"),
+                type_("hidden"),
+                value(format_value(preview.format)),
+            ]),
+            input([
+                name("duplicates"),
+                type_("hidden"),
+                value(duplicates_value(preview.duplicates)),
+            ]),
+            textarea([name("file"), attr("hidden", "")], &preview.file),
+            a(
+                [
+                    class("py-1.5 px-3 rounded border border-neutral-300"),
+                    href("/import"),
+                ],
+                "Cancel",
+            ),
+            button(
+                [
+                    class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded"),
+                    type_("submit"),
+                ],
+                "Import",
+            ),
+        ],
+    )
+}
@@ -103,15 +103,19 @@ pub async fn view(data: &Data<'_>, tx: &mut AppTx) -> ResponseResult<Element> {
                                 [
                                     dash_button(href("/bookmarks/create"), "Add a bookmark"),
                                     dash_button(href("/lists/create"), "Create a list"),
+                                    dash_button(href("/import"), "Import bookmarks"),
                                 ],
                             ),
                             div(
                                 class("flex flex-col gap-2 flex-auto"),
                                 [
+                                    dash_button(href("/timeline"), "Timeline"),
                                     dash_button(
                                         href(format!("/user/{}", data.authed_info.username)),
                                         "View my profile",
                                     ),
+                                    dash_button(href("/export"), "Export my data"),
+                                    dash_button(href("/settings"), "Settings"),
                                     form(
                                         [action("/logout"), method("post")],
                                         button(
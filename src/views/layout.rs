@@ -1,6 +1,6 @@
 use htmf::{into_elements::IntoElements, prelude::*};
 
-use super::base_document::base_document;
+use super::base_document::{Feed, base_document};
 use crate::{
     authentication::AuthUser,
     db::{self, AppTx, List, layout::AuthedInfo},
@@ -10,6 +10,8 @@ use crate::{
 pub struct Template {
     pub authed_info: Option<AuthedInfo>,
     pub previous_search_input: Option<String>,
+    /// Feeds of the current page, for autodiscovery by feed readers
+    pub feeds: Vec<Feed>,
 }
 
 impl Template {
@@ -22,6 +24,7 @@ impl Template {
         Ok(Template {
             authed_info: auth_info,
             previous_search_input: None,
+            feeds: Vec::new(),
         })
     }
 }
@@ -40,6 +43,7 @@ pub fn layout<Children: IntoElements>(children: Children, layout: &Template) ->
                 None => fragment(),
             },
         ]),
+        &layout.feeds,
     )
 }
 
@@ -12,6 +12,8 @@ pub struct Data {
     pub list: db::List,
     pub metadata: db::lists::Metadata,
     pub backlinks: Vec<db::List>,
+    /// For following public lists from other servers
+    pub fediverse_handle: Option<String>,
 }
 
 pub fn view(
@@ -21,16 +23,25 @@ pub fn view(
         list,
         metadata,
         backlinks,
+        fediverse_handle,
     }: &Data,
 ) -> Element {
+    let is_owner = layout
+        .authed_info
+        .as_ref()
+        .is_some_and(|authed_info| authed_info.ap_user_id == list.ap_user_id);
+
     layout::layout(
         fragment()
             .with(
                 div(class("bg-neutral-900 border-b border-black px-4"))
-                    .with(title_and_description(list, metadata))
-                    .with(layout.authed_info.as_ref().and_then(|authed_info| {
-                        (authed_info.ap_user_id == list.ap_user_id).then(|| edit_buttons(data))
-                    }))
+                    .with(title_and_description(
+                        list,
+                        metadata,
+                        fediverse_handle.as_deref(),
+                        is_owner,
+                    ))
+                    .with(is_owner.then(|| edit_buttons(data)))
                     .with(backlink_section(backlinks)),
             )
             .with(
@@ -51,29 +62,76 @@ pub fn view(
     )
 }
 
-fn title_and_description(list: &db::List, metadata: &db::lists::Metadata) -> Element {
+fn title_and_description(
+    list: &db::List,
+    metadata: &db::lists::Metadata,
+    fediverse_handle: Option<&str>,
+    is_owner: bool,
+) -> Element {
     header(class("pt-3 mb-4"))
         .with([
             h1(class("text-2xl font-bold tracking-tight")).with(&list.title),
-            div(class("flex flex-wrap text-sm gap-x-1 text-neutral-400")).with([
-                a([
-                    href(format!("/user/{}", metadata.username)),
-                    class("hover:text-neutral-200"),
+            div(class("flex flex-wrap text-sm gap-x-1 text-neutral-400"))
+                .with([
+                    a([
+                        href(format!("/user/{}", metadata.username)),
+                        class("hover:text-neutral-200"),
+                    ])
+                    .with(format!("by {}", metadata.username)),
+                    text("∙"),
+                    p([]).with(format!("{} bookmarks", metadata.linked_bookmark_count)),
+                    text("∙"),
+                    p([]).with(pluralize(metadata.linked_list_count, "list", "lists")),
+                    text("∙"),
+                    p(id("private_indicator")).with(if list.private {
+                        "private"
+                    } else {
+                        "public"
+                    }),
                 ])
-                .with(format!("by {}", metadata.username)),
-                text("∙"),
-                p([]).with(format!("{} bookmarks", metadata.linked_bookmark_count)),
-                text("∙"),
-                p([]).with(pluralize(metadata.linked_list_count, "list", "lists")),
-                text("∙"),
-                p(id("private_indicator")).with(if list.private { "private" } else { "public" }),
-            ]),
+                .with(fediverse_handle.map(|handle| {
+                    fragment().with([
+                        text("∙"),
+                        p([
+                            id("fediverse_handle"),
+                            title_attr(
+                                "Follow this list from Mastodon, Lemmy or other ties servers",
+                            ),
+                        ])
+                        .with(format!("@{handle}")),
+                    ])
+                }))
+                .with(
+                    (is_owner && metadata.linked_bookmark_count > 0)
+                        .then(|| archive_export_links(list)),
+                ),
         ])
         .with(list.content.as_ref().and_then(|content| {
             (!content.is_empty()).then_some(p(class("max-w-2xl mt-2")).with(content))
         }))
 }
 
+/// Download the newest snapshots of all bookmarks in the list.
+fn archive_export_links(list: &db::List) -> Element {
+    fragment().with([
+        text("∙"),
+        p(id("archive_exports")).with([
+            text("archives as "),
+            a([
+                href(format!("{}/archives.html", list.path())),
+                class("hover:text-neutral-200"),
+            ])
+            .with("HTML"),
+            text(" or "),
+            a([
+                href(format!("{}/archives.warc", list.path())),
+                class("hover:text-neutral-200"),
+            ])
+            .with("WARC"),
+        ]),
+    ])
+}
+
 fn backlink_section(backlinks: &[db::List]) -> Element {
     use htmf::prelude_inline::*;
 
@@ -178,6 +236,16 @@ fn edit_buttons(Data { list, .. }: &Data) -> Element {
             ])
             .with("Add new bookmark"),
         ]),
+        a([
+            class("block px-4 py-1 border rounded hover:bg-neutral-800 border-neutral-700 w-max"),
+            href(format!("/lists/{}/feeds", list.id)),
+        ])
+        .with("Feeds"),
+        a([
+            class("block px-4 py-1 border rounded hover:bg-neutral-800 border-neutral-700 w-max"),
+            href(format!("/lists/{}/delete", list.id)),
+        ])
+        .with("Delete"),
     ])
 }
 
@@ -231,6 +299,7 @@ fn list_item_bookmark(bookmark: &db::Bookmark) -> Element {
         ])
         .with(&bookmark.title),
         content::link_url(&bookmark.url),
+        content::notes(bookmark.notes.as_deref()),
     ])
 }
 
@@ -1,12 +1,18 @@
 #![allow(clippy::wildcard_imports)]
 #![allow(clippy::too_many_lines)]
+pub mod archive_diff;
 pub mod base_document;
+pub mod confirm_delete;
 pub mod content;
 pub mod create_bookmark;
 pub mod create_link;
 pub mod create_list;
+pub mod edit_bookmark;
 pub mod edit_list_title;
+pub mod export;
+pub mod feed_subscriptions;
 pub mod form;
+pub mod import;
 pub mod index;
 pub mod layout;
 pub mod list;
@@ -16,5 +22,8 @@ pub mod login_demo;
 pub mod oidc_select_username;
 pub mod profile;
 pub mod search_results;
+pub mod settings;
 pub mod show_bookmark;
+pub mod single_file_archive;
+pub mod timeline;
 pub mod unsorted_bookmarks;
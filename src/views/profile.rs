@@ -3,6 +3,8 @@ use uuid::Uuid;
 
 use crate::{
     db::{self, AppTx},
+    form_errors::FormErrors,
+    forms::users::LookupUser,
     response_error::ResponseResult,
     views::{content::pluralize, layout},
 };
@@ -15,6 +17,30 @@ pub struct Data {
     pub layout: layout::Template,
     pub ap_user: db::ApUser,
     pub public_lists: Vec<db::ListWithMetadata>,
+    /// Only known for local users
+    pub follows: Option<Follows>,
+    /// Only known when logged-in users view someone else's profile
+    pub follow_status: Option<FollowStatus>,
+    /// Only shown to users on their own profile
+    pub lookup: Option<Lookup>,
+}
+
+pub struct Follows {
+    pub followers: Vec<db::ApUser>,
+    pub following: Vec<db::ApUser>,
+}
+
+/// How the logged-in user follows the user of the profile.
+pub enum FollowStatus {
+    NotFollowing,
+    /// Waiting for a user on another server to accept the follow request
+    Requested,
+    Following,
+}
+
+pub struct Lookup {
+    pub input: LookupUser,
+    pub errors: FormErrors,
 }
 
 async fn get_metadata(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Metadata> {
@@ -42,6 +68,9 @@ pub async fn view(
         layout,
         ap_user,
         public_lists: lists,
+        follows,
+        follow_status,
+        lookup,
     }: &Data,
 ) -> ResponseResult<Element> {
     let metadata = get_metadata(&mut tx, ap_user.id).await?;
@@ -59,9 +88,15 @@ pub async fn view(
                     .bio
                     .as_ref()
                     .map_or(nothing(), |bio| p(class("m-4"), bio)),
+                follows.as_ref().map_or(nothing(), follow_counts),
+                follow_status
+                    .as_ref()
+                    .map_or(nothing(), |status| follow_button(ap_user, status)),
             ],
         ),
+        lookup.as_ref().map_or(nothing(), lookup_form),
         view_lists(lists, &metadata),
+        follows.as_ref().map_or(nothing(), view_follows),
     ]);
 
     Ok(layout::layout(children, layout))
@@ -123,3 +158,126 @@ fn list_item(list: &db::ListWithMetadata) -> Element {
         ],
     )
 }
+
+fn follow_counts(follows: &Follows) -> Element {
+    p(
+        [class("text-sm text-neutral-400"), id("follow_counts")],
+        [
+            span(
+                (),
+                pluralize(
+                    follows.followers.len().try_into().unwrap_or(-1),
+                    "follower",
+                    "followers",
+                ),
+            ),
+            text(" ∙ "),
+            span((), format!("{} following", follows.following.len())),
+        ],
+    )
+}
+
+fn follow_button(ap_user: &db::ApUser, status: &FollowStatus) -> Element {
+    let (path, button_text) = match status {
+        FollowStatus::NotFollowing => ("follow", "Follow"),
+        FollowStatus::Requested => ("unfollow", "Cancel follow request"),
+        FollowStatus::Following => ("unfollow", "Unfollow"),
+    };
+
+    form(
+        [
+            action(format!("/user/{}/{path}", ap_user.handle())),
+            class("mt-4"),
+            id("follow"),
+            method("POST"),
+        ],
+        button(
+            [
+                class("py-1.5 px-3 rounded border border-neutral-300"),
+                type_("submit"),
+            ],
+            button_text,
+        ),
+    )
+}
+
+fn lookup_form(lookup: &Lookup) -> Element {
+    form(
+        [
+            action("/users/lookup"),
+            class("flex flex-col px-4 py-4 bg-neutral-900 border-b border-black"),
+            id("lookup_user"),
+            method("POST"),
+        ],
+        [
+            label(
+                [class("font-bold tracking-tight"), for_("handle")],
+                "Find someone to follow",
+            ),
+            lookup.errors.view("handle"),
+            div(
+                class("flex gap-2 mt-2"),
+                [
+                    input([
+                        class("rounded py-1.5 px-3 bg-neutral-800 grow"),
+                        id("handle"),
+                        name("handle"),
+                        placeholder("user@example.com"),
+                        required(""),
+                        type_("text"),
+                        value(&lookup.input.handle),
+                    ]),
+                    button(
+                        [
+                            class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded"),
+                            type_("submit"),
+                        ],
+                        "Look up",
+                    ),
+                ],
+            ),
+        ],
+    )
+}
+
+fn view_follows(follows: &Follows) -> Element {
+    fragment([
+        actor_list("followers", "Followers", &follows.followers),
+        actor_list("following", "Following", &follows.following),
+    ])
+}
+
+fn actor_list(section_id: &str, title: &str, actors: &[db::ApUser]) -> Element {
+    if actors.is_empty() {
+        return nothing();
+    }
+
+    section(
+        [
+            class("px-4 py-4 border-t border-neutral-700"),
+            id(section_id),
+        ],
+        [
+            h2(class("font-bold mb-2 tracking-tight"), title),
+            ul(
+                class("flex flex-wrap gap-x-4 gap-y-1"),
+                actors
+                    .iter()
+                    .map(|actor| {
+                        let handle = actor.handle();
+                        li(
+                            (),
+                            a(
+                                [
+                                    href(format!("/user/{handle}")),
+                                    class("hover:text-fuchsia-300"),
+                                ],
+                                actor.display_name.as_deref().unwrap_or(&actor.username),
+                            ),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        ],
+    )
+}
@@ -1,72 +1,173 @@
 use htmf::{element::Element, prelude_inline::*};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use uuid::Uuid;
 
 use crate::{
-    db,
+    db::{
+        self, LinkDestination,
+        search::{Kind, QueryError},
+    },
     views::{content, layout},
 };
 
 pub struct Data {
     pub layout: layout::Template,
-    pub results: db::search::Results,
+    pub results: Result<db::search::Results, QueryError>,
+    pub kind: Option<Kind>,
 }
 
 pub fn view(data: &Data) -> Element {
-    layout::layout(results(data), &data.layout)
+    let content = match &data.results {
+        Ok(results) => result_list(data, results),
+        Err(error) => query_error(error),
+    };
+    layout::layout(content, &data.layout)
 }
 
-// TODO use percent encoding for previous search input in urls
+fn query_error(error: &QueryError) -> Element {
+    fragment([
+        header(
+            class("bg-neutral-900 px-4 pt-3 pb-3 border-b border-black"),
+            [
+                p(class("font-bold tracking-tight text-xl"), "Invalid search"),
+                p(class("text-red-700"), error.to_string()),
+            ],
+        ),
+        section(
+            class("px-4 pt-3 pb-4 text-sm text-neutral-400"),
+            [
+                p(class("pb-1"), "You can use these filters in your search:"),
+                ul(
+                    class("list-disc list-inside"),
+                    [
+                        ("-word", "exclude results containing a word or \"phrase\""),
+                        ("list:\"Rust Books\"", "items in a list"),
+                        ("domain:github.com", "bookmarks on a domain"),
+                        ("before:2025-01-31", "items created before a date"),
+                        (
+                            "archived:failed",
+                            "bookmarks by archive state: success, failed or pending",
+                        ),
+                        ("unsorted", "bookmarks that aren't in any list"),
+                        ("private", "private lists and bookmarks"),
+                    ]
+                    .map(|(example, description)| {
+                        li(
+                            (),
+                            [
+                                span(class("font-mono text-neutral-200"), example),
+                                span((), format!(" – {description}")),
+                            ],
+                        )
+                    }),
+                ),
+            ],
+        ),
+    ])
+}
 
-fn results(data: &Data) -> Element {
+fn result_list(data: &Data, results: &db::search::Results) -> Element {
     fragment([
-        p(
+        header(
             class(
-                "bg-neutral-900 px-4 pt-3 pb-3 font-bold tracking-tight border-b border-black \
-                 text-xl",
+                "flex flex-wrap items-baseline justify-between gap-x-4 bg-neutral-900 px-4 pt-3 \
+                 pb-3 border-b border-black",
             ),
-            format!("{} bookmarks found", data.results.total_count),
+            [
+                p(
+                    class("font-bold tracking-tight text-xl"),
+                    format!(
+                        "{} found",
+                        content::pluralize(results.total_count, "result", "results")
+                    ),
+                ),
+                kind_filter(data),
+            ],
         ),
         fragment(
-            data.results
-                .bookmarks
+            results
+                .items
                 .iter()
                 .map(|r| list_item(r, data))
                 .collect::<Vec<_>>(),
         ),
-        pagination(data),
+        pagination(data, results),
     ])
 }
 
-fn pagination(data: &Data) -> Element {
+fn kind_filter(data: &Data) -> Element {
+    let filter_link = |kind: Option<Kind>, label: &str| {
+        let classes = if kind == data.kind {
+            "text-neutral-100 font-bold"
+        } else {
+            "text-neutral-400 hover:text-neutral-100"
+        };
+        a([class(classes), href(search_url(data, kind, None))], label)
+    };
+
+    div(
+        class("flex gap-x-2 text-sm"),
+        [
+            filter_link(None, "All"),
+            filter_link(Some(Kind::Bookmarks), "Bookmarks"),
+            filter_link(Some(Kind::Lists), "Lists"),
+        ],
+    )
+}
+
+fn search_url(data: &Data, kind: Option<Kind>, after_id: Option<Uuid>) -> String {
+    let q = data.layout.previous_search_input.as_deref().unwrap_or("");
+    let mut url = format!("/search?q={}", utf8_percent_encode(q, NON_ALPHANUMERIC));
+    match kind {
+        Some(Kind::Bookmarks) => url.push_str("&kind=bookmarks"),
+        Some(Kind::Lists) => url.push_str("&kind=lists"),
+        None => {}
+    }
+    if let Some(after_id) = after_id {
+        url.push_str(&format!("&after_id={after_id}"));
+    }
+    url
+}
+
+/// Render a snippet returned by [`db::search::search`], highlighting the
+/// matched words.
+fn snippet(snippet: &str) -> Element {
+    let mut parts = Vec::new();
+    for (index, part) in snippet.split(db::search::HIGHLIGHT_START).enumerate() {
+        // Everything before the first start marker is unhighlighted text.
+        if index == 0 {
+            parts.push(fragment(part));
+            continue;
+        }
+        let (highlighted, rest) = part
+            .split_once(db::search::HIGHLIGHT_END)
+            .unwrap_or((part, ""));
+        parts.push(span(class("text-neutral-100 bg-neutral-700"), highlighted));
+        parts.push(fragment(rest));
+    }
+
+    p(class("max-w-2xl text-sm text-neutral-400"), parts)
+}
+
+fn pagination(data: &Data, results: &db::search::Results) -> Element {
     section(
         class("flex flex-row gap-4 justify-center w-full p-4 border-t border-neutral-700"),
         [
-            match data.results.previous_page {
-                db::search::PreviousPage::AfterBookmarkId(id) => {
-                    let url = format!(
-                        "/search?q={}&after_bookmark_id={}",
-                        data.layout.previous_search_input.as_deref().unwrap_or(""),
-                        id
-                    );
-                    a([href(url)], "Previous page")
-                }
+            match results.previous_page {
+                db::search::PreviousPage::AfterId(id) => a(
+                    [href(search_url(data, data.kind, Some(id)))],
+                    "Previous page",
+                ),
                 db::search::PreviousPage::IsFirstPage => {
-                    let url = format!(
-                        "/search?q={}",
-                        data.layout.previous_search_input.as_deref().unwrap_or(""),
-                    );
-                    a([href(url)], "Previous page")
+                    a([href(search_url(data, data.kind, None))], "Previous page")
                 }
                 db::search::PreviousPage::DoesNotExist => nothing(),
             },
-            match data.results.next_page_after_bookmark_id {
-                Some(next_page_after_bookmark_id) => {
-                    let url = format!(
-                        "/search?q={}&after_bookmark_id={}",
-                        data.layout.previous_search_input.as_deref().unwrap_or(""),
-                        next_page_after_bookmark_id
-                    );
-                    a([href(url)], "Next page")
-                }
+            match results.next_page_after_id {
+                Some(next_page_after_id) => a(
+                    [href(search_url(data, data.kind, Some(next_page_after_id)))],
+                    "Next page",
+                ),
                 None => nothing(),
             },
         ],
@@ -77,7 +178,17 @@ fn list_item(result: &db::search::Result, Data { layout, .. }: &Data) -> Element
     section(
         class("flex flex-wrap items-end gap-2 px-4 pt-4 pb-4 border-t border-neutral-700"),
         [
-            div(class("overflow-hidden"), list_item_bookmark(result)),
+            div(
+                class("overflow-hidden"),
+                [
+                    match &result.item {
+                        LinkDestination::Bookmark(bookmark) => list_item_bookmark(bookmark),
+                        LinkDestination::List(list) => list_item_list(list, result.item_count),
+                    },
+                    result.snippet.as_deref().map_or_else(nothing, snippet),
+                    containing_lists(&result.lists),
+                ],
+            ),
             if let Some(_authed_info) = &layout.authed_info {
                 div(
                     class(
@@ -87,7 +198,7 @@ fn list_item(result: &db::search::Result, Data { layout, .. }: &Data) -> Element
                     [a(
                         [
                             class("hover:text-neutral-100"),
-                            href(format!("/links/create?dest_id={}", result.bookmark_id)),
+                            href(format!("/links/create?dest_id={}", result.item.id())),
                         ],
                         "Connect",
                     )],
@@ -99,7 +210,7 @@ fn list_item(result: &db::search::Result, Data { layout, .. }: &Data) -> Element
     )
 }
 
-fn list_item_bookmark(result: &db::search::Result) -> Element {
+fn list_item_bookmark(bookmark: &db::Bookmark) -> Element {
     fragment([
         a(
             [
@@ -107,10 +218,51 @@ fn list_item_bookmark(result: &db::search::Result) -> Element {
                     "block overflow-hidden leading-8 text-orange-100 hover:text-orange-300 \
                      text-ellipsis whitespace-nowrap",
                 ),
-                href(format!("/bookmarks/{}", result.bookmark_id)),
+                href(bookmark.path()),
             ],
-            &result.title,
+            &bookmark.title,
         ),
-        content::link_url(&result.bookmark_url),
+        content::link_url(&bookmark.url),
     ])
 }
+
+fn list_item_list(list: &db::List, item_count: i64) -> Element {
+    fragment([
+        a(
+            [
+                class(
+                    "block overflow-hidden font-semibold leading-8 hover:text-fuchsia-300 \
+                     text-ellipsis whitespace-nowrap",
+                ),
+                href(list.path()),
+            ],
+            &list.title,
+        ),
+        p(
+            class("text-sm text-neutral-400"),
+            content::pluralize(item_count, "item", "items"),
+        ),
+    ])
+}
+
+fn containing_lists(lists: &[db::List]) -> Element {
+    if lists.is_empty() {
+        return nothing();
+    }
+
+    let link_elems = itertools::intersperse(
+        lists.iter().map(|list| {
+            fragment(a(
+                [href(list.path()), class("hover:text-fuchsia-300")],
+                &list.title,
+            ))
+        }),
+        span((), " ∙ "),
+    )
+    .collect::<Vec<_>>();
+
+    p(
+        class("text-sm text-neutral-400"),
+        [span((), "In "), fragment(link_elems)],
+    )
+}
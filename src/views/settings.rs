@@ -0,0 +1,176 @@
+use htmf::{into_attrs::IntoAttrs, prelude_inline::*};
+
+use super::{content, layout};
+use crate::{
+    db::api_tokens::{ApiToken, Scope},
+    form_errors::FormErrors,
+    forms::api_tokens::CreateApiToken,
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub tokens: Vec<ApiToken>,
+    pub input: CreateApiToken,
+    pub errors: FormErrors,
+    /// A token that was just created. It's only shown this once.
+    pub created_token: Option<String>,
+}
+
+pub fn view(data: &Data) -> Element {
+    let submit = button(
+        [
+            class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end"),
+            type_("submit"),
+        ],
+        "Create token",
+    );
+
+    layout::layout(
+        [
+            div(class("border-t border-black"), ()),
+            div(class("border-t border-neutral-700"), ()),
+            section(
+                [
+                    class("flex flex-col max-w-xl mx-4 mb-4 grow gap-4"),
+                    id("settings"),
+                ],
+                [
+                    header(
+                        class("mt-3"),
+                        [
+                            h1(class("text-xl font-bold"), "API tokens"),
+                            p(
+                                class("mt-2 text-sm text-neutral-400"),
+                                "Scripts and browser extensions can use these tokens to access \
+                                 your bookmarks through the JSON API at /api/v1. Send them in an \
+                                 \"Authorization: Bearer <token>\" header.",
+                            ),
+                        ],
+                    ),
+                    data.created_token
+                        .as_deref()
+                        .map_or_else(nothing, created_token),
+                    tokens(&data.tokens),
+                    form(
+                        [
+                            action("/settings/tokens"),
+                            class("flex flex-col"),
+                            id("create_token"),
+                            method("POST"),
+                        ],
+                        [
+                            h2(class("font-bold mb-2"), "Create a token"),
+                            label(for_("name"), "Name"),
+                            data.errors.view("name"),
+                            input([
+                                class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+                                id("name"),
+                                name("name"),
+                                placeholder("Browser extension"),
+                                required(""),
+                                type_("text"),
+                                value(&data.input.name),
+                            ]),
+                            label(
+                                class("mt-4"),
+                                [
+                                    input([
+                                        type_("checkbox"),
+                                        name("scope"),
+                                        value("write"),
+                                        (data.input.scope == Scope::Write)
+                                            .then(checked)
+                                            .into_attrs(),
+                                    ]),
+                                    span(
+                                        (),
+                                        " Allow creating, editing and deleting bookmarks and lists",
+                                    ),
+                                ],
+                            ),
+                            submit,
+                        ],
+                    ),
+                ],
+            ),
+        ],
+        &data.layout,
+    )
+}
+
+fn created_token(token: &str) -> Element {
+    div(
+        [
+            class("p-4 border rounded border-neutral-300"),
+            id("created_token"),
+        ],
+        [
+            p(
+                class("mb-2"),
+                "Copy your new token now. It won't be shown again.",
+            ),
+            code(class("block break-all select-all"), token),
+        ],
+    )
+}
+
+fn tokens(tokens: &[ApiToken]) -> Element {
+    if tokens.is_empty() {
+        return p(
+            class("text-neutral-400"),
+            "You haven't created any tokens yet.",
+        );
+    }
+
+    ul(
+        [class("flex flex-col gap-2"), id("tokens")],
+        tokens
+            .iter()
+            .map(|token| {
+                let last_used = token.last_used_at.map_or_else(
+                    || "never used".to_string(),
+                    |date| format!("last used on {}", content::format_date(date)),
+                );
+                li(
+                    class("flex items-center gap-4 p-4 border rounded border-neutral-700"),
+                    [
+                        div(
+                            class("grow"),
+                            [
+                                p(class("font-bold"), token.name.as_str()),
+                                p(
+                                    class("text-sm text-neutral-400"),
+                                    format!(
+                                        "{} access, created on {}, {last_used}",
+                                        scope_label(token.scope),
+                                        content::format_date(token.created_at)
+                                    ),
+                                ),
+                            ],
+                        ),
+                        form(
+                            [
+                                action(format!("/settings/tokens/{}/delete", token.id)),
+                                method("POST"),
+                            ],
+                            button(
+                                [
+                                    class("py-1.5 px-3 rounded border border-neutral-300"),
+                                    type_("submit"),
+                                ],
+                                "Revoke",
+                            ),
+                        ),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn scope_label(scope: Scope) -> &'static str {
+    match scope {
+        Scope::Read => "Read",
+        Scope::Write => "Write",
+    }
+}
@@ -9,7 +9,10 @@ use crate::{
 pub struct Data {
     pub layout: layout::Template,
     pub bookmark: db::Bookmark,
+    /// The snapshot to show, the newest one by default
     pub archive: Option<db::Archive>,
+    /// All snapshots of the bookmark, newest first
+    pub snapshots: Vec<db::archives::Snapshot>,
     pub backlinks: Vec<db::List>,
     pub username: String,
 }
@@ -19,10 +22,18 @@ pub fn view(
         layout,
         bookmark,
         archive,
+        snapshots,
         backlinks,
         username,
     }: Data,
 ) -> Element {
+    let is_owner = layout
+        .authed_info
+        .as_ref()
+        .is_some_and(|info| info.ap_user_id == bookmark.ap_user_id);
+    let shown_id = archive.as_ref().map(|archive| archive.id);
+    let latest = snapshots.first();
+
     layout::layout(
         fragment([
             header(
@@ -44,27 +55,61 @@ pub fn view(
                             ],
                         ),
                     ),
+                    notes_section(bookmark.notes.as_deref()),
                     backlink_section(&backlinks),
+                    snapshot_section(bookmark.id, &snapshots, shown_id, is_owner),
+                    if is_owner {
+                        owner_buttons(&bookmark)
+                    } else {
+                        nothing()
+                    },
                 ],
             ),
+            match (archive.as_ref(), latest) {
+                (Some(archive), Some(latest)) if archive.id != latest.id => {
+                    older_snapshot_notice(archive, &bookmark)
+                }
+                _ => nothing(),
+            },
             div(class("border-b border-black"), ()),
             div(class("border-b border-neutral-700"), ()),
             div(
                 id("archive-contents"),
-                archive_contents(
-                    archive.as_ref(),
-                    bookmark.id,
-                    layout
-                        .authed_info
-                        .as_ref()
-                        .is_some_and(|info| info.ap_user_id == bookmark.ap_user_id),
-                ),
+                archive_contents(archive.as_ref(), bookmark.id, is_owner),
             ),
         ]),
         &layout,
     )
 }
 
+fn owner_buttons(bookmark: &db::Bookmark) -> Element {
+    div(
+        class("flex flex-wrap gap-x-4 gap-y-2 mt-4 text-sm"),
+        [
+            a(
+                [
+                    class(
+                        "block px-4 py-1 border rounded hover:bg-neutral-800 border-neutral-700 \
+                         w-max",
+                    ),
+                    href(format!("/bookmarks/{}/edit", bookmark.id)),
+                ],
+                "Edit",
+            ),
+            a(
+                [
+                    class(
+                        "block px-4 py-1 border rounded hover:bg-neutral-800 border-neutral-700 \
+                         w-max",
+                    ),
+                    href(format!("/bookmarks/{}/delete", bookmark.id)),
+                ],
+                "Delete",
+            ),
+        ],
+    )
+}
+
 fn status(bookmark: &db::Bookmark, archive: Option<&db::Archive>, username: &str) -> Element {
     let archive_status = archive.map(|archive| match archive.status {
         db::archives::Status::Success => {
@@ -175,7 +220,145 @@ fn archive_contents(archive: Option<&db::Archive>, bookmark_id: Uuid, is_owner:
         );
     };
 
-    div(class("prose prose-invert px-4"), unsafe_raw_html(html))
+    fragment([
+        download_links(bookmark_id, archive.id),
+        div(class("prose prose-invert px-4"), unsafe_raw_html(html)),
+    ])
+}
+
+/// Download the snapshot to read it without ties.
+fn download_links(bookmark_id: Uuid, archive_id: Uuid) -> Element {
+    let path = format!("/bookmarks/{bookmark_id}/archives/{archive_id}");
+
+    p(
+        [
+            id("archive_downloads"),
+            class("px-4 pt-3 pb-1 text-sm text-neutral-400"),
+        ],
+        [
+            text("Download as "),
+            a(
+                [
+                    href(format!("{path}/archive.html")),
+                    class("hover:text-fuchsia-300"),
+                ],
+                "HTML",
+            ),
+            text(" or "),
+            a(
+                [
+                    href(format!("{path}/archive.warc")),
+                    class("hover:text-fuchsia-300"),
+                ],
+                "WARC",
+            ),
+        ],
+    )
+}
+
+fn older_snapshot_notice(archive: &db::Archive, bookmark: &db::Bookmark) -> Element {
+    p(
+        [
+            id("older_snapshot"),
+            class("px-4 py-2 text-sm bg-neutral-800 text-neutral-300"),
+        ],
+        [
+            text(format!(
+                "You're looking at a snapshot from {}. ",
+                content::format_date_time(archive.created_at)
+            )),
+            a(
+                [
+                    href(bookmark.path()),
+                    class("underline hover:text-fuchsia-300"),
+                ],
+                "Show the newest snapshot",
+            ),
+        ],
+    )
+}
+
+/// The archive history, with a link to each snapshot and to the changes since
+/// the previous successful snapshot.
+fn snapshot_section(
+    bookmark_id: Uuid,
+    snapshots: &[db::archives::Snapshot],
+    shown_id: Option<Uuid>,
+    is_owner: bool,
+) -> Element {
+    let latest_status = snapshots.first().map(|snapshot| snapshot.status);
+    let can_archive_again = is_owner && latest_status == Some(db::archives::Status::Success);
+    if snapshots.len() < 2 && !can_archive_again {
+        return nothing();
+    }
+
+    let items = snapshots.iter().enumerate().map(|(index, snapshot)| {
+        let previous_success = snapshots[index + 1..]
+            .iter()
+            .find(|older| older.status == db::archives::Status::Success);
+        let status = match snapshot.status {
+            db::archives::Status::Success => "archived",
+            db::archives::Status::Error => "failed",
+            db::archives::Status::Pending => "pending",
+        };
+
+        li(
+            class("flex flex-wrap gap-x-1"),
+            [
+                a(
+                    [
+                        href(format!("/bookmarks/{bookmark_id}/archives/{}", snapshot.id)),
+                        class(if Some(snapshot.id) == shown_id {
+                            "text-fuchsia-300"
+                        } else {
+                            "hover:text-fuchsia-300"
+                        }),
+                    ],
+                    content::format_date_time(snapshot.created_at),
+                ),
+                span(class("text-neutral-400"), status),
+                match previous_success {
+                    Some(previous) if snapshot.status == db::archives::Status::Success => {
+                        fragment([
+                            text(content::BULLET),
+                            a(
+                                [
+                                    href(format!(
+                                        "/bookmarks/{bookmark_id}/diff?from={}&to={}",
+                                        previous.id, snapshot.id
+                                    )),
+                                    class("text-neutral-400 hover:text-fuchsia-300"),
+                                ],
+                                "changes",
+                            ),
+                        ])
+                    }
+                    _ => nothing(),
+                },
+            ],
+        )
+    });
+
+    section(
+        [id("snapshots"), class("mt-4 text-sm")],
+        [
+            h2(class("font-bold mb-0.5 tracking-tight"), "Snapshots"),
+            ol(class("flex flex-col gap-0.5"), items.collect::<Vec<_>>()),
+            if can_archive_again {
+                div(class("mt-2"), archive_button(bookmark_id, "Archive again"))
+            } else {
+                nothing()
+            },
+        ],
+    )
+}
+
+fn notes_section(notes: Option<&str>) -> Element {
+    if notes.is_none() {
+        return nothing();
+    }
+
+    section(class("mt-4"), content::notes(notes))
 }
 
 fn backlink_section(backlinks: &[db::List]) -> Element {
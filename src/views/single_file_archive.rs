@@ -0,0 +1,94 @@
+use htmf::prelude_inline::*;
+
+use crate::{archive, views::content};
+
+/// Styles for reading the archive without ties' stylesheets.
+const STYLE: &str = "body { max-width: 42rem; margin: 2rem auto; padding: 0 1rem; font-family: \
+                     sans-serif; line-height: 1.6; color: #222; } img { max-width: 100%; height: \
+                     auto; } pre { overflow-x: auto; } header p { color: #666; font-size: 0.9rem; \
+                     } section + section { margin-top: 4rem; }";
+
+pub struct Data {
+    /// Title of the list when exporting several archives, `None` for a single
+    /// bookmark
+    pub list_title: Option<String>,
+    pub exports: Vec<archive::Export>,
+}
+
+/// A standalone HTML document containing archives along with their images.
+pub fn view(
+    Data {
+        list_title,
+        exports,
+    }: Data,
+) -> Element {
+    let document_title = list_title
+        .clone()
+        .or_else(|| exports.first().map(|export| export.bookmark.title.clone()))
+        .unwrap_or_default();
+
+    htmf::declare::document().with(html(
+        attr("lang", "en"),
+        [
+            head(
+                (),
+                [
+                    meta(attr("charset", "utf-8")),
+                    meta([
+                        name("viewport"),
+                        content("width=device-width,initial-scale=1"),
+                    ]),
+                    title_tag((), document_title),
+                    unsafe_raw_html(format!("<style>{STYLE}</style>")),
+                ],
+            ),
+            body(
+                (),
+                [
+                    match &list_title {
+                        Some(title) => h1((), title),
+                        None => nothing(),
+                    },
+                    fragment(
+                        exports
+                            .iter()
+                            .map(|export| archive_section(export, list_title.is_some()))
+                            .collect::<Vec<_>>(),
+                    ),
+                ],
+            ),
+        ],
+    ))
+}
+
+fn archive_section(export: &archive::Export, is_part_of_list: bool) -> Element {
+    let title = if is_part_of_list {
+        h2((), &export.bookmark.title)
+    } else {
+        h1((), &export.bookmark.title)
+    };
+
+    section(
+        id(format!("bookmark-{}", export.bookmark.id)),
+        [
+            header(
+                (),
+                [
+                    title,
+                    p(
+                        (),
+                        [
+                            text("Archived from "),
+                            a(href(&export.bookmark.url), &export.bookmark.url),
+                            text(format!(
+                                " on {}",
+                                content::format_date_time(export.archive.created_at)
+                            )),
+                        ],
+                    ),
+                ],
+            ),
+            unsafe_raw_html(export.self_contained_html()),
+        ],
+    )
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use htmf::prelude_inline::*;
+use uuid::Uuid;
+
+use super::{content, layout};
+use crate::db;
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub bookmarks: Vec<db::Bookmark>,
+    /// The authors of `bookmarks`, by their ID
+    pub authors: HashMap<Uuid, db::ApUser>,
+    pub muted: Vec<db::ApUser>,
+    /// Missing on the last page
+    pub next_page: Option<String>,
+}
+
+pub fn view(data: &Data) -> Element {
+    let next_page = data.next_page.as_ref().map_or(nothing(), |next_page| {
+        a(
+            [
+                class(
+                    "block p-4 border-t border-neutral-700 text-neutral-400 hover:text-neutral-200",
+                ),
+                href(next_page),
+                id("next_page"),
+            ],
+            "Older bookmarks",
+        )
+    });
+
+    layout::layout(
+        [
+            header(
+                class("px-4 pt-3 pb-4 bg-neutral-900 border-b border-black"),
+                [
+                    h1(class("text-xl font-bold"), "Timeline"),
+                    p(
+                        class("mt-2 text-sm text-neutral-400"),
+                        "Bookmarks of the people you follow, newest first.",
+                    ),
+                ],
+            ),
+            bookmarks(data),
+            next_page,
+            muted(&data.muted),
+        ],
+        &data.layout,
+    )
+}
+
+fn bookmarks(data: &Data) -> Element {
+    if data.bookmarks.is_empty() {
+        let profile_link = data.layout.authed_info.as_ref().map_or(nothing(), |info| {
+            a(
+                [
+                    href(format!("/user/{}", info.username)),
+                    class("text-neutral-200 hover:text-fuchsia-300"),
+                ],
+                "your profile",
+            )
+        });
+        return p(
+            class("p-4 text-neutral-400"),
+            [
+                text("Nothing here yet. Find people to follow on "),
+                profile_link,
+                text("."),
+            ],
+        );
+    }
+
+    ul(
+        id("timeline"),
+        data.bookmarks
+            .iter()
+            .map(|bookmark| bookmark_entry(bookmark, data.authors.get(&bookmark.ap_user_id)))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn bookmark_entry(bookmark: &db::Bookmark, author: Option<&db::ApUser>) -> Element {
+    let author_link = author.map_or(nothing(), |author| {
+        a(
+            [
+                href(format!("/user/{}", author.handle())),
+                class("hover:text-fuchsia-300"),
+            ],
+            author.display_name.as_deref().unwrap_or(&author.username),
+        )
+    });
+    let mute_button = author.map_or(nothing(), |author| {
+        form(
+            [
+                action(format!("/timeline/authors/{}/mute", author.id)),
+                method("POST"),
+            ],
+            button(
+                [
+                    class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+                    type_("submit"),
+                ],
+                "Mute author",
+            ),
+        )
+    });
+
+    li(
+        class("flex flex-wrap items-end justify-between gap-2 p-4 border-t border-neutral-700"),
+        [
+            div(
+                class("overflow-hidden"),
+                [
+                    p(
+                        class("text-sm text-neutral-400"),
+                        [
+                            author_link,
+                            text(" ∙ "),
+                            span((), content::format_date(bookmark.created_at)),
+                        ],
+                    ),
+                    a(
+                        [
+                            href(&bookmark.url),
+                            class(
+                                "block overflow-hidden leading-8 text-orange-100 \
+                                 hover:text-orange-300 text-ellipsis whitespace-nowrap",
+                            ),
+                        ],
+                        &bookmark.title,
+                    ),
+                    content::link_url(&bookmark.url),
+                    content::notes(bookmark.notes.as_deref()),
+                ],
+            ),
+            div(
+                class("flex justify-end gap-2 grow text-neutral-300"),
+                [
+                    form(
+                        [
+                            action(format!("/timeline/bookmarks/{}/save", bookmark.id)),
+                            method("POST"),
+                        ],
+                        button(
+                            [
+                                class(
+                                    "px-4 py-1 border rounded border-neutral-700 \
+                                     hover:bg-neutral-700",
+                                ),
+                                type_("submit"),
+                            ],
+                            "Save to my lists",
+                        ),
+                    ),
+                    mute_button,
+                ],
+            ),
+        ],
+    )
+}
+
+fn muted(muted: &[db::ApUser]) -> Element {
+    if muted.is_empty() {
+        return nothing();
+    }
+
+    section(
+        [class("px-4 py-4 border-t border-neutral-700"), id("muted")],
+        [
+            h2(class("font-bold mb-2 tracking-tight"), "Muted"),
+            ul(
+                class("flex flex-col gap-2"),
+                muted
+                    .iter()
+                    .map(|author| {
+                        li(
+                            class("flex items-center gap-4"),
+                            [
+                                a(
+                                    [
+                                        href(format!("/user/{}", author.handle())),
+                                        class("grow hover:text-fuchsia-300"),
+                                    ],
+                                    author.display_name.as_deref().unwrap_or(&author.username),
+                                ),
+                                form(
+                                    [
+                                        action(format!("/timeline/authors/{}/unmute", author.id)),
+                                        method("POST"),
+                                    ],
+                                    button(
+                                        [
+                                            class(
+                                                "px-4 py-1 border rounded border-neutral-700 \
+                                                 hover:bg-neutral-700",
+                                            ),
+                                            type_("submit"),
+                                        ],
+                                        "Unmute",
+                                    ),
+                                ),
+                            ],
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        ],
+    )
+}
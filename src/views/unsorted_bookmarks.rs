@@ -43,6 +43,7 @@ fn bookmark_entry(bookmark: &Bookmark) -> Element {
             ])
             .with(&bookmark.title),
             content::link_url(&bookmark.url),
+            content::notes(bookmark.notes.as_deref()),
         ]),
         div(class("flex justify-end gap-2 grow text-neutral-300")).with([a([
             href(format!("/links/create?dest_id={bookmark_id}")),
@@ -52,7 +53,8 @@ fn bookmark_entry(bookmark: &Bookmark) -> Element {
             text("Add to list"),
             a([
                 attr("hx-delete", format!("/bookmarks/{bookmark_id}")),
-                href(format!("/bookmarks/{bookmark_id}")),
+                attr("hx-confirm", format!(r#"Delete "{}"?"#, bookmark.title)),
+                href(format!("/bookmarks/{bookmark_id}/delete")),
                 class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-600"),
             ])
             .with([text("Delete")]),